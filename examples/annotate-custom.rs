@@ -1,4 +1,3 @@
-use annotate_snippets::{AnnotationKind, Group, Level, Renderer, Snippet};
 use serde::{de::Visitor, Deserialize};
 
 #[derive(Debug)]
@@ -129,21 +128,7 @@ fn main() {
 	let thing = match thing {
 		Ok(thing) => thing,
 		Err(err) => {
-			let code = err.code().to_string();
-			let message = if let Some(span) = err.span() {
-				Level::ERROR.primary_title(code.as_str()).element(
-					Snippet::source(WITH_ERROR)
-						.path("test/test.mf")
-						.fold(true)
-						.annotation(AnnotationKind::Primary.span(span.range())),
-				)
-			} else {
-				Group::with_title(Level::ERROR.primary_title(code.as_str()))
-			};
-
-			let renderer = Renderer::styled();
-			anstream::println!("{}", renderer.render(&[message]));
-
+			eprintln!("{}", err.render(WITH_ERROR, "test/test.mf"));
 			return;
 		}
 	};
@@ -0,0 +1,366 @@
+//! a language server for mayfig files, that speaks the lsp over stdio
+//!
+//! publishes the errors of the documents as diagnostics, and provides
+//! document symbols for categories, folding ranges and formatting.
+
+use lsp_server::{Connection, ExtractError, Message, Notification, Request, Response};
+use lsp_types::{
+	notification::{
+		DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+		PublishDiagnostics,
+	},
+	request::{DocumentSymbolRequest, FoldingRangeRequest, Formatting, Request as _},
+	Diagnostic, DiagnosticSeverity, DocumentSymbol, DocumentSymbolResponse, FoldingRange,
+	FoldingRangeKind, InitializeParams, OneOf, Position as LspPosition, PublishDiagnosticsParams,
+	Range, ServerCapabilities, SymbolKind, TextDocumentSyncCapability, TextDocumentSyncKind,
+	TextEdit, Uri,
+};
+use mayfig::{
+	de::FsResolver,
+	error::{Position, Span},
+	syntax::{NodeKind, SyntaxNode},
+	Deserializer, Error, Value,
+};
+use std::{collections::HashMap, path::PathBuf, process::ExitCode};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+fn main() -> ExitCode {
+	match run() {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("error: {err}");
+			ExitCode::FAILURE
+		}
+	}
+}
+
+fn run() -> Result<(), BoxError> {
+	let (connection, threads) = Connection::stdio();
+
+	let capabilities = ServerCapabilities {
+		text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+		document_symbol_provider: Some(OneOf::Left(true)),
+		folding_range_provider: Some(true.into()),
+		document_formatting_provider: Some(OneOf::Left(true)),
+		..ServerCapabilities::default()
+	};
+
+	let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+	let _params = serde_json::from_value::<InitializeParams>(params)?;
+
+	let mut server = Server {
+		connection,
+		documents: HashMap::new(),
+	};
+	server.run()?;
+
+	drop(server);
+	threads.join()?;
+
+	Ok(())
+}
+
+struct Server {
+	connection: Connection,
+	/// the text of the open documents
+	documents: HashMap<Uri, String>,
+}
+
+impl Server {
+	fn run(&mut self) -> Result<(), BoxError> {
+		while let Ok(message) = self.connection.receiver.recv() {
+			match message {
+				Message::Request(req) => {
+					if self.connection.handle_shutdown(&req)? {
+						return Ok(());
+					}
+
+					let response = self.request(req);
+					self.connection.sender.send(response.into())?;
+				}
+				Message::Notification(not) => self.notification(not)?,
+				Message::Response(_) => {}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn request(&self, req: Request) -> Response {
+		match req.method.as_str() {
+			DocumentSymbolRequest::METHOD => handle::<DocumentSymbolRequest>(req, |params| {
+				let text = self.documents.get(&params.text_document.uri)?;
+				let symbols = symbols(text, mayfig::syntax::parse(text).root());
+				Some(DocumentSymbolResponse::Nested(symbols))
+			}),
+			FoldingRangeRequest::METHOD => handle::<FoldingRangeRequest>(req, |params| {
+				let text = self.documents.get(&params.text_document.uri)?;
+				let mut ranges = Vec::new();
+				folding_ranges(mayfig::syntax::parse(text).root(), &mut ranges);
+				Some(ranges)
+			}),
+			Formatting::METHOD => handle::<Formatting>(req, |params| {
+				let text = self.documents.get(&params.text_document.uri)?;
+				format(text, params.options.insert_spaces, params.options.tab_size)
+			}),
+			method => {
+				let code = lsp_server::ErrorCode::MethodNotFound as i32;
+				let message = format!("unknown method {method}");
+				Response::new_err(req.id, code, message)
+			}
+		}
+	}
+
+	fn notification(&mut self, not: Notification) -> Result<(), BoxError> {
+		match not.method.as_str() {
+			DidOpenTextDocument::METHOD => {
+				let params = not
+					.extract::<lsp_types::DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)?;
+				let document = params.text_document;
+				self.documents.insert(document.uri.clone(), document.text);
+				self.publish(document.uri, Some(document.version))?;
+			}
+			DidChangeTextDocument::METHOD => {
+				let params = not.extract::<lsp_types::DidChangeTextDocumentParams>(
+					DidChangeTextDocument::METHOD,
+				)?;
+				let document = params.text_document;
+				// the documents are synced in full, so the last change is the whole text
+				if let Some(change) = params.content_changes.into_iter().last() {
+					self.documents.insert(document.uri.clone(), change.text);
+					self.publish(document.uri, Some(document.version))?;
+				}
+			}
+			DidCloseTextDocument::METHOD => {
+				let params = not.extract::<lsp_types::DidCloseTextDocumentParams>(
+					DidCloseTextDocument::METHOD,
+				)?;
+				let uri = params.text_document.uri;
+				self.documents.remove(&uri);
+				self.send_diagnostics(uri, Vec::new(), None)?;
+			}
+			_ => {}
+		}
+
+		Ok(())
+	}
+
+	/// publish the diagnostics of the document at `uri`
+	fn publish(&self, uri: Uri, version: Option<i32>) -> Result<(), BoxError> {
+		let Some(text) = self.documents.get(&uri) else {
+			return Ok(());
+		};
+
+		let diagnostics = diagnostics(text, file_path(&uri));
+		self.send_diagnostics(uri, diagnostics, version)
+	}
+
+	fn send_diagnostics(
+		&self,
+		uri: Uri,
+		diagnostics: Vec<Diagnostic>,
+		version: Option<i32>,
+	) -> Result<(), BoxError> {
+		let params = PublishDiagnosticsParams {
+			uri,
+			diagnostics,
+			version,
+		};
+
+		let not = Notification::new(PublishDiagnostics::METHOD.to_owned(), params);
+		self.connection.sender.send(not.into())?;
+		Ok(())
+	}
+}
+
+/// respond to the request `R` with the result of `f`
+fn handle<R: lsp_types::request::Request>(
+	req: Request,
+	f: impl FnOnce(R::Params) -> R::Result,
+) -> Response {
+	let id = req.id.clone();
+	match req.extract::<R::Params>(R::METHOD) {
+		Ok((id, params)) => Response::new_ok(id, f(params)),
+		Err(ExtractError::JsonError { method, error }) => {
+			let code = lsp_server::ErrorCode::InvalidParams as i32;
+			let message = format!("invalid params for {method}: {error}");
+			Response::new_err(id, code, message)
+		}
+		Err(ExtractError::MethodMismatch(req)) => {
+			let code = lsp_server::ErrorCode::MethodNotFound as i32;
+			let message = format!("unknown method {}", req.method);
+			Response::new_err(id, code, message)
+		}
+	}
+}
+
+/// the path of the file at `uri`, if it is a `file://` uri
+fn file_path(uri: &Uri) -> Option<PathBuf> {
+	if uri.scheme().is_none_or(|scheme| scheme.as_str() != "file") {
+		return None;
+	}
+
+	let path = uri.path().as_estr().decode().into_string().ok()?;
+	Some(PathBuf::from(path.as_ref()))
+}
+
+/// the syntax errors of the `text`, or the first error while deserializing it,
+/// if the text has no syntax errors
+///
+/// includes are resolved relative to the file at `path`, or reported as errors if it's `None`.
+fn diagnostics(text: &str, path: Option<PathBuf>) -> Vec<Diagnostic> {
+	let parse = mayfig::syntax::parse(text);
+	if !parse.errors().is_empty() {
+		let errors = parse.errors().iter();
+		return errors.map(|err| diagnostic(text, err)).collect();
+	}
+
+	let mut de = Deserializer::from_str(text);
+	if let Some(path) = path {
+		de.set_resolver(FsResolver::for_file(path));
+	}
+
+	let Err(errors) = de.deserialize_recover::<Value>() else {
+		return Vec::new();
+	};
+
+	let diagnostics = errors.iter().map(|err| {
+		let source = match err.span() {
+			Some(Span::Point(pos) | Span::Span(pos, _)) => pos.source,
+			None => 0,
+		};
+
+		let mut diagnostic = diagnostic(text, err);
+		// errors in included sources can't be shown in their source
+		if let Some(name) = de.source_name(source) {
+			diagnostic.range = Range::default();
+			diagnostic.message = format!("{name}: {}", diagnostic.message);
+		}
+
+		diagnostic
+	});
+
+	diagnostics.collect()
+}
+
+fn diagnostic(text: &str, err: &Error) -> Diagnostic {
+	let range = err.span().map(|span| range(text, span)).unwrap_or_default();
+	let message = match err.path() {
+		Some(path) => format!("{}\nin `{path}`", err.code()),
+		None => err.code().to_string(),
+	};
+
+	Diagnostic {
+		range,
+		severity: Some(DiagnosticSeverity::ERROR),
+		source: Some("mayfig".to_owned()),
+		message,
+		..Diagnostic::default()
+	}
+}
+
+/// convert a [`Position`] in the `text` to an lsp position
+///
+/// the lsp counts columns in utf16 code units, instead of chars like [`Position::col`].
+fn position(text: &str, pos: Position) -> LspPosition {
+	// only the line up to the position is counted, not the lines before it
+	let before = text.get(..pos.index).unwrap_or_default();
+	let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+	let character = before[line_start..]
+		.chars()
+		.map(char::len_utf16)
+		.sum::<usize>();
+
+	LspPosition::new(pos.line as u32 - 1, character as u32)
+}
+
+/// convert a [`Span`] in the `text` to an lsp range
+///
+/// a point is extended to the char after it, if it's not a newline.
+fn range(text: &str, span: Span) -> Range {
+	match span {
+		Span::Point(pos) => {
+			let start = position(text, pos);
+			let next = text.get(pos.index..).and_then(|rest| rest.chars().next());
+			let end = match next {
+				Some(ch) if ch != '\n' => {
+					LspPosition::new(start.line, start.character + ch.len_utf16() as u32)
+				}
+				_ => start,
+			};
+
+			Range::new(start, end)
+		}
+		Span::Span(start, end) => Range::new(position(text, start), position(text, end)),
+	}
+}
+
+/// the document symbols of the categories in the `node`
+#[expect(deprecated)]
+fn symbols(text: &str, node: &SyntaxNode) -> Vec<DocumentSymbol> {
+	node.entries()
+		.filter(|entry| entry.kind() == NodeKind::Category)
+		.filter_map(|category| {
+			let key = category.key()?;
+			let children = category.value().map(|map| symbols(text, map));
+
+			Some(DocumentSymbol {
+				name: category.name().unwrap_or_else(|| key.to_string()),
+				detail: None,
+				kind: SymbolKind::OBJECT,
+				tags: None,
+				deprecated: None,
+				range: range(text, category.span()),
+				selection_range: range(text, key.span()),
+				children,
+			})
+		})
+		.collect()
+}
+
+/// collect the folding ranges of the maps and sequences in the `node`
+fn folding_ranges(node: &SyntaxNode, ranges: &mut Vec<FoldingRange>) {
+	if let (NodeKind::Map | NodeKind::Seq, Span::Span(start, end)) = (node.kind(), node.span()) {
+		// keep the line with the closing bracket visible
+		let (start_line, end_line) = (start.line as u32 - 1, end.line as u32 - 1);
+		if end_line > start_line + 1 {
+			ranges.push(FoldingRange {
+				start_line,
+				end_line: end_line - 1,
+				kind: Some(FoldingRangeKind::Region),
+				..FoldingRange::default()
+			});
+		}
+	}
+
+	for child in node.nodes() {
+		folding_ranges(child, ranges);
+	}
+}
+
+/// format the `text`, with the indentation of the editor
+///
+/// returns `None` if the text has syntax errors.
+fn format(text: &str, insert_spaces: bool, tab_size: u32) -> Option<Vec<TextEdit>> {
+	let indent = if insert_spaces {
+		" ".repeat(tab_size as usize)
+	} else {
+		"\t".to_owned()
+	};
+
+	let formatted = mayfig::fmt::Formatter::with_indent(&indent)
+		.format(text)
+		.ok()?;
+	if formatted == text {
+		return Some(Vec::new());
+	}
+
+	// replace the whole document
+	let lines = text.split('\n').count();
+	let last = text.rsplit('\n').next().unwrap_or_default();
+	let end = LspPosition::new(lines as u32 - 1, last.encode_utf16().count() as u32);
+	let range = Range::new(LspPosition::new(0, 0), end);
+
+	Some(vec![TextEdit::new(range, formatted)])
+}
@@ -0,0 +1,301 @@
+//! the `mayfig` command line tool, to check, format and convert mayfig files
+
+use clap::{Parser, Subcommand, ValueEnum};
+use mayfig::{
+	de::FsResolver,
+	error::{Path, Report, Segment},
+	Deserializer, Error, Value,
+};
+use std::{
+	io::{IsTerminal, Read},
+	path::{Path as FsPath, PathBuf},
+	process::ExitCode,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "check, format and convert mayfig files")]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// parse files and report every error
+	///
+	/// reads from stdin if no files are given.
+	Check { files: Vec<PathBuf> },
+	/// format files in place
+	///
+	/// formats stdin to stdout if no files are given.
+	Fmt {
+		/// don't write anything, fail if a file isn't formatted
+		#[arg(long)]
+		check: bool,
+		files: Vec<PathBuf>,
+	},
+	/// print the value at a path, like `input.keyboard.repeat-rate` or `size[0]`
+	///
+	/// strings are printed without quotes, other values as mayfig.
+	Get {
+		path: Path,
+		/// the file to read, reads from stdin if missing
+		file: Option<PathBuf>,
+	},
+	/// convert a file between mayfig and json, and print it
+	Convert {
+		/// the format to convert to
+		///
+		/// defaults to mayfig for `.json` files, and to json otherwise.
+		#[arg(long, value_enum)]
+		to: Option<Format>,
+		/// the file to read, reads from stdin if missing
+		file: Option<PathBuf>,
+	},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+	Mayfig,
+	Json,
+}
+
+/// the errors have already been reported
+struct Failed;
+
+/// a file or stdin, with its text
+struct Input {
+	/// the name used in error messages
+	name: String,
+	text: String,
+	/// the path of the file, or `None` for stdin
+	path: Option<PathBuf>,
+}
+
+impl Input {
+	fn read(file: Option<&FsPath>) -> Result<Self, Failed> {
+		let Some(path) = file.filter(|path| *path != FsPath::new("-")) else {
+			let mut text = String::new();
+			if let Err(err) = std::io::stdin().read_to_string(&mut text) {
+				eprintln!("error: failed to read stdin: {err}");
+				return Err(Failed);
+			}
+
+			return Ok(Input {
+				name: "<stdin>".to_owned(),
+				text,
+				path: None,
+			});
+		};
+
+		let name = path.display().to_string();
+		let text = std::fs::read_to_string(path).map_err(|err| {
+			eprintln!("error: failed to read {name}: {err}");
+			Failed
+		})?;
+
+		Ok(Input {
+			name,
+			text,
+			path: Some(path.to_owned()),
+		})
+	}
+
+	/// print `err` with a snippet of the input
+	fn report(&self, err: &Error) {
+		print_report(err.render(&self.text, &self.name));
+	}
+
+	/// deserialize the input into a [`Value`], resolving includes relative to the input,
+	/// and report every error
+	fn load(&self) -> Result<Value, Failed> {
+		let mut de = Deserializer::from_str(&self.text);
+		de.set_resolver(match &self.path {
+			Some(path) => FsResolver::for_file(path),
+			None => FsResolver::new("."),
+		});
+
+		de.deserialize_recover::<Value>().map_err(|errors| {
+			// errors in included files are shown with their source
+			for err in &errors {
+				print_report(err.render_with(&de, &self.text, &self.name));
+			}
+			Failed
+		})
+	}
+}
+
+/// print the `report` to stderr, colored if stderr is a terminal and `NO_COLOR` is not set
+fn print_report(mut report: Report) {
+	let stderr = std::io::stderr();
+	report.set_styled(stderr.is_terminal() && std::env::var_os("NO_COLOR").is_none());
+	eprintln!("{report}");
+}
+
+fn main() -> ExitCode {
+	let cli = Cli::parse();
+	let result = match cli.command {
+		Command::Check { files } => check(&files),
+		Command::Fmt { check, files } => fmt(&files, check),
+		Command::Get { path, file } => get(&path, file.as_deref()),
+		Command::Convert { to, file } => convert(to, file.as_deref()),
+	};
+
+	match result {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(Failed) => ExitCode::FAILURE,
+	}
+}
+
+/// read every file, or stdin if there are none, and call `f` with each of them
+fn each(files: &[PathBuf], mut f: impl FnMut(Input) -> Result<(), Failed>) -> Result<(), Failed> {
+	if files.is_empty() {
+		return f(Input::read(None)?);
+	}
+
+	let mut result = Ok(());
+	for file in files {
+		if Input::read(Some(file)).and_then(&mut f).is_err() {
+			result = Err(Failed);
+		}
+	}
+
+	result
+}
+
+fn check(files: &[PathBuf]) -> Result<(), Failed> {
+	each(files, |input| {
+		let parse = mayfig::syntax::parse(&input.text);
+		if !parse.errors().is_empty() {
+			for err in parse.errors() {
+				input.report(err);
+			}
+
+			return Err(Failed);
+		}
+
+		input.load().map(drop)
+	})
+}
+
+fn fmt(files: &[PathBuf], check: bool) -> Result<(), Failed> {
+	each(files, |input| {
+		let formatted = mayfig::fmt::format(&input.text).map_err(|err| {
+			input.report(&err);
+			Failed
+		})?;
+
+		let Some(path) = &input.path else {
+			if check && formatted != input.text {
+				eprintln!("{} is not formatted", input.name);
+				return Err(Failed);
+			} else if !check {
+				print!("{formatted}");
+			}
+
+			return Ok(());
+		};
+
+		if formatted == input.text {
+			Ok(())
+		} else if check {
+			eprintln!("{} is not formatted", input.name);
+			Err(Failed)
+		} else {
+			std::fs::write(path, formatted).map_err(|err| {
+				eprintln!("error: failed to write {}: {err}", input.name);
+				Failed
+			})
+		}
+	})
+}
+
+fn get(path: &Path, file: Option<&FsPath>) -> Result<(), Failed> {
+	let input = Input::read(file)?;
+	let value = input.load()?;
+
+	let Some(value) = lookup(&value, path.segments()) else {
+		eprintln!("error: path {path} doesn't exist in {}", input.name);
+		return Err(Failed);
+	};
+
+	match value {
+		Value::String(str) => println!("{str}"),
+		value => {
+			let out = mayfig::to_string(value).map_err(|err| {
+				eprintln!("error: {err}");
+				Failed
+			})?;
+			println!("{}", out.trim_end());
+		}
+	}
+
+	Ok(())
+}
+
+/// look up the value at the `segments` in `value`
+fn lookup<'v>(value: &'v Value, segments: &[Segment]) -> Option<&'v Value> {
+	let Some((segment, rest)) = segments.split_first() else {
+		return Some(value);
+	};
+
+	let value = match (value, segment) {
+		(Value::Map(map), Segment::Key(key)) => map
+			.iter()
+			.find(|(k, _)| is_key(k, key))
+			.map(|(_, value)| value)?,
+		(Value::Seq(seq) | Value::Tagged(_, seq), Segment::Index(idx)) => seq.get(*idx)?,
+		_ => return None,
+	};
+
+	lookup(value, rest)
+}
+
+/// if the map key `value` is named `key`
+///
+/// complex keys are named by their first value, like in [`Segment::Key`].
+fn is_key(value: &Value, key: &str) -> bool {
+	match value {
+		Value::String(str) => str == key,
+		Value::Number(num) => num.to_string() == key,
+		Value::Bool(bool) => bool.to_string() == key,
+		Value::Seq(seq) => seq.first().is_some_and(|first| is_key(first, key)),
+		Value::Tagged(tag, _) => tag == key,
+		Value::Map(_) => false,
+	}
+}
+
+fn convert(to: Option<Format>, file: Option<&FsPath>) -> Result<(), Failed> {
+	let input = Input::read(file)?;
+	let is_json = input
+		.path
+		.as_deref()
+		.and_then(FsPath::extension)
+		.is_some_and(|ext| ext == "json");
+	let to = to.unwrap_or(if is_json {
+		Format::Mayfig
+	} else {
+		Format::Json
+	});
+
+	let out = match to {
+		Format::Json => {
+			let value = input.load()?;
+			serde_json::to_string_pretty(&value).map_err(|err| err.to_string())
+		}
+		Format::Mayfig => serde_json::from_str::<serde_json::Value>(&input.text)
+			.map_err(|err| format!("failed to parse {}: {err}", input.name))
+			.and_then(|value| mayfig::to_string(&value).map_err(|err| err.to_string())),
+	};
+
+	match out {
+		Ok(out) => {
+			println!("{}", out.trim_end());
+			Ok(())
+		}
+		Err(err) => {
+			eprintln!("error: {err}");
+			Err(Failed)
+		}
+	}
+}
@@ -1,34 +1,425 @@
 //! deserialize mayfig into a rust data structure.
 
 use self::{
-	access::{MapAcc, SeqAcc, TopMapAcc},
+	access::{MapAcc, MapState, Merge, Scan, SeqAcc, SpannedAcc},
+	include::Sources,
 	r#enum::TaggedEnumValueAcc,
-	read::{Read, Ref, StrRead},
+	read::{IoRead, Read, Ref, SliceRead, StrRead},
+	recover::{Frame, Key, Recovery},
+	variable::{Kind, Scope, Variable},
 };
-use crate::error::{Error, ErrorCode, Span};
-use serde_core::forward_to_deserialize_any;
-use std::borrow::Cow;
+use crate::{
+	error::{Error, ErrorCode, Path, Position, Segment, Span},
+	spanned,
+};
+use serde_core::de::{Deserialize, Error as _, IgnoredAny};
+use std::{borrow::Cow, collections::HashMap};
 
 mod access;
 mod r#enum;
+mod env;
+pub(crate) mod include;
 mod map;
-mod read;
+pub(crate) mod read;
+mod recover;
+pub(crate) mod variable;
+
+pub use env::{Environment, SystemEnvironment};
+pub use include::{FsResolver, MemoryResolver, Resolver, Source};
 
 /// a mayfig deseralizer.
 pub struct Deserializer<R> {
-	read: R,
+	read: Sources<R>,
 	indent: usize,
 	scratch: String,
+	/// the path to the value that is currently being deserialized
+	path: Vec<Segment>,
+	key: KeyCapture,
+	/// the state of recovering from errors, only while recovering
+	recover: Option<Box<Recovery>>,
+	unused: Unused,
+	duplicate_keys: DuplicateKeys,
+	merge_categories: bool,
+	/// whether a map is being scanned for duplicate keys
+	scanning: bool,
+	/// the repeated categories to merge into the next category
+	merge: Option<Merge>,
+	/// the scans of nested maps, by their start
+	scans: HashMap<Position, Scan>,
+	/// the variables that are in scope, innermost last
+	variables: Vec<Variable>,
+	/// the environment to expand strings with, if expansion is enabled
+	environment: Option<Box<dyn Environment>>,
 }
 
 impl<'de, R: Read<'de>> Deserializer<R> {
 	fn new(read: R) -> Self {
 		Deserializer {
-			read,
+			read: Sources::new(read),
 			indent: 0,
 			scratch: String::new(),
+			path: Vec::new(),
+			key: KeyCapture::default(),
+			recover: None,
+			unused: Unused::default(),
+			duplicate_keys: DuplicateKeys::default(),
+			merge_categories: false,
+			scanning: false,
+			merge: None,
+			scans: HashMap::new(),
+			variables: Vec::new(),
+			environment: None,
+		}
+	}
+
+	/// set how keys that occur more than once in the same map are handled
+	///
+	/// the default is [`DuplicateKeys::Allow`].
+	pub fn set_duplicate_keys(&mut self, duplicate_keys: DuplicateKeys) {
+		self.duplicate_keys = duplicate_keys;
+	}
+
+	/// set whether categories that occur more than once in the same map
+	/// are deep merged into the first one, instead of being duplicate keys
+	///
+	/// ```
+	/// # use std::collections::HashMap;
+	/// # use serde::Deserialize;
+	/// let input = "bind {\n\tmod+q = \"quit\"\n}\nbind {\n\tmod+t = \"kitty\"\n}\n";
+	///
+	/// let mut de = mayfig::Deserializer::from_str(input);
+	/// de.set_merge_categories(true);
+	///
+	/// let config = HashMap::<String, HashMap<String, String>>::deserialize(&mut de).unwrap();
+	/// assert_eq!(config["bind"].len(), 2);
+	/// ```
+	pub fn set_merge_categories(&mut self, merge_categories: bool) {
+		self.merge_categories = merge_categories;
+	}
+
+	/// set the [`Resolver`] that loads the sources of `include` directives
+	///
+	/// without a resolver every `include` directive is an error.
+	pub fn set_resolver(&mut self, resolver: impl Resolver + 'static) {
+		self.read.set_resolver(Box::new(resolver));
+	}
+
+	/// expand environment variables in quoted strings, with the values of `environment`
+	///
+	/// `${VAR}` is replaced with the value of `VAR`, `${VAR:-default}` with `default`
+	/// if `VAR` is unset or empty, and a leading `~/` with the home directory.
+	/// `$${` is a literal `${`, and raw strings in backticks are not expanded.
+	///
+	/// ```
+	/// # use std::collections::HashMap;
+	/// # use serde::Deserialize;
+	/// let env = HashMap::from([("HOME".to_owned(), "/home/may".to_owned())]);
+	///
+	/// let mut de = mayfig::Deserializer::from_str("xkb-file = \"~/.config/may.xkb\"");
+	/// de.set_environment(env);
+	///
+	/// let config = HashMap::<String, String>::deserialize(&mut de).unwrap();
+	/// assert_eq!(config["xkb-file"], "/home/may/.config/may.xkb");
+	/// ```
+	pub fn set_environment(&mut self, environment: impl Environment + 'static) {
+		self.environment = Some(Box::new(environment));
+	}
+
+	/// deserialize a type `T`, collecting all errors
+	///
+	/// after an error, the rest of the map entry it happened in is skipped,
+	/// so that the rest of the input is still validated. errors that only
+	/// happened because an entry was skipped, like a missing field, are not
+	/// reported.
+	///
+	/// ```
+	/// # use std::collections::HashMap;
+	/// let mut de = mayfig::Deserializer::from_reader("a = 1\nb = x\nc = y\n".as_bytes());
+	/// let errors = de.deserialize_recover::<HashMap<String, u32>>().unwrap_err();
+	///
+	/// let paths = errors.iter().map(|err| err.path().unwrap().to_string());
+	/// assert_eq!(paths.collect::<Vec<_>>(), ["b", "c"]);
+	/// ```
+	///
+	/// # errors
+	///
+	/// this returns every error that was found, if there was any, in order of
+	/// discovery. see [`from_str`] for the possible errors.
+	pub fn deserialize_recover<T>(&mut self) -> Result<T, Vec<Error>>
+	where
+		T: Deserialize<'de>,
+	{
+		self.recover = Some(Box::default());
+		let val = self.resume::<T>();
+		let errors = self.recover.take().map(|recover| recover.errors);
+
+		match (val, errors.unwrap_or_default()) {
+			(Some(val), errors) if errors.is_empty() => Ok(val),
+			(_, errors) => Err(errors),
+		}
+	}
+
+	/// deserialize `T` until it succeeds, resuming after every error that unwound to the top
+	fn resume<T>(&mut self) -> Option<T>
+	where
+		T: Deserialize<'de>,
+	{
+		loop {
+			let val = T::deserialize(&mut *self);
+			let recover = self.recover.as_deref_mut()?;
+			match val {
+				Ok(val) => return Some(val),
+				Err(err) if recover.unwinding => {
+					if recover.report {
+						recover.errors.push(err);
+					}
+				}
+				Err(err) => {
+					if !recover.tainted || recover::is_fatal(&err) {
+						recover.errors.push(err);
+					}
+					return None;
+				}
+			}
+
+			if !self.unwound(0) {
+				return None;
+			}
+		}
+	}
+
+	/// skip the values that are left to resume, because the value
+	/// that failed with `err` couldn't resume them
+	fn drain(&mut self, err: Error) -> Error {
+		let indent = self.indent;
+		while self.resuming().is_some() {
+			let skipped = IgnoredAny::deserialize(&mut *self);
+			let Some(recover) = self.recover.as_deref_mut() else {
+				break;
+			};
+
+			match skipped {
+				Ok(_) => recover.tainted = true,
+				Err(skipped) if recover.unwinding => {
+					if recover.report {
+						recover.errors.push(skipped);
+					}
+					if !self.unwound(indent) {
+						break;
+					}
+				}
+				Err(skipped) => {
+					recover.resume.clear();
+					recover.tainted = false;
+					return skipped;
+				}
+			}
+		}
+
+		err
+	}
+
+	/// an error unwound to a point with `indent`, get ready to resume its frames.
+	///
+	/// returns `false` if it unwound at the same position before, which means
+	/// resuming doesn't get anywhere
+	fn unwound(&mut self, indent: usize) -> bool {
+		let position = self.read.position();
+		let Some(recover) = self.recover.as_deref_mut() else {
+			return false;
+		};
+
+		recover.unwinding = false;
+		recover.resume = std::mem::take(&mut recover.frames);
+		if !recover.resumed.insert(position) {
+			recover.resume.clear();
+			return false;
+		}
+
+		self.indent = indent;
+		self.key = KeyCapture::default();
+		self.unused.value = None;
+		self.unused.ignoring = false;
+		self.merge = None;
+		true
+	}
+
+	/// whether an error is unwinding, to resume the values it passes through later
+	fn is_unwinding(&self) -> bool {
+		self.recover
+			.as_ref()
+			.is_some_and(|recover| recover.unwinding)
+	}
+
+	/// the next value to resume after an error, if there is any
+	fn resuming(&self) -> Option<&Frame> {
+		self.recover.as_ref()?.resume.last()
+	}
+
+	/// take the next frame to resume, if it matches `f`
+	fn resume_frame<T>(&mut self, f: impl FnOnce(Frame) -> Result<T, Frame>) -> Option<T> {
+		let resume = &mut self.recover.as_deref_mut()?.resume;
+		match f(resume.pop()?) {
+			Ok(val) => Some(val),
+			Err(frame) => {
+				resume.push(frame);
+				None
+			}
+		}
+	}
+
+	fn resume_map(&mut self) -> Option<MapState> {
+		self.resume_frame(|frame| match frame {
+			Frame::Map(state) => Ok(*state),
+			frame => Err(frame),
+		})
+	}
+
+	fn resume_seq(&mut self) -> Option<usize> {
+		self.resume_frame(|frame| match frame {
+			Frame::Seq(index) => Ok(index),
+			frame => Err(frame),
+		})
+	}
+
+	fn resume_entry(&mut self) -> Option<Key> {
+		self.resume_frame(|frame| match frame {
+			Frame::Entry(key) => Ok(key),
+			frame => Err(frame),
+		})
+	}
+
+	/// a map value or sequence element starts, which hasn't skipped an error yet
+	fn begin_value(&mut self) {
+		if let Some(recover) = &mut self.recover {
+			recover.tainted = false;
+		}
+	}
+
+	/// a map or sequence ended, which skipped an error if `recovered`
+	fn end_value(&mut self, recovered: bool) {
+		if let Some(recover) = &mut self.recover {
+			recover.tainted = recovered;
+		}
+	}
+
+	/// returns the name of the included source with the id `source`,
+	/// as used in [`Position::source`].
+	///
+	/// returns `None` for the main input and for unknown sources.
+	pub fn source_name(&self, source: usize) -> Option<&str> {
+		self.read.name(source)
+	}
+
+	/// returns the text of the included source with the id `source`
+	///
+	/// returns `None` for the main input and for unknown sources.
+	pub fn source_text(&self, source: usize) -> Option<&str> {
+		self.read.text(source)
+	}
+
+	/// returns the keys that were ignored while deserializing, because
+	/// they weren't used by the `Deserialize` impl.
+	///
+	/// this happens for struct fields that don't exist, unless the struct
+	/// uses `#[serde(deny_unknown_fields)]`.
+	///
+	/// ```
+	/// # use mayfig::Deserializer;
+	/// # use serde::Deserialize;
+	/// #[derive(Deserialize)]
+	/// struct Cursor {
+	///     size: u32,
+	/// }
+	///
+	/// let mut deserializer = Deserializer::from_str("size = 24\nszie = 32\n");
+	/// let cursor = Cursor::deserialize(&mut deserializer).unwrap();
+	///
+	/// let unused = deserializer.unused_keys();
+	/// assert_eq!(unused[0].path().to_string(), "szie");
+	/// assert_eq!(unused[0].span().range(), 10..14);
+	/// ```
+	pub fn unused_keys(&self) -> &[UnusedKey] {
+		&self.unused.keys
+	}
+}
+
+/// how to handle keys that occur more than once in the same map
+///
+/// keys are compared by their values, so `"mod+q"` and `mod+q` are the same key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+	/// pass every occurrence of the key to the type being deserialized,
+	/// so maps keep the last one and structs return a duplicate field error
+	#[default]
+	Allow,
+	/// return an [`ErrorCode::DuplicateKey`] error
+	Error,
+	/// use the last occurrence of the key and ignore all others
+	LastWins,
+	/// use the first occurrence of the key and ignore all others
+	FirstWins,
+}
+
+/// a map key that was ignored while deserializing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedKey {
+	path: Path,
+	span: Span,
+}
+
+impl UnusedKey {
+	/// returns the path to the key, including the key itself
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// returns the span of the key
+	pub fn span(&self) -> Span {
+		self.span
+	}
+}
+
+#[derive(Default)]
+struct Unused {
+	keys: Vec<UnusedKey>,
+	/// the start of the current map value and the span of its key
+	value: Option<(Position, Span)>,
+	/// if a value of an unused key is currently being ignored
+	ignoring: bool,
+}
+
+/// captures the scalars of a map key, the first one is used as a [`Segment::Key`]
+#[derive(Default)]
+struct KeyCapture {
+	active: bool,
+	/// every scalar of the key, to compare keys
+	scalars: Vec<String>,
+	span: Option<Span>,
+}
+
+impl KeyCapture {
+	fn start(&mut self) {
+		self.active = true;
+		self.scalars.clear();
+	}
+
+	fn stop(&mut self, span: Span) {
+		self.active = false;
+		self.span = Some(span);
+	}
+
+	fn capture(&mut self, scalar: &str) {
+		if self.active {
+			self.scalars.push(scalar.to_owned());
 		}
 	}
+
+	/// take the first scalar of the key
+	fn take(&mut self) -> String {
+		let scalars = std::mem::take(&mut self.scalars);
+		scalars.into_iter().next().unwrap_or_default()
+	}
 }
 
 impl<'de> Deserializer<StrRead<'de>> {
@@ -40,6 +431,26 @@ impl<'de> Deserializer<StrRead<'de>> {
 	}
 }
 
+impl<'de> Deserializer<SliceRead<'de>> {
+	/// create a mayfig deserializer from a `&[u8]`.
+	///
+	/// the input is validated as utf8 while deserializing.
+	pub fn from_slice(input: &'de [u8]) -> Self {
+		let read = SliceRead::new(input);
+		Deserializer::new(read)
+	}
+}
+
+impl<R: std::io::Read> Deserializer<IoRead<R>> {
+	/// create a mayfig deserializer from an io stream.
+	///
+	/// the reader is buffered internally.
+	pub fn from_reader(reader: R) -> Self {
+		let read = IoRead::new(reader);
+		Deserializer::new(read)
+	}
+}
+
 /// deserialize a type `T` from a mayfig `&str`
 ///
 /// # errors
@@ -57,49 +468,114 @@ where
 	T::deserialize(&mut deserializer)
 }
 
+/// deserialize a type `T` from a mayfig `&str`, returning the keys that were
+/// ignored as well.
+///
+/// see [`Deserializer::unused_keys`] for more info.
+///
+/// # errors
+///
+/// see [`from_str`] for the possible errors.
+pub fn from_str_with_unused<'a, T>(input: &'a str) -> Result<(T, Vec<UnusedKey>), Error>
+where
+	T: serde_core::de::Deserialize<'a>,
+{
+	let mut deserializer = Deserializer::from_str(input);
+	let val = T::deserialize(&mut deserializer)?;
+	Ok((val, deserializer.unused.keys))
+}
+
+/// deserialize a type `T` from a mayfig `&str`, collecting all errors
+///
+/// see [`Deserializer::deserialize_recover`] for how errors are recovered from.
+///
+/// # errors
+///
+/// this returns every error that was found, if there was any, in order of
+/// discovery. see [`from_str`] for the possible errors.
+pub fn from_str_recover<'a, T>(input: &'a str) -> Result<T, Vec<Error>>
+where
+	T: serde_core::de::Deserialize<'a>,
+{
+	let mut deserializer = Deserializer::from_str(input);
+	deserializer.deserialize_recover()
+}
+
+/// deserialize a type `T` from a mayfig `&[u8]`
+///
+/// # errors
+///
+/// this returns an [`ErrorCode::InvalidUtf8`] error if the input is not valid utf8,
+/// an error if the structure of the input does not match the structure of `T`,
+/// or if the `Deserialize` impl of `T` returns an error.
+///
+/// for more info on possible errors take a look at the
+/// [`ErrorCode`] enum
+pub fn from_slice<'a, T>(input: &'a [u8]) -> Result<T, Error>
+where
+	T: serde_core::de::Deserialize<'a>,
+{
+	let mut deserializer = Deserializer::from_slice(input);
+	T::deserialize(&mut deserializer)
+}
+
+/// deserialize a type `T` from an io stream of mayfig
+///
+/// the reader is buffered internally, so there is no need to wrap it
+/// in a [`BufReader`](std::io::BufReader).
+///
+/// # errors
+///
+/// this returns an [`ErrorCode::Io`] error if reading from the reader fails,
+/// an [`ErrorCode::InvalidUtf8`] error if the input is not valid utf8,
+/// an error if the structure of the input does not match the structure of `T`,
+/// or if the `Deserialize` impl of `T` returns an error.
+///
+/// for more info on possible errors take a look at the
+/// [`ErrorCode`] enum
+pub fn from_reader<R, T>(reader: R) -> Result<T, Error>
+where
+	R: std::io::Read,
+	T: serde_core::de::DeserializeOwned,
+{
+	let mut deserializer = Deserializer::from_reader(reader);
+	T::deserialize(&mut deserializer)
+}
+
 impl<'de, R: Read<'de>> Deserializer<R> {
 	/// discard comment
-	fn discard_comment(&mut self) {
-		debug_assert_eq!(self.read.peek(), Some('#'));
-		while let Some(peek) = self.read.peek() {
+	fn discard_comment(&mut self) -> Result<(), Error> {
+		debug_assert_eq!(self.read.peek()?, Some('#'));
+		while let Some(peek) = self.read.peek()? {
 			self.read.discard();
 			if peek == '\n' {
 				break;
 			}
 		}
+
+		Ok(())
 	}
 
 	/// peek next character that isn't a whitespace
-	fn peek_any(&mut self) -> Option<char> {
-		loop {
-			let peek = self.read.peek()?;
+	fn peek_any(&mut self) -> Result<Option<char>, Error> {
+		while let Some(peek) = self.read.peek()? {
 			if peek == '#' {
-				self.discard_comment();
+				self.discard_comment()?;
 			} else if read::is_whitespace(peek) {
 				self.read.discard();
 			} else {
-				return Some(peek);
+				return Ok(Some(peek));
 			}
 		}
+
+		Ok(None)
 	}
 
 	/// peek next character on the current line, that isn't whitespace.
 	///
 	/// returns an [`Error::UnexpectedNewline`] error if nothing is found before a new line
 	fn peek_line(&mut self) -> Result<Option<char>, Error> {
-		while let Some(peek) = self.read.peek() {
-			if read::is_whitespace_line(peek) {
-				self.read.discard();
-			} else if peek == '\n' || peek == '#' {
-				let point = self.read.position();
-				let code = ErrorCode::UnexpectedNewline;
-				return Err(Error::with_point(code, point));
-			} else {
-				return Ok(Some(peek));
-			}
-		}
-
-		Ok(None)
+		read::peek_line(&mut self.read)
 	}
 
 	/// peek next character on the next line, that isn't whitespace.
@@ -107,12 +583,12 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 	/// returns an [`Error::ExpectedNewline`] error if something was found before the linebreak
 	fn peek_newline(&mut self) -> Result<Option<char>, Error> {
 		let mut is_newline = false;
-		while let Some(peek) = self.read.peek() {
+		while let Some(peek) = self.read.peek()? {
 			if read::is_whitespace_line(peek) {
 				self.read.discard();
 			} else if peek == '#' {
 				is_newline = true;
-				self.discard_comment();
+				self.discard_comment()?;
 			} else if peek == '\n' {
 				is_newline = true;
 				self.read.discard();
@@ -128,24 +604,52 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 		Ok(None)
 	}
 
-	fn discard_commata(&mut self) {
-		while self.peek_any().is_some_and(|peek| peek == ',') {
+	/// attach the current path to the error, if it doesn't have one already
+	fn attach_path(&mut self, mut err: Error) -> Error {
+		if err.path.is_none() && !self.path.is_empty() {
+			err.path = Some(Box::new(Path(self.path.clone())));
+		}
+
+		err
+	}
+
+	/// run `f` with `segment` pushed onto the path
+	fn with_segment<T>(
+		&mut self,
+		segment: Segment,
+		f: impl FnOnce(&mut Self) -> Result<T, Error>,
+	) -> Result<T, Error> {
+		self.path.push(segment);
+		let val = f(self).map_err(|err| self.attach_path(err));
+		self.path.pop();
+		val
+	}
+
+	fn discard_commata(&mut self) -> Result<(), Error> {
+		while self.peek_any()?.is_some_and(|peek| peek == ',') {
 			self.read.discard();
 		}
+
+		Ok(())
 	}
 
 	fn num<'s>(&'s mut self) -> Result<(Ref<'de, 's, str>, Span), Error> {
+		if self.read.peek()? == Some('$') {
+			return self.substitute(Kind::Num, ErrorCode::ExpectedNumeric('$'));
+		}
+
 		let start = self.read.position();
 		self.scratch.clear();
 
 		let r#ref = self.read.num(&mut self.scratch)?;
 		if r#ref.is_empty() {
-			let peek = self.read.peek().ok_or(Error::EOF)?;
+			let peek = self.read.peek()?.ok_or(Error::EOF)?;
 
 			let point = self.read.position();
 			let code = ErrorCode::ExpectedNumeric(peek);
 			Err(Error::with_point(code, point))
 		} else {
+			self.key.capture(&r#ref);
 			let span = Span::Span(start, self.read.position());
 			Ok((r#ref, span))
 		}
@@ -155,13 +659,58 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 		self.scratch.clear();
 
 		let start = self.read.position();
-		let word = self.read.word(&mut self.scratch)?;
-		Ok((word, Span::Span(start, self.read.position())))
+		let word = match self.read.word(&mut self.scratch)? {
+			Ref::Borrow(word) => Some(word),
+			Ref::Scratch(_) => None,
+		};
+
+		if self.read.peek()? != Some('$') {
+			let word = word.map_or(Ref::Scratch(&*self.scratch), Ref::Borrow);
+			self.key.capture(&word);
+			return Ok((word, Span::Span(start, self.read.position())));
+		}
+
+		// variables can be substituted anywhere in a word, like `$mod+q`
+		if let Some(word) = word {
+			self.scratch.push_str(word);
+		}
+
+		while self.read.peek()? == Some('$') {
+			let (variable, _) = self.variable()?;
+			self.scratch.push_str(&variable.value);
+
+			let part = self.read.word(&mut self.scratch)?;
+			if let Ref::Borrow(part) = part {
+				self.scratch.push_str(part);
+			}
+		}
+
+		self.key.capture(&self.scratch);
+		let span = Span::Span(start, self.read.position());
+		Ok((Ref::Scratch(&self.scratch), span))
+	}
+
+	/// substitute a variable that has to be of the `kind`, or return an error with the `code`
+	fn substitute<'s>(
+		&'s mut self,
+		kind: Kind,
+		code: ErrorCode,
+	) -> Result<(Ref<'de, 's, str>, Span), Error> {
+		let (variable, span) = self.variable()?;
+		if variable.kind != kind {
+			return Err(Error::with_span(code, span));
+		}
+
+		self.scratch = variable.value;
+		self.key.capture(&self.scratch);
+		Ok((Ref::Scratch(&self.scratch), span))
 	}
 
 	fn str<'s>(&'s mut self) -> Result<(Ref<'de, 's, str>, Span), Error> {
-		let peek = self.read.peek().ok_or(Error::EOF)?;
-		if peek != '"' && peek != '\'' {
+		let peek = self.read.peek()?.ok_or(Error::EOF)?;
+		if peek == '$' {
+			return self.substitute(Kind::Str, ErrorCode::ExpectedQuote(peek));
+		} else if !read::is_quote(peek) {
 			let point = self.read.position();
 			let code = ErrorCode::ExpectedQuote(peek);
 			return Err(Error::with_point(code, point));
@@ -170,7 +719,24 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 		let start = self.read.position();
 		self.scratch.clear();
 		let str = self.read.str(&mut self.scratch)?;
-		Ok((str, Span::Span(start, self.read.position())))
+		let span = Span::Span(start, self.read.position());
+
+		let expanded = match &self.environment {
+			Some(environment) if peek != '`' => env::expand(&str, &**environment, span)?,
+			_ => None,
+		};
+
+		let str = match (expanded, str) {
+			(Some(expanded), _) => {
+				self.scratch = expanded;
+				Ref::Scratch(&*self.scratch)
+			}
+			(None, Ref::Borrow(str)) => Ref::Borrow(str),
+			(None, Ref::Scratch(_)) => Ref::Scratch(&*self.scratch),
+		};
+
+		self.key.capture(&str);
+		Ok((str, span))
 	}
 
 	fn str_bytes<'s>(&'s mut self) -> Result<(Ref<'de, 's, [u8]>, Span), Error> {
@@ -181,10 +747,10 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 	}
 
 	fn identifier<'s>(&'s mut self) -> Result<(Ref<'de, 's, str>, Span), Error> {
-		let peek = self.read.peek().ok_or(Error::EOF)?;
-		if peek == '"' || peek == '\'' {
+		let peek = self.read.peek()?.ok_or(Error::EOF)?;
+		if read::is_quote(peek) {
 			return self.str();
-		} else if !peek.is_ascii_alphabetic() && peek != '_' {
+		} else if !peek.is_ascii_alphabetic() && peek != '_' && peek != '$' {
 			let point = self.read.position();
 			let code = ErrorCode::ExpectedAsciiAlphabetic(peek);
 			return Err(Error::with_point(code, point));
@@ -198,22 +764,65 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 		V: serde_core::de::Visitor<'any>,
 	{
 		let (w, span) = self.num()?;
-		if w.contains('.') || w.contains('e') {
-			let n = parse_f64(&w, span)?;
-			visitor.visit_f64(n).map_err(|err| add_span(err, span))
-		} else if w.starts_with('-') {
-			let n = w.parse::<i64>().map_err(|_| {
-				let code = ErrorCode::InvalidNum(w.to_owned());
-				Error::with_span(code, span)
+		visit_number(&w, span, visitor)
+	}
+
+	/// deserialize a map, or a struct if `is_struct`, which can't take a key twice
+	fn visit_map<V>(&mut self, visitor: V, is_struct: bool) -> Result<V::Value, Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		let start = self.read.position();
+
+		// the indent goes back when the map ends, even if its visitor fails after that
+		if let Some(state) = self.resume_map() {
+			self.indent += 1;
+			let map_acc = MapAcc::resumed(self, state);
+			let val = visitor.visit_map(map_acc).map_err(|err| {
+				let end = self.read.position();
+				add_span(err, Span::Span(start, end))
+			})?;
+
+			return Ok(val);
+		}
+
+		let peek = self.peek_any()?;
+		let value = if let Some('{') = peek {
+			self.indent += 1;
+
+			let start = self.read.position();
+			self.read.discard();
+
+			let merge = self.merge.take_if(|merge| merge.at == start);
+			let map_acc = match merge {
+				Some(merge) => MapAcc::merged(self, is_struct, merge.blocks),
+				None => MapAcc::new(self, is_struct),
+			};
+			let val = visitor.visit_map(map_acc).map_err(|err| {
+				let end = self.read.position();
+				add_span(err, Span::Span(start, end))
 			})?;
-			visitor.visit_i64(n).map_err(|err| add_span(err, span))
+
+			Ok(val)
+		} else if self.indent != 0 {
+			let peek = peek.ok_or(Error::EOF)?;
+
+			let point = self.read.position();
+			let code = ErrorCode::ExpectedMap(peek);
+			return Err(Error::with_point(code, point));
 		} else {
-			let n = w.parse::<u64>().map_err(|_| {
-				let code = ErrorCode::InvalidNum(w.to_owned());
-				Error::with_span(code, span)
+			self.indent += 1;
+
+			let top_map_acc = MapAcc::top(self, is_struct);
+			let val = visitor.visit_map(top_map_acc).map_err(|err| {
+				let end = self.read.position();
+				add_span(err, Span::Span(start, end))
 			})?;
-			visitor.visit_u64(n).map_err(|err| add_span(err, span))
-		}
+
+			Ok(val)
+		};
+
+		value
 	}
 }
 
@@ -224,14 +833,31 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.peek_any().ok_or(Error::EOF)?;
+		match self.resuming() {
+			Some(Frame::Map(_)) => return self.deserialize_map(visitor),
+			Some(Frame::Seq(_)) => return self.deserialize_seq(visitor),
+			_ => {}
+		}
+
+		let peek = self.peek_any()?.ok_or(Error::EOF)?;
 		if self.indent == 0 || peek == '{' {
 			self.deserialize_map(visitor)
 		} else if peek == '[' {
 			self.deserialize_seq(visitor)
 		} else if let '0'..='9' | '.' | '-' | '+' = peek {
 			self.deserialize_number(visitor)
-		} else if peek == '"' || peek == '\'' {
+		} else if peek == '$' {
+			let (variable, span) = self.variable()?;
+			match variable.kind {
+				Kind::Num => visit_number(&variable.value, span, visitor),
+				Kind::Str => visitor.visit_string(variable.value),
+				Kind::Word => {
+					let b = parse_bool(&variable.value, span)?;
+					visitor.visit_bool(b)
+				}
+			}
+			.map_err(|err| add_span(err, span))
+		} else if read::is_quote(peek) {
 			let start = self.read.position();
 			let (str, span) = self.str()?;
 			let str = match str {
@@ -277,10 +903,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<u8>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<u8>(&w, span)?;
 		visitor.visit_u8(n).map_err(|err| add_span(err, span))
 	}
 
@@ -289,10 +912,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<u16>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<u16>(&w, span)?;
 		visitor.visit_u16(n).map_err(|err| add_span(err, span))
 	}
 
@@ -301,10 +921,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<u32>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<u32>(&w, span)?;
 		visitor.visit_u32(n).map_err(|err| add_span(err, span))
 	}
 
@@ -313,22 +930,25 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<u64>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<u64>(&w, span)?;
 		visitor.visit_u64(n).map_err(|err| add_span(err, span))
 	}
 
+	fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		let (w, span) = self.num()?;
+		let n = parse_int::<u128>(&w, span)?;
+		visitor.visit_u128(n).map_err(|err| add_span(err, span))
+	}
+
 	fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<i8>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<i8>(&w, span)?;
 		visitor.visit_i8(n).map_err(|err| add_span(err, span))
 	}
 
@@ -337,10 +957,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<i16>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<i16>(&w, span)?;
 		visitor.visit_i16(n).map_err(|err| add_span(err, span))
 	}
 
@@ -349,10 +966,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<i32>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<i32>(&w, span)?;
 		visitor.visit_i32(n).map_err(|err| add_span(err, span))
 	}
 
@@ -361,13 +975,19 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		V: serde_core::de::Visitor<'de>,
 	{
 		let (w, span) = self.num()?;
-		let n = w.parse::<i64>().map_err(|_| {
-			let code = ErrorCode::InvalidNum(w.to_owned());
-			Error::with_span(code, span)
-		})?;
+		let n = parse_int::<i64>(&w, span)?;
 		visitor.visit_i64(n).map_err(|err| add_span(err, span))
 	}
 
+	fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		let (w, span) = self.num()?;
+		let n = parse_int::<i128>(&w, span)?;
+		visitor.visit_i128(n).map_err(|err| add_span(err, span))
+	}
+
 	fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
@@ -413,9 +1033,9 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.read.peek().ok_or(Error::EOF)?;
+		let peek = self.read.peek()?.ok_or(Error::EOF)?;
 		match peek {
-			'"' | '\'' => {
+			ch if read::is_quote(ch) || ch == '$' => {
 				let (r#ref, sp) = self.str_bytes()?;
 				match r#ref {
 					Ref::Borrow(b) => visitor.visit_borrowed_bytes(b).map_err(|e| add_span(e, sp)),
@@ -478,23 +1098,32 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		self.indent += 1;
-
-		let next = self.read.peek().ok_or(Error::EOF)?;
-		if next != '[' {
-			let point = self.read.position();
-			let code = ErrorCode::ExpectedSeq(next);
-			return Err(Error::with_point(code, point));
-		}
-
 		let start = self.read.position();
-		self.read.discard();
+		let seq = if let Some(index) = self.resume_seq() {
+			self.indent += 1;
+			SeqAcc::resumed(self, index)
+		} else {
+			let next = self.read.peek()?.ok_or(Error::EOF)?;
+			if next != '[' {
+				let point = self.read.position();
+				let code = ErrorCode::ExpectedSeq(next);
+				return Err(Error::with_point(code, point));
+			}
+
+			self.indent += 1;
+			self.read.discard();
+			SeqAcc::new(self)
+		};
 
-		let seq = SeqAcc::new(self);
 		let val = visitor.visit_seq(seq);
 
-		self.discard_commata();
-		let next = self.peek_any().ok_or(Error::EOF)?;
+		// the rest of the sequence is skipped when recovering
+		if self.recover.is_some() && val.is_err() {
+			return val;
+		}
+
+		self.discard_commata()?;
+		let next = self.peek_any()?.ok_or(Error::EOF)?;
 
 		// point for seq end check
 		let seq_end = self.read.position();
@@ -538,72 +1167,47 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let start = self.read.position();
-
-		let peek = self.peek_any();
-		let value = if let Some('{') = peek {
-			self.indent += 1;
-
-			let start = self.read.position();
-			self.read.discard();
-
-			let map_acc = MapAcc::new(self);
-			let val = visitor.visit_map(map_acc).map_err(|err| {
-				let end = self.read.position();
-				add_span(err, Span::Span(start, end))
-			})?;
-
-			self.indent -= 1;
-
-			Ok(val)
-		} else if self.indent != 0 {
-			let peek = peek.ok_or(Error::EOF)?;
-
-			let point = self.read.position();
-			let code = ErrorCode::ExpectedMap(peek);
-			return Err(Error::with_point(code, point));
-		} else {
-			self.indent += 1;
-
-			let top_map_acc = TopMapAcc::new(self);
-			let val = visitor.visit_map(top_map_acc).map_err(|err| {
-				let end = self.read.position();
-				add_span(err, Span::Span(start, end))
-			})?;
-
-			self.indent -= 1;
-
-			Ok(val)
-		};
-
-		value
+		self.visit_map(visitor, false)
 	}
 
 	fn deserialize_struct<V>(
 		self,
-		_name: &'static str,
-		_fields: &'static [&'static str],
+		name: &'static str,
+		fields: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		self.deserialize_map(visitor)
+		if spanned::is_spanned(name, fields) {
+			if self.resuming().is_none() {
+				self.peek_any()?;
+			}
+			return visitor.visit_map(SpannedAcc::new(self));
+		}
+
+		self.visit_map(visitor, true)
 	}
 
 	fn deserialize_enum<V>(
 		self,
 		_name: &'static str,
-		_variants: &'static [&'static str],
+		variants: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.read.peek().ok_or(Error::EOF)?;
-		if peek == '"' || peek == '\'' {
+		// enums can't be resumed, so whatever is left of them is skipped
+		if self.resuming().is_some() {
+			let err = Error::custom("skipped enum");
+			return Err(self.drain(err));
+		}
+
+		let peek = self.read.peek()?.ok_or(Error::EOF)?;
+		if read::is_quote(peek) || peek == '$' {
 			let start = self.read.position();
-			let acc = TaggedEnumValueAcc::new(self);
+			let acc = TaggedEnumValueAcc::new(self, variants);
 			visitor.visit_enum(acc).map_err(|err| {
 				let end = self.read.position();
 				add_span(err, Span::Span(start, end))
@@ -626,10 +1230,63 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut Deserializer<
 		}
 	}
 
-	forward_to_deserialize_any! { ignored_any }
+	fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		// only the value of a map entry is reported, not every value inside of it
+		let value = self.unused.value.take();
+		let is_unused =
+			!self.unused.ignoring && value.is_some_and(|(start, _)| start == self.read.position());
+
+		if !is_unused {
+			return self.deserialize_any(visitor);
+		}
+
+		if let Some((_, span)) = value {
+			let path = Path(self.path.clone());
+			self.unused.keys.push(UnusedKey { path, span });
+		}
+
+		self.unused.ignoring = true;
+		let val = self.deserialize_any(visitor);
+		self.unused.ignoring = false;
+		val
+	}
+}
+
+/// visit the number `w`, as the smallest fitting type
+fn visit_number<'any, V>(w: &str, span: Span, visitor: V) -> Result<V::Value, Error>
+where
+	V: serde_core::de::Visitor<'any>,
+{
+	if is_float(w) {
+		let n = parse_f64(w, span)?;
+		visitor.visit_f64(n).map_err(|err| add_span(err, span))
+	} else if w.starts_with('-') {
+		let n = parse_int::<i128>(w, span)?;
+		match i64::try_from(n) {
+			Ok(n) => visitor.visit_i64(n),
+			Err(_) => visitor.visit_i128(n),
+		}
+		.map_err(|err| add_span(err, span))
+	} else {
+		let n = parse_int::<u128>(w, span)?;
+		match u64::try_from(n) {
+			Ok(n) => visitor.visit_u64(n),
+			Err(_) => visitor.visit_u128(n),
+		}
+		.map_err(|err| add_span(err, span))
+	}
 }
 
-fn parse_bool(word: &str, span: Span) -> Result<bool, Error> {
+/// whether the scanned number `num` is valid, like when it is deserialized
+pub(crate) fn is_valid_number(num: &str) -> bool {
+	let span = Span::Point(Position::start(0));
+	visit_number(num, span, serde_core::de::IgnoredAny).is_ok()
+}
+
+pub(crate) fn parse_bool(word: &str, span: Span) -> Result<bool, Error> {
 	if word.eq_ignore_ascii_case("true") {
 		Ok(true)
 	} else if word.eq_ignore_ascii_case("false") {
@@ -640,6 +1297,53 @@ fn parse_bool(word: &str, span: Span) -> Result<bool, Error> {
 	}
 }
 
+/// whether the number should be parsed as a float
+fn is_float(num: &str) -> bool {
+	let unsigned = num.trim_start_matches(['+', '-']);
+	let prefixed = ["0x", "0o", "0b"].iter().any(|p| unsigned.starts_with(p));
+	!prefixed && (num.contains('.') || num.contains('e'))
+}
+
+/// parse an integer with an optional `0x`, `0o` or `0b` prefix and `_` separators
+fn parse_int<T>(num: &str, span: Span) -> Result<T, Error>
+where
+	T: TryFrom<u128> + TryFrom<i128>,
+{
+	let invalid = || {
+		let code = ErrorCode::InvalidNum(num.to_owned());
+		Error::with_span(code, span)
+	};
+
+	let (negative, unsigned) = match num.as_bytes().first() {
+		Some(b'-') => (true, &num[1..]),
+		Some(b'+') => (false, &num[1..]),
+		_ => (false, num),
+	};
+
+	let (radix, digits) = if let Some(digits) = unsigned.strip_prefix("0x") {
+		(16, digits)
+	} else if let Some(digits) = unsigned.strip_prefix("0o") {
+		(8, digits)
+	} else if let Some(digits) = unsigned.strip_prefix("0b") {
+		(2, digits)
+	} else {
+		(10, unsigned)
+	};
+
+	let digits = digits.replace('_', "");
+	if digits.is_empty() || !digits.chars().all(|ch| ch.is_digit(radix)) {
+		return Err(invalid());
+	}
+
+	let magnitude = u128::from_str_radix(&digits, radix).map_err(|_| invalid())?;
+	if negative {
+		let n = 0i128.checked_sub_unsigned(magnitude).ok_or_else(invalid)?;
+		T::try_from(n).map_err(|_| invalid())
+	} else {
+		T::try_from(magnitude).map_err(|_| invalid())
+	}
+}
+
 fn parse_f64(num: &str, span: Span) -> Result<f64, Error> {
 	let stripped = if let Some(stripped) = num.strip_prefix('+') {
 		if stripped.starts_with(['+', '-']) {
@@ -651,6 +1355,7 @@ fn parse_f64(num: &str, span: Span) -> Result<f64, Error> {
 		num
 	};
 
+	let stripped = stripped.replace('_', "");
 	if stripped.eq_ignore_ascii_case(".inf") {
 		Ok(f64::INFINITY)
 	} else if stripped.eq_ignore_ascii_case("-.inf") {
@@ -658,12 +1363,13 @@ fn parse_f64(num: &str, span: Span) -> Result<f64, Error> {
 	} else if stripped.eq_ignore_ascii_case(".nan") {
 		let code = ErrorCode::UnsupportedNaN;
 		Err(Error::with_span(code, span))
-	} else if let Ok(float) = stripped.parse::<f64>() {
-		if float.is_finite() {
-			Ok(float)
-		} else {
-			unreachable!()
-		}
+	} else if let Some(float) = stripped
+		.parse::<f64>()
+		.ok()
+		.filter(|float| float.is_finite())
+	{
+		// too large floats are parsed as infinity
+		Ok(float)
 	} else {
 		let code = ErrorCode::InvalidNum(num.to_owned());
 		Err(Error::with_span(code, span))
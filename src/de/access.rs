@@ -1,17 +1,50 @@
-use super::{map::MapKey, read::Read};
+use super::{
+	include,
+	map::MapKey,
+	read::Read,
+	recover::{self, Frame, Key},
+	variable, DuplicateKeys,
+};
 use crate::{
-	error::{Error, ErrorCode},
-	Deserializer,
+	error::{Duplicate, Error, ErrorCode, Position, Segment, Span},
+	spanned, Deserializer,
+};
+use serde_core::de::{
+	value::{BorrowedStrDeserializer, SeqDeserializer},
+	IgnoredAny, IntoDeserializer, MapAccess, SeqAccess,
+};
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	marker::PhantomData,
 };
-use serde_core::de::{MapAccess, SeqAccess};
 
 pub struct SeqAcc<'a, R> {
 	de: &'a mut Deserializer<R>,
+	index: usize,
+	/// whether the element at `index` is resumed after an error
+	is_resumed: bool,
+	/// whether the sequence was resumed, so its visitor missed elements
+	recovered: bool,
 }
 
 impl<'a, 'de, R: Read<'de>> SeqAcc<'a, R> {
 	pub fn new(de: &'a mut Deserializer<R>) -> Self {
-		SeqAcc { de }
+		SeqAcc {
+			de,
+			index: 0,
+			is_resumed: false,
+			recovered: false,
+		}
+	}
+
+	/// a sequence that is resumed after an error, in its element at `index`
+	pub fn resumed(de: &'a mut Deserializer<R>, index: usize) -> Self {
+		SeqAcc {
+			de,
+			index,
+			is_resumed: true,
+			recovered: true,
+		}
 	}
 }
 
@@ -22,74 +55,722 @@ impl<'de, R: Read<'de>> SeqAccess<'de> for SeqAcc<'_, R> {
 	where
 		T: serde_core::de::DeserializeSeed<'de>,
 	{
-		self.de.discard_commata();
-		if self.de.peek_any().ok_or(Error::EOF)? == ']' {
-			return Ok(None);
+		let is_resumed = std::mem::take(&mut self.is_resumed);
+		if !is_resumed {
+			self.de.discard_commata()?;
+			if self.de.peek_any()?.ok_or(Error::EOF)? == ']' {
+				self.de.end_value(self.recovered);
+				return Ok(None);
+			}
+
+			// sequences in map keys are not part of the path
+			if self.de.key.active {
+				return seed.deserialize(&mut *self.de).map(Some);
+			}
 		}
 
-		seed.deserialize(&mut *self.de).map(Some)
+		let index = self.index;
+		self.index += 1;
+
+		self.de.begin_value();
+		let val = self
+			.de
+			.with_segment(Segment::Index(index), |de| seed.deserialize(de));
+		val.map(Some).inspect_err(|_| {
+			// the element is resumed along with the sequence
+			if let Some(recover) = self.de.recover.as_deref_mut().filter(|r| r.unwinding) {
+				recover.frames.push(Frame::Seq(index));
+			}
+		})
 	}
 }
 
-pub struct TopMapAcc<'a, R> {
+pub struct MapAcc<'a, R> {
 	de: &'a mut Deserializer<R>,
 	is_first: bool,
+	/// the top level map isn't surrounded by braces
+	is_top: bool,
+	/// whether the map is deserialized as a struct, which can't take a key twice
+	is_struct: bool,
+	/// whether the map was scanned for duplicate keys yet
+	is_scanned: bool,
+	/// the start of the map, if it is scanned as part of an outer map
+	scan_start: Option<Position>,
+	/// whether the reader is marked until the end of the map, to rewind to merged blocks
+	is_marked: bool,
+	/// the key of the entry that was just read, for errors after its value
+	last_key: Option<Segment>,
+	keys: Keys,
+	/// the positions of the `{` of the categories that are merged into this one
+	blocks: VecDeque<Position>,
+	/// the position after the first block, to resume at after the last merged block
+	resume: Option<Position>,
+	/// the positions after the `include` directives of the sources that are being read
+	returns: Vec<Position>,
+	/// the number of variables that were in scope before the map
+	variables: usize,
+	/// whether an error was skipped in the map, so its visitor missed entries
+	recovered: bool,
+	/// whether the entry whose value is next is resumed after an error
+	is_resumed: bool,
 }
 
-impl<'a, 'de, R: Read<'de>> TopMapAcc<'a, R> {
-	pub fn new(de: &'a mut Deserializer<R>) -> Self {
-		TopMapAcc { de, is_first: true }
+/// the state of a map that an error unwound through, to resume it
+pub struct MapState {
+	is_first: bool,
+	is_top: bool,
+	is_struct: bool,
+	is_scanned: bool,
+	scan_start: Option<Position>,
+	is_marked: bool,
+	last_key: Option<Segment>,
+	keys: Keys,
+	blocks: VecDeque<Position>,
+	resume: Option<Position>,
+	returns: Vec<Position>,
+	variables: usize,
+}
+
+/// the keys of a map, to handle duplicate keys
+#[derive(Default)]
+struct Keys {
+	/// the start of the current entry
+	current: Option<Position>,
+	/// the first occurrence of every key, for [`DuplicateKeys::Error`]
+	/// and [`DuplicateKeys::FirstWins`]
+	seen: HashMap<Vec<String>, Span>,
+	scan: Scan,
+	/// every entry of the map, while scanning
+	entries: Vec<Entry>,
+}
+
+/// the result of scanning a map
+#[derive(Default)]
+pub struct Scan {
+	/// the starts of the entries that are skipped because of duplicate keys
+	/// or because they are merged into an earlier category
+	skip: HashSet<Position>,
+	/// the blocks of repeated categories, by the start of the first one
+	merge: HashMap<Position, Vec<Position>>,
+}
+
+impl Scan {
+	/// find the entries to skip and the categories to merge
+	fn new(entries: &[Entry], policy: DuplicateKeys, merge: bool) -> Self {
+		let mut scan = Scan::default();
+		let mut entries = entries.iter().collect::<Vec<_>>();
+		if merge {
+			// repeated categories are merged into the first one
+			let mut first = HashMap::<&[String], Position>::new();
+			entries.retain(|entry| {
+				let Some(block) = entry.block else {
+					return true;
+				};
+
+				if let Some(&start) = first.get(&*entry.key) {
+					scan.merge.entry(start).or_default().push(block);
+					scan.skip.insert(entry.start);
+					false
+				} else {
+					first.insert(&entry.key, entry.start);
+					true
+				}
+			});
+		}
+
+		if policy == DuplicateKeys::LastWins {
+			let mut kept = HashMap::new();
+			for entry in &entries {
+				kept.insert(&entry.key, entry.start);
+			}
+
+			let kept = kept.into_values().collect::<HashSet<_>>();
+			let skip = entries.iter().map(|entry| entry.start);
+			scan.skip.extend(skip.filter(|start| !kept.contains(start)));
+		}
+
+		scan
 	}
 }
 
-impl<'de, R: Read<'de>> MapAccess<'de> for TopMapAcc<'_, R> {
-	type Error = Error;
+/// a map entry, recorded while scanning
+struct Entry {
+	key: Vec<String>,
+	start: Position,
+	/// the position of the `{`, if the entry is a category
+	block: Option<Position>,
+}
 
-	fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+/// the blocks of repeated categories that are merged into the category at `at`
+pub struct Merge {
+	/// the position of the `{` of the first category
+	pub at: Position,
+	pub blocks: Vec<Position>,
+}
+
+impl<'a, 'de, R: Read<'de>> MapAcc<'a, R> {
+	pub fn new(de: &'a mut Deserializer<R>, is_struct: bool) -> Self {
+		let variables = de.variables.len();
+		MapAcc {
+			de,
+			is_first: true,
+			is_top: false,
+			is_struct,
+			is_scanned: false,
+			scan_start: None,
+			is_marked: false,
+			last_key: None,
+			keys: Keys::default(),
+			blocks: VecDeque::new(),
+			resume: None,
+			returns: Vec::new(),
+			variables,
+			recovered: false,
+			is_resumed: false,
+		}
+	}
+
+	/// a map that continues in the `blocks` of repeated categories
+	pub fn merged(de: &'a mut Deserializer<R>, is_struct: bool, blocks: Vec<Position>) -> Self {
+		MapAcc {
+			blocks: blocks.into(),
+			..MapAcc::new(de, is_struct)
+		}
+	}
+
+	pub fn top(de: &'a mut Deserializer<R>, is_struct: bool) -> Self {
+		MapAcc {
+			is_top: true,
+			..MapAcc::new(de, is_struct)
+		}
+	}
+
+	/// a map that is resumed after an error unwound through it
+	pub fn resumed(de: &'a mut Deserializer<R>, state: MapState) -> Self {
+		MapAcc {
+			de,
+			is_first: state.is_first,
+			is_top: state.is_top,
+			is_struct: state.is_struct,
+			is_scanned: state.is_scanned,
+			scan_start: state.scan_start,
+			is_marked: state.is_marked,
+			last_key: state.last_key,
+			keys: state.keys,
+			blocks: state.blocks,
+			resume: state.resume,
+			returns: state.returns,
+			variables: state.variables,
+			recovered: true,
+			is_resumed: false,
+		}
+	}
+
+	/// take the state of the map, to resume it after an error unwound through it
+	fn suspend(&mut self) -> MapState {
+		MapState {
+			is_first: self.is_first,
+			is_top: self.is_top,
+			is_struct: self.is_struct,
+			is_scanned: self.is_scanned,
+			scan_start: self.scan_start.take(),
+			is_marked: std::mem::take(&mut self.is_marked),
+			last_key: self.last_key.take(),
+			keys: std::mem::take(&mut self.keys),
+			blocks: std::mem::take(&mut self.blocks),
+			resume: self.resume.take(),
+			returns: std::mem::take(&mut self.returns),
+			variables: self.variables,
+		}
+	}
+
+	/// move to the start of the next entry, returns `false` at the end of the map
+	fn next_entry(&mut self) -> Result<bool, Error> {
+		loop {
+			// a `}` can't close the map from inside of an included source
+			let is_closable = !self.is_top && self.returns.is_empty();
+			if is_closable {
+				if let Ok(Some('}')) = self.de.peek_line() {
+					if self.close() {
+						continue;
+					}
+					return Ok(self.end());
+				}
+			}
+
+			let peek = if self.is_first {
+				self.is_first = false;
+				self.de.peek_any()?
+			} else {
+				let peek = self.de.peek_newline();
+				peek.map_err(|err| self.after_entry(err))?
+			};
+			self.last_key = None;
+
+			return match peek {
+				None => {
+					if let Some(end) = self.returns.pop() {
+						self.de.read.rewind(end);
+						continue;
+					}
+
+					if self.is_top {
+						Ok(self.end())
+					} else {
+						Err(Error::EOF)
+					}
+				}
+				Some('}') if is_closable => {
+					if self.close() {
+						continue;
+					}
+					Ok(self.end())
+				}
+				Some(_) => Ok(true),
+			};
+		}
+	}
+
+	/// handle an `include "path"` directive at the current position,
+	/// returns `false` if there is none
+	fn include(&mut self) -> Result<bool, Error> {
+		let start = self.de.read.position();
+		if !include::directive(&mut self.de.read, &mut self.de.scratch)? {
+			return Ok(false);
+		}
+
+		self.de.scratch.clear();
+		let path = String::from(self.de.read.str(&mut self.de.scratch)?);
+		let end = self.de.read.position();
+
+		let span = Span::Span(start, end);
+		let included = self.de.read.include(&path, span);
+		included.map_err(|err| self.de.attach_path(err))?;
+
+		self.returns.push(end);
+		self.is_first = true;
+		Ok(true)
+	}
+
+	/// attach the entry that was just read to an error after its value,
+	/// like a missing newline
+	fn after_entry(&mut self, err: Error) -> Error {
+		let Some(segment) = self.last_key.take() else {
+			return err;
+		};
+
+		let err = self.de.with_segment(segment, |_| Err::<(), _>(err));
+		err.unwrap_err()
+	}
+
+	/// the map ended, so its variables go out of scope
+	fn end(&mut self) -> bool {
+		self.de.variables.truncate(self.variables);
+		self.de.indent -= 1;
+		self.de.end_value(self.recovered);
+
+		if std::mem::take(&mut self.is_marked) {
+			self.de.read.commit();
+		}
+
+		// keep the scan of a nested map for when it is deserialized
+		if let Some(start) = self.scan_start.take() {
+			let policy = self.de.duplicate_keys;
+			let scan = Scan::new(&self.keys.entries, policy, self.de.merge_categories);
+			self.de.scans.insert(start, scan);
+		}
+
+		false
+	}
+
+	/// handle a `$name = value` variable definition at the current position,
+	/// returns `false` if there is none
+	fn define(&mut self) -> Result<bool, Error> {
+		let Some(name) = variable::definition(&mut self.de.read)? else {
+			return Ok(false);
+		};
+
+		let defined = variable::define(&mut *self.de, name);
+		let variable = defined.map_err(|err| self.de.attach_path(err))?;
+		self.de.variables.push(variable);
+
+		Ok(true)
+	}
+
+	/// close the current block, returns `true` if the map continues in the next merged block
+	fn close(&mut self) -> bool {
+		self.de.read.discard();
+
+		if let Some(block) = self.blocks.pop_front() {
+			self.resume.get_or_insert(self.de.read.position());
+			self.de.read.rewind(block);
+			self.de.read.discard();
+			self.is_first = true;
+			true
+		} else {
+			if let Some(resume) = self.resume.take() {
+				self.de.read.rewind(resume);
+			}
+			false
+		}
+	}
+
+	fn key<K>(&mut self, seed: K) -> Result<K::Value, Error>
 	where
 		K: serde_core::de::DeserializeSeed<'de>,
 	{
-		let peek = if self.is_first {
-			self.is_first = false;
-			self.de.peek_any()
-		} else {
-			self.de.peek_newline()?
-		};
-		if peek.is_none() {
-			return Ok(None);
+		let start = self.de.read.position();
+		self.de.key.start();
+		let mut map_key = MapKey::new(&mut *self.de);
+		let key = seed.deserialize(&mut map_key);
+
+		let span = Span::Span(start, self.de.read.position());
+		self.de.key.stop(span);
+
+		let key = key.map_err(|err| match err.code {
+			// the field itself is unknown, not the map
+			ErrorCode::UnknownField(_) => {
+				let segment = Segment::Key(self.de.key.take());
+				let err = self.de.with_segment(segment, |_| Err::<(), _>(err));
+				err.unwrap_err()
+			}
+			_ => self.de.attach_path(err),
+		})?;
+
+		let scalars = &self.de.key.scalars;
+		self.keys.current = Some(start);
+		if self.de.scanning {
+			self.keys.entries.push(Entry {
+				key: scalars.clone(),
+				start,
+				block: None,
+			});
+		} else if self.de.duplicate_keys == DuplicateKeys::Error
+			&& !self.keys.scan.skip.contains(&start)
+		{
+			if let Some(&first) = self.keys.seen.get(scalars) {
+				let duplicate = Duplicate {
+					key: scalars.join(" "),
+					first,
+					second: span,
+				};
+				let code = ErrorCode::DuplicateKey(Box::new(duplicate));
+				let err = Error::with_span(code, span);
+				return Err(self.de.attach_path(err));
+			}
+
+			self.keys.seen.insert(scalars.clone(), span);
 		}
 
-		let mut map_key = MapKey::new(self.de);
-		seed.deserialize(&mut map_key).map(Some)
+		Ok(key)
 	}
 
-	fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+	fn value<V>(&mut self, seed: V) -> Result<V::Value, Error>
 	where
 		V: serde_core::de::DeserializeSeed<'de>,
 	{
-		let peek = self.de.peek_line()?.ok_or(Error::EOF)?;
+		let scalars = self
+			.de
+			.recover
+			.is_some()
+			.then(|| self.de.key.scalars.clone());
+		let segment = Segment::Key(self.de.key.take());
+		self.last_key = Some(segment.clone());
+		let key_span = self.de.key.span.take();
+		let key = scalars
+			.zip(key_span)
+			.map(|(scalars, span)| Key { scalars, span });
+
+		let is_resumed = std::mem::take(&mut self.is_resumed);
+		let indent = self.de.indent;
+		self.de.begin_value();
+
+		let keys = &mut self.keys;
+		let val = self.de.with_segment(segment, |de| {
+			// the value of a resumed entry continues where the error unwound
+			if is_resumed {
+				return seed.deserialize(de);
+			}
+
+			let peek = de.peek_line()?.ok_or(Error::EOF)?;
+
+			if peek == '=' {
+				de.read.discard();
+			} else if peek != '{' {
+				let point = de.read.position();
+				let code = ErrorCode::ExpectedValue(peek);
+				return Err(Error::with_point(code, point));
+			} else if de.scanning {
+				if let Some(entry) = keys.entries.last_mut() {
+					entry.block = Some(de.read.position());
+				}
+			} else if let Some(blocks) = keys
+				.current
+				.and_then(|start| keys.scan.merge.remove(&start))
+			{
+				let at = de.read.position();
+				de.merge = Some(Merge { at, blocks });
+			}
+
+			let _ = de.peek_line()?.ok_or(Error::EOF)?;
+			let start = de.read.position();
+			de.unused.value = key_span.map(|span| (start, span));
+
+			seed.deserialize(de)
+		});
+
+		self.de.merge = None;
+		val.map_err(|err| self.unwind(err, indent, key))
+	}
+
+	/// continue in the entry with `key`, after an error unwound through its value
+	fn resume_key<K>(&mut self, seed: K, key: Key) -> Result<K::Value, Error>
+	where
+		K: serde_core::de::DeserializeSeed<'de>,
+	{
+		self.de.key.scalars.clone_from(&key.scalars);
+		self.de.key.span = Some(key.span);
+
+		match seed.deserialize(key) {
+			Ok(key) => {
+				self.is_resumed = true;
+				Ok(key)
+			}
+			Err(err) => {
+				let indent = self.de.indent;
+				Err(self.unwind(err, indent, None))
+			}
+		}
+	}
 
-		if peek == '=' {
+	/// skip the rest of an entry after an error before its key was read,
+	/// to continue with the next entry when recovering
+	fn skip_error(&mut self, err: Error, indent: usize) -> Result<(), Error> {
+		if self.de.scanning || recover::is_fatal(&err) {
+			return Err(err);
+		}
+
+		let Some(recover) = self.de.recover.as_deref_mut() else {
+			return Err(err);
+		};
+
+		recover.errors.push(err);
+		self.recovered = true;
+
+		let start = self.de.read.position();
+		let depth = self.de.indent.saturating_sub(indent);
+		recover::resync(&mut self.de.read, depth)?;
+		self.de.indent = indent;
+
+		// an error right at a `}` that doesn't close the map has to skip it
+		let is_closable = !self.is_top && self.returns.is_empty();
+		let peek = self.de.read.peek()?;
+		let is_stuck = peek.is_some_and(|ch| ch != '\n' && (ch != '}' || !is_closable));
+		if is_stuck && self.de.read.position() == start {
 			self.de.read.discard();
-		} else if peek != '{' {
-			let point = self.de.read.position();
-			let code = ErrorCode::ExpectedValue(peek);
-			return Err(Error::with_point(code, point));
 		}
 
-		let _ = self.de.peek_line()?.ok_or(Error::EOF)?;
-		seed.deserialize(&mut *self.de)
+		Ok(())
 	}
-}
 
-pub struct MapAcc<'a, R> {
-	de: &'a mut Deserializer<R>,
-	is_first: bool,
-}
+	/// handle an error after the key of an entry was read, which the visitor
+	/// of the map can't continue after.
+	///
+	/// when recovering, the rest of the entry is skipped and the error unwinds
+	/// to the top, to resume the map from its frame
+	fn unwind(&mut self, mut err: Error, indent: usize, key: Option<Key>) -> Error {
+		if self.de.recover.is_none() || self.de.scanning || recover::is_fatal(&err) {
+			return err;
+		}
 
-impl<'a, 'de, R: Read<'de>> MapAcc<'a, R> {
-	pub fn new(de: &'a mut Deserializer<R>) -> Self {
-		MapAcc { de, is_first: true }
+		if !self.de.is_unwinding() {
+			// the value may not have resumed everything that was left of it
+			err = self.de.drain(err);
+			if recover::is_fatal(&err) {
+				return err;
+			}
+
+			let depth = self.de.indent.saturating_sub(indent);
+			if let Err(err) = recover::resync(&mut self.de.read, depth) {
+				return err;
+			}
+		}
+
+		let state = self.suspend();
+		let Some(recover) = self.de.recover.as_deref_mut() else {
+			return err;
+		};
+
+		if recover.unwinding {
+			recover.frames.extend(key.map(Frame::Entry));
+		} else {
+			recover.unwinding = true;
+			recover.report = !recover.tainted;
+		}
+
+		recover.frames.push(Frame::Map(Box::new(state)));
+		err
+	}
+
+	/// skip the entry at the current position, without reporting it as unused
+	fn skip_entry(&mut self) -> Result<(), Error> {
+		self.key(PhantomData::<IgnoredAny>)?;
+		self.skip_value()
+	}
+
+	/// skip the value of the entry whose key was just read, without reporting it as unused
+	fn skip_value(&mut self) -> Result<(), Error> {
+		// the entry is never resumed, so an error in it is skipped in place
+		let recover = self.de.recover.take();
+		let ignoring = std::mem::replace(&mut self.de.unused.ignoring, true);
+		let skipped = self.value(PhantomData::<IgnoredAny>);
+		self.de.unused.ignoring = ignoring;
+		self.de.recover = recover;
+
+		skipped.map(|_| ())
+	}
+
+	/// skip the entry at the current position if its key was seen before,
+	/// for [`DuplicateKeys::FirstWins`]. returns whether it was skipped
+	fn skip_duplicate(&mut self) -> Result<bool, Error> {
+		let start = self.de.read.position();
+		self.de.read.mark();
+		let skipped = self.read_duplicate(start);
+		self.de.read.commit();
+		skipped
+	}
+
+	fn read_duplicate(&mut self, start: Position) -> Result<bool, Error> {
+		self.key(PhantomData::<IgnoredAny>)?;
+		let span = Span::Span(start, self.de.read.position());
+
+		let scalars = &self.de.key.scalars;
+		if self.keys.seen.contains_key(scalars) {
+			self.skip_value()?;
+			return Ok(true);
+		}
+
+		// only the key is read again, by the visitor of the map
+		self.keys.seen.insert(scalars.clone(), span);
+		self.de.read.rewind(start);
+		Ok(false)
+	}
+
+	/// scan the keys of the map ahead of time, to find the entries that
+	/// have to be skipped for [`DuplicateKeys::LastWins`] and the categories
+	/// that have to be merged
+	///
+	/// nested maps are scanned along with the outer map, so every map is only scanned once
+	fn scan(&mut self) -> Result<(), Error> {
+		let policy = self.de.duplicate_keys;
+		let merge = self.de.merge_categories;
+		if policy != DuplicateKeys::LastWins && !merge {
+			return Ok(());
+		}
+
+		let start = self.de.read.position();
+		if self.de.scanning {
+			self.scan_start = Some(start);
+			return Ok(());
+		}
+
+		// a merged map continues in blocks the outer scan saw as separate maps
+		let scanned = self.de.scans.remove(&start);
+		if let Some(scan) = scanned.filter(|_| self.blocks.is_empty()) {
+			self.keys.scan = scan;
+			return Ok(());
+		}
+
+		// a map keeps the last value of a key by itself, but a struct rejects it
+		if !merge && !self.is_struct {
+			return Ok(());
+		}
+
+		let ignoring = std::mem::replace(&mut self.de.unused.ignoring, true);
+		self.de.scanning = true;
+		self.de.read.mark();
+
+		let mut acc = MapAcc {
+			de: &mut *self.de,
+			is_first: self.is_first,
+			is_top: self.is_top,
+			is_struct: self.is_struct,
+			is_scanned: true,
+			scan_start: None,
+			is_marked: false,
+			last_key: None,
+			keys: Keys::default(),
+			blocks: self.blocks.clone(),
+			resume: self.resume,
+			returns: self.returns.clone(),
+			variables: self.variables,
+			recovered: false,
+			is_resumed: false,
+		};
+		let indent = acc.de.indent;
+		let scanned = acc.scan_entries();
+		let entries = std::mem::take(&mut acc.keys.entries);
+		self.de.indent = indent;
+
+		self.de.scanning = false;
+		self.de.unused.ignoring = ignoring;
+		if let Err(err) = scanned {
+			// the error is found again and skipped when recovering
+			if self.de.recover.is_some() {
+				self.de.read.rewind(start);
+				self.de.read.commit();
+				return Ok(());
+			}
+
+			self.de.read.commit();
+			return Err(err);
+		}
+
+		self.de.read.rewind(start);
+		self.keys.scan = Scan::new(&entries, policy, merge);
+
+		// merged blocks are read out of order, so they need the reader until the end
+		if merge {
+			self.is_marked = true;
+		} else {
+			self.de.read.commit();
+		}
+
+		Ok(())
+	}
+
+	/// move to the key of the next entry, returns `false` at the end of the map
+	fn next_key(&mut self) -> Result<bool, Error> {
+		loop {
+			if !self.next_entry()? {
+				return Ok(false);
+			}
+
+			if self.include()? || self.define()? {
+				continue;
+			}
+
+			let start = self.de.read.position();
+			if self.keys.scan.skip.contains(&start) {
+				self.skip_entry()?;
+				continue;
+			}
+
+			let first_wins = self.de.duplicate_keys == DuplicateKeys::FirstWins;
+			if first_wins && !self.de.scanning && self.skip_duplicate()? {
+				continue;
+			}
+
+			return Ok(true);
+		}
+	}
+
+	fn scan_entries(&mut self) -> Result<(), Error> {
+		while self.next_key_seed(PhantomData::<IgnoredAny>)?.is_some() {
+			self.next_value_seed(PhantomData::<IgnoredAny>)?;
+		}
+
+		Ok(())
 	}
 }
 
@@ -100,42 +781,106 @@ impl<'de, R: Read<'de>> MapAccess<'de> for MapAcc<'_, R> {
 	where
 		K: serde_core::de::DeserializeSeed<'de>,
 	{
-		if let Ok(Some('}')) = self.de.peek_line() {
-			self.de.read.discard();
-			return Ok(None);
+		if !self.is_scanned {
+			self.is_scanned = true;
+			self.scan()?;
 		}
 
-		let peek = if self.is_first {
-			self.is_first = false;
-			self.de.peek_any()
-		} else {
-			self.de.peek_newline()?
-		};
+		// a resumed map continues in the entry an error unwound through
+		if let Some(key) = self.de.resume_entry() {
+			return self.resume_key(seed, key).map(Some);
+		}
 
-		if peek.ok_or(Error::EOF)? == '}' {
-			self.de.read.discard();
-			return Ok(None);
+		let indent = self.de.indent;
+		loop {
+			match self.next_key() {
+				Ok(true) => break,
+				Ok(false) => return Ok(None),
+				Err(err) => self.skip_error(err, indent)?,
+			}
 		}
 
-		let mut map_key = MapKey::new(&mut *self.de);
-		seed.deserialize(&mut map_key).map(Some)
+		let key = self.key(seed);
+		key.map(Some).map_err(|err| self.unwind(err, indent, None))
 	}
 
 	fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::DeserializeSeed<'de>,
 	{
-		let peek = self.de.peek_line()?.ok_or(Error::EOF)?;
+		self.value(seed)
+	}
+}
 
-		if peek == '=' {
-			self.de.read.discard();
-		} else if peek != '{' {
-			let point = self.de.read.position();
-			let code = ErrorCode::ExpectedValue(peek);
-			return Err(Error::with_point(code, point));
+/// a deserializer that knows its current position, used for [`Spanned`](crate::Spanned)
+pub trait Locate {
+	fn position(&mut self) -> Position;
+}
+
+impl<'de, R: Read<'de>> Locate for Deserializer<R> {
+	fn position(&mut self) -> Position {
+		self.read.position()
+	}
+}
+
+pub struct SpannedAcc<'a, D> {
+	de: &'a mut D,
+	start: Position,
+	/// index into [`spanned::FIELDS`]
+	field: usize,
+}
+
+impl<'a, D: Locate> SpannedAcc<'a, D> {
+	pub fn new(de: &'a mut D) -> Self {
+		let start = de.position();
+		SpannedAcc {
+			de,
+			start,
+			field: 0,
 		}
+	}
+}
+
+impl<'de, D> MapAccess<'de> for SpannedAcc<'_, D>
+where
+	D: Locate,
+	for<'d> &'d mut D: serde_core::de::Deserializer<'de, Error = Error>,
+{
+	type Error = Error;
+
+	fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+	where
+		K: serde_core::de::DeserializeSeed<'de>,
+	{
+		let Some(&field) = spanned::FIELDS.get(self.field) else {
+			return Ok(None);
+		};
+
+		let key = BorrowedStrDeserializer::new(field);
+		seed.deserialize(key).map(Some)
+	}
+
+	fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::DeserializeSeed<'de>,
+	{
+		let field = spanned::FIELDS[self.field];
+		self.field += 1;
+
+		let position = match field {
+			spanned::START => self.start,
+			spanned::END => self.de.position(),
+			_ => return seed.deserialize(&mut *self.de),
+		};
 
-		let _ = self.de.peek_line()?.ok_or(Error::EOF)?;
-		seed.deserialize(&mut *self.de)
+		let Position {
+			line,
+			col,
+			index,
+			source,
+		} = position;
+		let position = [line, col, index, source];
+		let position = position.into_iter().map(usize::into_deserializer);
+		seed.deserialize(SeqDeserializer::new(position))
 	}
 }
@@ -5,3 +5,17 @@ mod value;
 
 pub use key::TaggedEnumKeyAcc;
 pub use value::TaggedEnumValueAcc;
+
+use crate::error::{Error, ErrorCode, Unknown};
+
+/// expect the `variants` that `deserialize_enum` was called with
+/// in an unknown variant error, if there are any
+fn with_variants(mut err: Error, variants: &'static [&'static str]) -> Error {
+	if let ErrorCode::UnknownVariant(unknown) = &mut err.code {
+		if !variants.is_empty() {
+			**unknown = Unknown::new(&unknown.name, variants);
+		}
+	}
+
+	err
+}
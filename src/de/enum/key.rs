@@ -1,13 +1,13 @@
-use super::{fake::FakeStringDeserializer, unit::TaggedUnitEnumAcc};
+use super::{fake::FakeStringDeserializer, unit::TaggedUnitEnumAcc, with_variants};
 use crate::{
 	de::{
-		access::SeqAcc,
+		access::{Locate, SeqAcc, SpannedAcc},
 		add_span,
 		map::MapKey,
-		read::{Read, Ref},
+		read::{is_quote, Read, Ref},
 	},
-	error::ErrorCode,
-	Deserializer, Error,
+	error::{ErrorCode, Position},
+	spanned, Deserializer, Error,
 };
 use serde_core::de::{Deserializer as _, EnumAccess, VariantAccess};
 use std::borrow::Cow;
@@ -15,13 +15,16 @@ use std::borrow::Cow;
 pub struct TaggedEnumKeyAcc<'a, 'b, 'de, R> {
 	map_key: &'a mut MapKey<'b, R>,
 	string: Option<Cow<'de, str>>,
+	/// the variants of the enum, if they are known
+	variants: &'static [&'static str],
 }
 
 impl<'a, 'b, 'de, R: Read<'de>> TaggedEnumKeyAcc<'a, 'b, 'de, R> {
-	pub fn new(map_key: &'a mut MapKey<'b, R>) -> Self {
+	pub fn new(map_key: &'a mut MapKey<'b, R>, variants: &'static [&'static str]) -> Self {
 		TaggedEnumKeyAcc {
 			map_key,
 			string: None,
+			variants,
 		}
 	}
 
@@ -29,6 +32,7 @@ impl<'a, 'b, 'de, R: Read<'de>> TaggedEnumKeyAcc<'a, 'b, 'de, R> {
 		TaggedEnumKeyAcc {
 			map_key,
 			string: Some(string),
+			variants: &[],
 		}
 	}
 }
@@ -41,14 +45,14 @@ impl<'de, R: Read<'de>> EnumAccess<'de> for TaggedEnumKeyAcc<'_, '_, 'de, R> {
 	where
 		V: serde_core::de::DeserializeSeed<'de>,
 	{
-		if let Some(string) = self.string.take() {
-			let fake = FakeStringDeserializer::new(string);
-			let variant = seed.deserialize(fake)?;
-			Ok((variant, self))
+		let variant = if let Some(string) = self.string.take() {
+			seed.deserialize(FakeStringDeserializer::new(string))
 		} else {
-			let variant = seed.deserialize(&mut *self.map_key)?;
-			Ok((variant, self))
-		}
+			seed.deserialize(&mut *self.map_key)
+		};
+
+		let variant = variant.map_err(|err| with_variants(err, self.variants))?;
+		Ok((variant, self))
 	}
 }
 
@@ -73,12 +77,12 @@ impl<'de, R: Read<'de>> VariantAccess<'de> for TaggedEnumKeyAcc<'_, '_, 'de, R>
 
 		self.map_key.de.indent += 1;
 
-		self.map_key.de.peek_any().ok_or(Error::EOF)?;
-		let variant = TaggedKey::new(&mut *self.map_key.de);
-		let val = seed.deserialize(variant)?;
+		self.map_key.de.peek_any()?.ok_or(Error::EOF)?;
+		let mut variant = TaggedKey::new(&mut *self.map_key.de);
+		let val = seed.deserialize(&mut variant)?;
 
-		self.map_key.de.discard_commata();
-		let peek = self.map_key.de.peek_any().ok_or(Error::EOF)?;
+		self.map_key.de.discard_commata()?;
+		let peek = self.map_key.de.peek_any()?.ok_or(Error::EOF)?;
 		if peek != ']' {
 			let point = self.map_key.de.read.position();
 			let code = ErrorCode::ExpectedSeqEnd(next);
@@ -121,7 +125,13 @@ impl<'a, 'de, R: Read<'de>> TaggedKey<'a, R> {
 	}
 }
 
-impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for TaggedKey<'_, R> {
+impl<'de, R: Read<'de>> Locate for TaggedKey<'_, R> {
+	fn position(&mut self) -> Position {
+		self.de.read.position()
+	}
+}
+
+impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut TaggedKey<'_, R> {
 	type Error = Error;
 
 	fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -248,9 +258,9 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for TaggedKey<'_, R> {
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.de.peek_any().ok_or(Error::EOF)?;
+		let peek = self.de.peek_any()?.ok_or(Error::EOF)?;
 		match peek {
-			'"' | '\'' => {
+			ch if is_quote(ch) => {
 				let (r#ref, sp) = self.de.str_bytes()?;
 				match r#ref {
 					Ref::Borrow(b) => visitor.visit_borrowed_bytes(b).map_err(|e| add_span(e, sp)),
@@ -317,7 +327,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for TaggedKey<'_, R> {
 		let acc = SeqAcc::new(self.de);
 		let val = visitor.visit_seq(acc)?;
 
-		self.de.discard_commata();
+		self.de.discard_commata()?;
 
 		Ok(val)
 	}
@@ -350,28 +360,32 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for TaggedKey<'_, R> {
 
 	fn deserialize_struct<V>(
 		self,
-		_name: &'static str,
-		_fields: &'static [&'static str],
+		name: &'static str,
+		fields: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		self.deserialize_map(visitor)
+		if spanned::is_spanned(name, fields) {
+			return visitor.visit_map(SpannedAcc::new(self));
+		}
+
+		self.de.visit_map(visitor, true)
 	}
 
 	fn deserialize_enum<V>(
 		self,
 		_name: &'static str,
-		_variants: &'static [&'static str],
+		variants: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.de.read.peek().ok_or(Error::EOF)?;
-		if peek == '"' || peek == '\'' {
-			let acc = TaggedUnitEnumAcc::new(&mut *self.de);
+		let peek = self.de.read.peek()?.ok_or(Error::EOF)?;
+		if is_quote(peek) {
+			let acc = TaggedUnitEnumAcc::new(&mut *self.de, variants);
 			visitor.visit_enum(acc)
 		} else {
 			let point = self.de.read.position();
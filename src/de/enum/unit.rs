@@ -1,13 +1,16 @@
+use super::with_variants;
 use crate::{de::read::Read, Deserializer, Error};
 use serde_core::de::{EnumAccess, VariantAccess};
 
 pub struct TaggedUnitEnumAcc<'a, R> {
 	de: &'a mut Deserializer<R>,
+	/// the variants of the enum
+	variants: &'static [&'static str],
 }
 
 impl<'a, 'de, R: Read<'de>> TaggedUnitEnumAcc<'a, R> {
-	pub fn new(de: &'a mut Deserializer<R>) -> Self {
-		TaggedUnitEnumAcc { de }
+	pub fn new(de: &'a mut Deserializer<R>, variants: &'static [&'static str]) -> Self {
+		TaggedUnitEnumAcc { de, variants }
 	}
 }
 
@@ -19,7 +22,8 @@ impl<'de, R: Read<'de>> EnumAccess<'de> for TaggedUnitEnumAcc<'_, R> {
 	where
 		V: serde_core::de::DeserializeSeed<'de>,
 	{
-		let variant = seed.deserialize(&mut *self.de)?;
+		let variant = seed.deserialize(&mut *self.de);
+		let variant = variant.map_err(|err| with_variants(err, self.variants))?;
 		Ok((variant, self))
 	}
 }
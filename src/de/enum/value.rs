@@ -1,12 +1,12 @@
-use super::{fake::FakeStringDeserializer, unit::TaggedUnitEnumAcc};
+use super::{fake::FakeStringDeserializer, unit::TaggedUnitEnumAcc, with_variants};
 use crate::{
 	de::{
-		access::SeqAcc,
+		access::{Locate, SeqAcc, SpannedAcc},
 		add_span,
-		read::{Read, Ref},
+		read::{is_quote, Read, Ref},
 	},
-	error::{Error, ErrorCode, Span},
-	Deserializer,
+	error::{Error, ErrorCode, Position, Segment, Span},
+	spanned, Deserializer,
 };
 use serde_core::de::{Deserializer as _, EnumAccess, VariantAccess};
 use std::borrow::Cow;
@@ -14,17 +14,24 @@ use std::borrow::Cow;
 pub struct TaggedEnumValueAcc<'a, 'de, R> {
 	de: &'a mut Deserializer<R>,
 	string: Option<Cow<'de, str>>,
+	/// the variants of the enum, if they are known
+	variants: &'static [&'static str],
 }
 
 impl<'a, 'de, R: Read<'de>> TaggedEnumValueAcc<'a, 'de, R> {
-	pub fn new(de: &'a mut Deserializer<R>) -> Self {
-		TaggedEnumValueAcc { de, string: None }
+	pub fn new(de: &'a mut Deserializer<R>, variants: &'static [&'static str]) -> Self {
+		TaggedEnumValueAcc {
+			de,
+			string: None,
+			variants,
+		}
 	}
 
 	pub fn with_tag(de: &'a mut Deserializer<R>, string: Cow<'de, str>) -> Self {
 		TaggedEnumValueAcc {
 			de,
 			string: Some(string),
+			variants: &[],
 		}
 	}
 }
@@ -37,14 +44,14 @@ impl<'de, R: Read<'de>> EnumAccess<'de> for TaggedEnumValueAcc<'_, 'de, R> {
 	where
 		V: serde_core::de::DeserializeSeed<'de>,
 	{
-		if let Some(string) = self.string.take() {
-			let fake = FakeStringDeserializer::new(string);
-			let variant = seed.deserialize(fake)?;
-			Ok((variant, self))
+		let variant = if let Some(string) = self.string.take() {
+			seed.deserialize(FakeStringDeserializer::new(string))
 		} else {
-			let variant = seed.deserialize(&mut *self.de)?;
-			Ok((variant, self))
-		}
+			seed.deserialize(&mut *self.de)
+		};
+
+		let variant = variant.map_err(|err| with_variants(err, self.variants))?;
+		Ok((variant, self))
 	}
 }
 
@@ -73,9 +80,33 @@ impl<'de, R: Read<'de>> VariantAccess<'de> for TaggedEnumValueAcc<'_, 'de, R> {
 		let mut variant = TaggedValue::new(self.de);
 		let val = seed.deserialize(&mut variant);
 
-		let val = if !variant.is_map {
-			self.de.discard_commata();
-			let peek = self.de.peek_any().ok_or(Error::EOF)?;
+		// a single value in brackets is the first element of the sequence
+		let is_scalar = variant.bracket_assert && !variant.is_seq;
+		let is_map = variant.is_map;
+		let mut val = val.map_err(|err| {
+			if is_scalar && err.path.is_none() {
+				self.de.path.push(Segment::Index(0));
+				let err = self.de.attach_path(err);
+				self.de.path.pop();
+				err
+			} else {
+				err
+			}
+		});
+
+		let val = if !is_map {
+			// an error that already knows where it happened is more
+			// useful than complaining about the missing end of the sequence
+			if let Err(err) = val {
+				if err.span.is_some() {
+					return Err(err);
+				}
+
+				val = Err(err);
+			}
+
+			self.de.discard_commata()?;
+			let peek = self.de.peek_any()?.ok_or(Error::EOF)?;
 
 			let seq_end_point = self.de.read.position();
 
@@ -114,7 +145,7 @@ impl<'de, R: Read<'de>> VariantAccess<'de> for TaggedEnumValueAcc<'_, 'de, R> {
 		V: serde_core::de::Visitor<'de>,
 	{
 		let _ = self.de.peek_line()?.ok_or(Error::EOF)?;
-		self.de.deserialize_map(visitor)
+		self.de.visit_map(visitor, true)
 	}
 }
 
@@ -122,6 +153,7 @@ struct TaggedValue<'a, R> {
 	de: &'a mut Deserializer<R>,
 	bracket_assert: bool,
 	is_map: bool,
+	is_seq: bool,
 }
 
 impl<'a, 'de, R: Read<'de>> TaggedValue<'a, R> {
@@ -130,6 +162,7 @@ impl<'a, 'de, R: Read<'de>> TaggedValue<'a, R> {
 			de,
 			bracket_assert: false,
 			is_map: false,
+			is_seq: false,
 		}
 	}
 
@@ -138,7 +171,7 @@ impl<'a, 'de, R: Read<'de>> TaggedValue<'a, R> {
 			return Ok(());
 		}
 
-		let peek = self.de.read.peek().ok_or(Error::EOF)?;
+		let peek = self.de.read.peek()?.ok_or(Error::EOF)?;
 		if peek != '[' {
 			let point = self.de.read.position();
 			let code = ErrorCode::ExpectedSeq(peek);
@@ -147,11 +180,33 @@ impl<'a, 'de, R: Read<'de>> TaggedValue<'a, R> {
 			self.de.read.discard();
 
 			self.bracket_assert = true;
-			let _ = self.de.peek_any().ok_or(Error::EOF);
+			self.de.peek_any()?;
 
 			Ok(())
 		}
 	}
+
+	fn visit_map<V>(&mut self, visitor: V, is_struct: bool) -> Result<V::Value, Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		if !self.bracket_assert {
+			let peek = self.de.read.peek()?.ok_or(Error::EOF)?;
+			if peek == '[' {
+				self.de.read.discard();
+			} else if peek == '{' {
+				self.is_map = true;
+			}
+		}
+
+		self.de.visit_map(visitor, is_struct)
+	}
+}
+
+impl<'de, R: Read<'de>> Locate for TaggedValue<'_, R> {
+	fn position(&mut self) -> Position {
+		self.de.read.position()
+	}
 }
 
 impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut TaggedValue<'_, R> {
@@ -304,9 +359,9 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut TaggedValue<'
 		V: serde_core::de::Visitor<'de>,
 	{
 		self.assert_bracket()?;
-		let peek = self.de.peek_any().ok_or(Error::EOF)?;
+		let peek = self.de.peek_any()?.ok_or(Error::EOF)?;
 		match peek {
-			'"' | '\'' => {
+			ch if is_quote(ch) => {
 				let (r#ref, sp) = self.de.str_bytes()?;
 				match r#ref {
 					Ref::Borrow(b) => visitor.visit_borrowed_bytes(b).map_err(|e| add_span(e, sp)),
@@ -336,7 +391,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut TaggedValue<'
 		V: serde_core::de::Visitor<'de>,
 	{
 		self.assert_bracket()?;
-		let peek = self.de.read.peek().ok_or(Error::EOF)?;
+		let peek = self.de.read.peek()?.ok_or(Error::EOF)?;
 		if peek == ']' {
 			visitor.visit_none()
 		} else {
@@ -381,11 +436,12 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut TaggedValue<'
 		V: serde_core::de::Visitor<'de>,
 	{
 		self.assert_bracket()?;
+		self.is_seq = true;
 
 		let acc = SeqAcc::new(self.de);
 		let val = visitor.visit_seq(acc)?;
 
-		self.de.discard_commata();
+		self.de.discard_commata()?;
 
 		Ok(val)
 	}
@@ -415,41 +471,43 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut TaggedValue<'
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.de.read.peek().ok_or(Error::EOF)?;
-		if peek == '[' {
-			self.de.read.discard();
-		} else if peek == '{' {
-			self.is_map = true;
-		}
-
-		self.de.deserialize_map(visitor)
+		self.visit_map(visitor, false)
 	}
 
 	fn deserialize_struct<V>(
 		self,
-		_name: &'static str,
-		_fields: &'static [&'static str],
+		name: &'static str,
+		fields: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		self.deserialize_map(visitor)
+		if spanned::is_spanned(name, fields) {
+			// the span starts inside of the brackets
+			if self.de.read.peek()? == Some('[') {
+				self.assert_bracket()?;
+			}
+
+			return visitor.visit_map(SpannedAcc::new(self));
+		}
+
+		self.visit_map(visitor, true)
 	}
 
 	fn deserialize_enum<V>(
 		self,
 		_name: &'static str,
-		_variants: &'static [&'static str],
+		variants: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
 		self.assert_bracket()?;
-		let peek = self.de.read.peek().ok_or(Error::EOF)?;
-		if peek == '"' || peek == '\'' {
-			let acc = TaggedUnitEnumAcc::new(&mut *self.de);
+		let peek = self.de.read.peek()?.ok_or(Error::EOF)?;
+		if is_quote(peek) {
+			let acc = TaggedUnitEnumAcc::new(&mut *self.de, variants);
 			visitor.visit_enum(acc)
 		} else {
 			let point = self.de.read.position();
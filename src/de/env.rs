@@ -0,0 +1,96 @@
+//! expansion of environment variables and `~` in strings.
+
+use crate::error::{Error, ErrorCode, Span};
+use std::collections::HashMap;
+
+/// the environment that variables are expanded from.
+///
+/// set with [`Deserializer::set_environment`](crate::Deserializer::set_environment).
+pub trait Environment {
+	/// returns the value of the environment variable `name`, if it is set
+	fn var(&self, name: &str) -> Option<String>;
+
+	/// returns the home directory, that `~` expands to
+	fn home(&self) -> Option<String> {
+		self.var("HOME")
+	}
+}
+
+/// the [`Environment`] of the current process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemEnvironment;
+
+impl Environment for SystemEnvironment {
+	fn var(&self, name: &str) -> Option<String> {
+		std::env::var(name).ok()
+	}
+}
+
+impl Environment for HashMap<String, String> {
+	fn var(&self, name: &str) -> Option<String> {
+		self.get(name).cloned()
+	}
+}
+
+/// expand `${VAR}`, `${VAR:-default}` and a leading `~/` in the string `str`
+/// with the `span`, returns `None` if there is nothing to expand.
+///
+/// `$${` is a literal `${`.
+pub fn expand(str: &str, env: &dyn Environment, span: Span) -> Result<Option<String>, Error> {
+	let is_home = str == "~" || str.starts_with("~/");
+	if !is_home && !str.contains("${") {
+		return Ok(None);
+	}
+
+	let mut expanded = String::with_capacity(str.len());
+	let mut rest = str;
+	if is_home {
+		let home = env.home().ok_or_else(|| {
+			let code = ErrorCode::UndefinedEnvVar("HOME".to_owned());
+			Error::with_span(code, span)
+		})?;
+
+		expanded.push_str(&home);
+		rest = &rest[1..];
+	}
+
+	while let Some(start) = rest.find("${") {
+		// an escaped `$${` is kept as `${`
+		if let Some(before) = rest[..start].strip_suffix('$') {
+			expanded.push_str(before);
+			expanded.push_str("${");
+			rest = &rest[start + 2..];
+			continue;
+		}
+
+		expanded.push_str(&rest[..start]);
+
+		let Some(end) = rest[start..].find('}') else {
+			return Err(Error::with_span(ErrorCode::UnterminatedEnvVar, span));
+		};
+
+		let var = &rest[start + 2..start + end];
+		let (name, default) = match var.split_once(":-") {
+			Some((name, default)) => (name, Some(default)),
+			None => (var, None),
+		};
+
+		// like in the shell, the default is also used for empty variables
+		let value = match default {
+			Some(default) => env
+				.var(name)
+				.filter(|value| !value.is_empty())
+				.unwrap_or_else(|| default.to_owned()),
+			None => env.var(name).ok_or_else(|| {
+				let code = ErrorCode::UndefinedEnvVar(name.to_owned());
+				Error::with_span(code, span)
+			})?,
+		};
+		expanded.push_str(&value);
+
+		rest = &rest[start + end + 1..];
+	}
+
+	expanded.push_str(rest);
+	Ok(Some(expanded))
+}
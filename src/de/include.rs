@@ -0,0 +1,348 @@
+//! the `include "path"` directive.
+//!
+//! an included source is spliced into the map the directive is in, as if its
+//! entries were written in place of the directive.
+
+use super::read::{self, IoRead, Read, Ref};
+use crate::error::{Error, ErrorCode, Include, Position, Span};
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+};
+
+/// a source loaded by a [`Resolver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+	/// the name of the source, like its path
+	///
+	/// used to resolve includes relative to this source and to detect cycles,
+	/// so two names should be equal if and only if they refer to the same source.
+	pub name: String,
+	/// the content of the source
+	pub text: String,
+}
+
+/// loads the sources of `include` directives.
+///
+/// set with [`Deserializer::set_resolver`](crate::Deserializer::set_resolver).
+pub trait Resolver {
+	/// load the source at `path`, that was included by the source named `from`,
+	/// or by the main input if `from` is `None`.
+	///
+	/// # errors
+	///
+	/// returns an error if the source can't be loaded.
+	fn resolve(&mut self, path: &str, from: Option<&str>) -> std::io::Result<Source>;
+
+	/// returns the name of the main input, if it is one of the sources of this
+	/// resolver, so that including it is detected as a cycle
+	fn main_name(&self) -> Option<&str> {
+		None
+	}
+}
+
+/// a [`Resolver`] that reads included files from the filesystem.
+///
+/// paths are resolved relative to the directory of the file that includes
+/// them, and relative to the base directory for the main input. the
+/// names of the sources are their canonicalized paths.
+#[derive(Debug, Clone)]
+pub struct FsResolver {
+	base: PathBuf,
+	/// the canonicalized path of the main input, if it is a file
+	main: Option<String>,
+}
+
+impl FsResolver {
+	/// create a resolver that resolves the includes of the main input
+	/// relative to the directory `base`
+	pub fn new(base: impl Into<PathBuf>) -> Self {
+		FsResolver {
+			base: base.into(),
+			main: None,
+		}
+	}
+
+	/// create a resolver for a main input that was read from the file at `path`
+	///
+	/// its includes are resolved relative to the directory of the file,
+	/// and including the file itself is a cycle.
+	pub fn for_file(path: impl AsRef<Path>) -> Self {
+		let path = path.as_ref();
+		let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+		let base = path.parent().unwrap_or(Path::new("")).to_owned();
+		let main = Some(path.to_string_lossy().into_owned());
+
+		FsResolver { base, main }
+	}
+}
+
+impl Resolver for FsResolver {
+	fn resolve(&mut self, path: &str, from: Option<&str>) -> std::io::Result<Source> {
+		let dir = match from {
+			Some(from) => Path::new(from).parent().unwrap_or(Path::new("")),
+			None => &self.base,
+		};
+
+		let path = std::fs::canonicalize(dir.join(path))?;
+		let text = std::fs::read_to_string(&path)?;
+		let name = path.to_string_lossy().into_owned();
+
+		Ok(Source { name, text })
+	}
+
+	fn main_name(&self) -> Option<&str> {
+		self.main.as_deref()
+	}
+}
+
+/// a [`Resolver`] that looks up included sources by their path in memory.
+///
+/// ```
+/// # use mayfig::de::MemoryResolver;
+/// # use std::collections::HashMap;
+/// let mut resolver = MemoryResolver::new();
+/// resolver.insert("binds.mf", "mod+q = \"close\"");
+///
+/// let mut de = mayfig::Deserializer::from_str("include \"binds.mf\"\n");
+/// de.set_resolver(resolver);
+///
+/// let binds = serde::Deserialize::deserialize(&mut de);
+/// let binds: HashMap<String, String> = binds.unwrap();
+/// assert_eq!(binds["mod+q"], "close");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+	sources: HashMap<String, String>,
+	main: Option<String>,
+}
+
+impl MemoryResolver {
+	/// create an empty resolver
+	pub fn new() -> Self {
+		MemoryResolver::default()
+	}
+
+	/// add a source, that can be included as `name`
+	pub fn insert(&mut self, name: impl Into<String>, text: impl Into<String>) {
+		self.sources.insert(name.into(), text.into());
+	}
+
+	/// set the name of the main input, so that including it is a cycle
+	pub fn set_main_name(&mut self, name: impl Into<String>) {
+		self.main = Some(name.into());
+	}
+}
+
+impl Resolver for MemoryResolver {
+	fn resolve(&mut self, path: &str, _from: Option<&str>) -> std::io::Result<Source> {
+		let Some(text) = self.sources.get(path) else {
+			let err = std::io::Error::new(std::io::ErrorKind::NotFound, "source not found");
+			return Err(err);
+		};
+
+		Ok(Source {
+			name: path.to_owned(),
+			text: text.clone(),
+		})
+	}
+
+	fn main_name(&self) -> Option<&str> {
+		self.main.as_deref()
+	}
+}
+
+/// read the `include` of a directive at the current position, up to its path,
+/// returns `false` if there is none
+pub fn directive<'de, R: Read<'de>>(read: &mut R, scratch: &mut String) -> Result<bool, Error> {
+	if read.peek()? != Some('i') {
+		return Ok(false);
+	}
+
+	let start = read.position();
+	read.mark();
+
+	let found = keyword(read, scratch);
+	if let Ok(false) = found {
+		read.rewind(start);
+	}
+
+	read.commit();
+	found
+}
+
+/// read a word, returns whether it is `include` followed by a path
+fn keyword<'de, R: Read<'de>>(read: &mut R, scratch: &mut String) -> Result<bool, Error> {
+	scratch.clear();
+	let word = read.word(scratch)?;
+	let is_include = &*word == "include";
+
+	while read.peek()?.is_some_and(read::is_whitespace_line) {
+		read.discard();
+	}
+
+	Ok(is_include && read.peek()?.is_some_and(read::is_quote))
+}
+
+/// an included source
+struct Included {
+	name: String,
+	/// the source that included this one
+	parent: usize,
+	read: IoRead<std::io::Empty>,
+}
+
+/// reads the main input and the sources included into it
+pub struct Sources<R> {
+	main: R,
+	included: Vec<Included>,
+	/// the source that is currently read, `0` being the main input
+	current: usize,
+	resolver: Option<Box<dyn Resolver>>,
+	/// the sources that were included, by the position after their directive
+	directives: HashMap<Position, usize>,
+}
+
+impl<'de, R: Read<'de>> Sources<R> {
+	pub fn new(main: R) -> Self {
+		Sources {
+			main,
+			included: Vec::new(),
+			current: 0,
+			resolver: None,
+			directives: HashMap::new(),
+		}
+	}
+
+	pub fn set_resolver(&mut self, resolver: Box<dyn Resolver>) {
+		self.resolver = Some(resolver);
+	}
+
+	fn get(&self, source: usize) -> Option<&Included> {
+		self.included.get(source.checked_sub(1)?)
+	}
+
+	pub fn name(&self, source: usize) -> Option<&str> {
+		self.get(source).map(|included| &*included.name)
+	}
+
+	pub fn text(&self, source: usize) -> Option<&str> {
+		self.get(source).map(|included| included.read.text())
+	}
+
+	/// continue reading in the source included by the directive with the `span`,
+	/// loading it if it wasn't already
+	pub fn include(&mut self, path: &str, span: Span) -> Result<(), Error> {
+		let Span::Span(_, end) = span else {
+			unreachable!("the directive is a span")
+		};
+
+		let source = match self.directives.get(&end) {
+			Some(&source) => source,
+			None => {
+				let source = self
+					.load(path)
+					.map_err(|code| Error::with_span(code, span))?;
+				self.directives.insert(end, source);
+				source
+			}
+		};
+
+		self.rewind(Position::start(source));
+		Ok(())
+	}
+
+	fn load(&mut self, path: &str) -> Result<usize, ErrorCode> {
+		let from = self.name(self.current).map(str::to_owned);
+		let resolved = match &mut self.resolver {
+			Some(resolver) => resolver.resolve(path, from.as_deref()),
+			None => Err(std::io::Error::new(
+				std::io::ErrorKind::Unsupported,
+				"no resolver set",
+			)),
+		};
+
+		let resolved = resolved.map_err(|error| {
+			let path = path.to_owned();
+			ErrorCode::Include(Box::new(Include { path, error }))
+		})?;
+
+		// walk up the sources that include the current one, up to the main input
+		let mut ancestor = self.get(self.current);
+		while let Some(included) = ancestor {
+			if included.name == resolved.name {
+				return Err(ErrorCode::IncludeCycle(resolved.name));
+			}
+			ancestor = self.get(included.parent);
+		}
+
+		let main = self.resolver.as_deref().and_then(Resolver::main_name);
+		if main == Some(&*resolved.name) {
+			return Err(ErrorCode::IncludeCycle(resolved.name));
+		}
+
+		let source = self.included.len() + 1;
+		self.included.push(Included {
+			name: resolved.name,
+			parent: self.current,
+			read: IoRead::source(resolved.text, source),
+		});
+
+		Ok(source)
+	}
+
+	fn read(&mut self) -> &mut dyn Read<'de> {
+		match self.current {
+			0 => &mut self.main,
+			source => &mut self.included[source - 1].read,
+		}
+	}
+}
+
+impl<'de, R: Read<'de>> Read<'de> for Sources<R> {
+	fn peek(&mut self) -> Result<Option<char>, Error> {
+		self.read().peek()
+	}
+
+	fn next(&mut self) -> Result<Option<char>, Error> {
+		self.read().next()
+	}
+
+	fn discard(&mut self) {
+		self.read().discard();
+	}
+
+	fn position(&mut self) -> Position {
+		self.read().position()
+	}
+
+	fn rewind(&mut self, pos: Position) {
+		self.current = pos.source;
+		self.read().rewind(pos);
+	}
+
+	// included sources keep their whole text, so only the main input is marked
+	fn mark(&mut self) {
+		self.main.mark();
+	}
+
+	fn commit(&mut self) {
+		self.main.commit();
+	}
+
+	fn num<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read().num(scratch)
+	}
+
+	fn word<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read().word(scratch)
+	}
+
+	fn str<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read().str(scratch)
+	}
+
+	fn comment<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read().comment(scratch)
+	}
+}
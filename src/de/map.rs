@@ -1,11 +1,12 @@
 use super::{
+	access::{Locate, SpannedAcc},
 	add_span,
 	r#enum::TaggedEnumKeyAcc,
-	read::{Read, Ref},
+	read::{is_quote, Read, Ref},
 };
 use crate::{
-	error::{Error, ErrorCode, Span},
-	Deserializer,
+	error::{Error, ErrorCode, Position, Span},
+	spanned, Deserializer,
 };
 use serde_core::forward_to_deserialize_any;
 use std::borrow::Cow;
@@ -20,6 +21,12 @@ impl<'a, 'de, R: Read<'de>> MapKey<'a, R> {
 	}
 }
 
+impl<'de, R: Read<'de>> Locate for MapKey<'_, R> {
+	fn position(&mut self) -> Position {
+		self.de.read.position()
+	}
+}
+
 impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut MapKey<'_, R> {
 	type Error = Error;
 
@@ -27,7 +34,7 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut MapKey<'_, R>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.de.peek_any().ok_or(Error::EOF)?;
+		let peek = self.de.peek_any()?.ok_or(Error::EOF)?;
 		match peek {
 			'[' => self.deserialize_seq(visitor),
 			'{' => self.deserialize_map(visitor),
@@ -255,29 +262,33 @@ impl<'de, R: Read<'de>> serde_core::de::Deserializer<'de> for &mut MapKey<'_, R>
 
 	fn deserialize_struct<V>(
 		self,
-		_name: &'static str,
-		_fields: &'static [&'static str],
-		_visitor: V,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
+		if spanned::is_spanned(name, fields) {
+			return visitor.visit_map(SpannedAcc::new(self));
+		}
+
 		Err(Error::new(ErrorCode::UnsupportedMapKey("struct")))
 	}
 
 	fn deserialize_enum<V>(
 		self,
 		_name: &'static str,
-		_variants: &'static [&'static str],
+		variants: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value, Self::Error>
 	where
 		V: serde_core::de::Visitor<'de>,
 	{
-		let peek = self.de.read.peek().ok_or(Error::EOF)?;
-		if peek.is_ascii_alphabetic() || peek == '"' || peek == '\'' {
+		let peek = self.de.read.peek()?.ok_or(Error::EOF)?;
+		if peek.is_ascii_alphabetic() || is_quote(peek) {
 			let start = self.de.read.position();
-			let acc = TaggedEnumKeyAcc::new(self);
+			let acc = TaggedEnumKeyAcc::new(self, variants);
 			visitor.visit_enum(acc).map_err(|err| {
 				let end = self.de.read.position();
 				add_span(err, Span::Span(start, end))
@@ -1,16 +1,18 @@
-use crate::error::{Error, ErrorCode, Position};
+use crate::error::{Error, ErrorCode, Position, Span};
 use std::{iter::Peekable, ops::Deref, str::CharIndices};
 
 impl Position {
-	fn new() -> Self {
+	/// the start of the source
+	pub fn start(source: usize) -> Self {
 		Position {
 			line: 1,
 			col: 1,
 			index: 0,
+			source,
 		}
 	}
 
-	fn next(&mut self, ch: char) {
+	pub(crate) fn next(&mut self, ch: char) {
 		self.index += ch.len_utf8();
 		if ch == '\n' {
 			self.line += 1;
@@ -53,19 +55,34 @@ impl<'de, 's> From<Ref<'de, 's, str>> for String {
 }
 
 pub trait Read<'de> {
-	fn peek(&mut self) -> Option<char>;
+	fn peek(&mut self) -> Result<Option<char>, Error>;
 
-	fn next(&mut self) -> Option<char>;
+	fn next(&mut self) -> Result<Option<char>, Error>;
 
 	fn discard(&mut self);
 
 	fn position(&mut self) -> Position;
 
+	/// go back to a position that was returned by [`Read::position`] before,
+	/// while the reader is marked
+	fn rewind(&mut self, pos: Position);
+
+	/// start keeping what is read, to be able to rewind to the current position
+	///
+	/// every mark has to be released with [`Read::commit`].
+	fn mark(&mut self) {}
+
+	/// release a [`Read::mark`], what was read can be dropped once no mark is left
+	fn commit(&mut self) {}
+
 	fn num<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error>;
 
 	fn word<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error>;
 
 	fn str<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error>;
+
+	/// read the rest of the line, after the `#` of a comment
+	fn comment<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error>;
 }
 
 pub struct StrRead<'de> {
@@ -79,7 +96,7 @@ impl<'de> StrRead<'de> {
 		StrRead {
 			input,
 			iter: input.char_indices().peekable(),
-			pos: Position::new(),
+			pos: Position::start(0),
 		}
 	}
 
@@ -91,67 +108,394 @@ impl<'de> StrRead<'de> {
 }
 
 impl<'de> Read<'de> for StrRead<'de> {
-	fn peek(&mut self) -> Option<char> {
-		self.iter.peek().map(|x| x.1)
+	fn peek(&mut self) -> Result<Option<char>, Error> {
+		Ok(self.iter.peek().map(|x| x.1))
 	}
 
-	fn next(&mut self) -> Option<char> {
-		self.iter.next().map(|x| x.1).inspect(|x| self.pos.next(*x))
+	fn next(&mut self) -> Result<Option<char>, Error> {
+		Ok(self.iter.next().map(|x| x.1).inspect(|x| self.pos.next(*x)))
 	}
 
 	fn discard(&mut self) {
-		let _ = self.next();
+		if let Some((_, ch)) = self.iter.next() {
+			self.pos.next(ch);
+		}
 	}
 
 	fn position(&mut self) -> Position {
 		self.pos
 	}
 
+	fn rewind(&mut self, pos: Position) {
+		self.iter = self.input[pos.index..].char_indices().peekable();
+		self.pos = pos;
+	}
+
 	fn num<'s>(&mut self, _scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
 		let start = self.position().index;
+		scan_num(self, |_| {})?;
 
-		if let Some('+' | '-') = self.peek() {
-			self.discard();
+		let borrow = self.slice(start);
+		let r#ref = Ref::Borrow(borrow);
+		Ok(r#ref)
+	}
+
+	fn word<'s>(&mut self, _scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		let start = self.position().index;
+
+		while let Some(peek) = self.peek()? {
+			if peek.is_ascii_alphanumeric() || peek == '_' || peek == '-' || peek == '+' {
+				self.discard();
+			} else if is_delimiter(peek) || peek == '$' {
+				// a variable can continue the word
+				break;
+			} else {
+				let point = self.position();
+				let code = ErrorCode::ExpectedAsciiAlphanumeric(peek);
+				return Err(Error::with_point(code, point));
+			}
 		}
 
-		if let Some('.') = self.peek() {
+		let borrow = self.slice(start);
+		let r#ref = Ref::Borrow(borrow);
+		Ok(r#ref)
+	}
+
+	fn comment<'s>(&mut self, _scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		let start = self.position().index;
+		while self.peek()?.is_some_and(|peek| peek != '\n') {
 			self.discard();
+		}
 
-			if let Some('a'..='z' | 'A'..='Z') = self.peek() {
-				while let Some('a'..='z' | 'A'..='Z') = self.peek() {
+		let borrow = self.slice(start);
+		let r#ref = Ref::Borrow(borrow);
+		Ok(r#ref)
+	}
+
+	fn str<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		let quote = self.next()?.ok_or(Error::EOF)?;
+		debug_assert!(is_quote(quote), "is {:?}", quote);
+
+		let r#ref = if quote == '`' {
+			let start = self.position().index;
+			loop {
+				let peek = self.peek()?.ok_or(Error::EOF)?;
+				if peek == '`' {
+					let borrow = self.slice(start);
 					self.discard();
+					break Ref::Borrow(borrow);
+				} else if is_raw_control(peek) {
+					let point = self.position();
+					let code = ErrorCode::UnescapedControl(peek);
+					return Err(Error::with_point(code, point));
 				}
 
-				let borrow = self.slice(start);
-				let r#ref = Ref::Borrow(borrow);
-				return Ok(r#ref);
+				self.discard();
 			}
-		}
+		} else if self.peek()? == Some(quote) {
+			self.discard();
 
-		while let Some(peek) = self.peek() {
-			if let '0'..='9' | '.' | 'e' | '-' | '+' = peek {
+			if self.peek()? == Some(quote) {
 				self.discard();
-			} else if is_delimiter(peek) {
-				break;
+
+				parse_multiline(self, quote, scratch)?;
+				Ref::Scratch(scratch.as_str())
 			} else {
+				Ref::Borrow("")
+			}
+		} else {
+			let mut start = self.position().index;
+			loop {
+				let peek = self.peek()?.ok_or(Error::EOF)?;
+
+				if peek == quote {
+					if scratch.is_empty() {
+						let borrow = self.slice(start);
+						self.discard();
+
+						break Ref::Borrow(borrow);
+					} else {
+						let slice = self.slice(start);
+
+						scratch.push_str(slice);
+						self.discard();
+						break Ref::Scratch(scratch);
+					}
+				}
+
+				if peek.is_ascii_control() {
+					let point = self.position();
+					let code = ErrorCode::UnescapedControl(peek);
+					return Err(Error::with_point(code, point));
+				} else if peek == '\\' {
+					let slice = self.slice(start);
+					scratch.push_str(slice);
+
+					parse_escape(self, scratch)?;
+
+					start = self.position().index;
+				} else {
+					self.discard();
+				}
+			}
+		};
+
+		if let Some(peek) = self.peek()? {
+			if !is_delimiter(peek) {
 				let point = self.position();
-				let code = ErrorCode::ExpectedNumeric(peek);
+				let code = ErrorCode::ExpectedDelimiter(peek);
 				return Err(Error::with_point(code, point));
 			}
 		}
 
-		let borrow = self.slice(start);
-		let r#ref = Ref::Borrow(borrow);
 		Ok(r#ref)
 	}
+}
 
-	fn word<'s>(&mut self, _scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
-		let start = self.position().index;
+pub struct SliceRead<'de> {
+	read: StrRead<'de>,
+	/// whether the valid part of the input is followed by invalid utf8
+	invalid: bool,
+}
+
+impl<'de> SliceRead<'de> {
+	pub fn new(input: &'de [u8]) -> Self {
+		let (valid, invalid) = match std::str::from_utf8(input) {
+			Ok(valid) => (valid, false),
+			Err(err) => {
+				let valid = &input[..err.valid_up_to()];
+				let valid = std::str::from_utf8(valid).expect("should be valid utf8");
+				(valid, true)
+			}
+		};
+
+		SliceRead {
+			read: StrRead::new(valid),
+			invalid,
+		}
+	}
+
+	/// error for when the valid part of the input has been exhausted
+	fn eof(&mut self) -> Error {
+		if self.invalid {
+			let point = self.read.position();
+			Error::with_point(ErrorCode::InvalidUtf8, point)
+		} else {
+			Error::EOF
+		}
+	}
+}
+
+impl<'de> Read<'de> for SliceRead<'de> {
+	fn peek(&mut self) -> Result<Option<char>, Error> {
+		match self.read.peek()? {
+			None if self.invalid => Err(self.eof()),
+			peek => Ok(peek),
+		}
+	}
+
+	fn next(&mut self) -> Result<Option<char>, Error> {
+		match self.read.next()? {
+			None if self.invalid => Err(self.eof()),
+			next => Ok(next),
+		}
+	}
+
+	fn discard(&mut self) {
+		self.read.discard();
+	}
+
+	fn position(&mut self) -> Position {
+		self.read.position()
+	}
+
+	fn rewind(&mut self, pos: Position) {
+		self.read.rewind(pos);
+	}
+
+	fn mark(&mut self) {
+		self.read.mark();
+	}
+
+	fn commit(&mut self) {
+		self.read.commit();
+	}
+
+	fn num<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read.num(scratch)
+	}
+
+	fn word<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read.word(scratch)
+	}
 
-		while let Some(peek) = self.peek() {
+	fn comment<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		self.read.comment(scratch)
+	}
+
+	fn str<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		match self.read.str(scratch) {
+			Err(err) if matches!(err.code, ErrorCode::Eof) => Err(self.eof()),
+			res => res,
+		}
+	}
+}
+
+pub struct IoRead<R> {
+	bytes: std::io::Bytes<std::io::BufReader<R>>,
+	peek: Option<char>,
+	pos: Position,
+	/// the text that was read from `base` on, to be able to rewind
+	history: String,
+	/// the index of the start of the history
+	base: usize,
+	/// the number of marks that weren't committed yet
+	marks: usize,
+	/// whether the whole history is kept, for a source that is already in memory
+	retain: bool,
+}
+
+impl<R: std::io::Read> IoRead<R> {
+	pub fn new(reader: R) -> Self {
+		IoRead {
+			bytes: std::io::Read::bytes(std::io::BufReader::new(reader)),
+			peek: None,
+			pos: Position::start(0),
+			history: String::new(),
+			base: 0,
+			marks: 0,
+			retain: false,
+		}
+	}
+
+	/// the text that is kept to be able to rewind, all of it for a source in memory
+	pub fn text(&self) -> &str {
+		&self.history
+	}
+
+	/// the next char of the history, if the reader was rewound
+	fn replay(&self) -> Option<char> {
+		let index = self.pos.index - self.base;
+		self.history[index..].chars().next()
+	}
+
+	fn next_byte(&mut self) -> Result<Option<u8>, Error> {
+		match self.bytes.next() {
+			Some(Ok(byte)) => Ok(Some(byte)),
+			Some(Err(err)) => {
+				let code = ErrorCode::Io(err);
+				Err(Error::with_point(code, self.pos))
+			}
+			None => Ok(None),
+		}
+	}
+
+	/// decode the next utf8 encoded char from the reader
+	fn decode(&mut self) -> Result<Option<char>, Error> {
+		let Some(first) = self.next_byte()? else {
+			return Ok(None);
+		};
+
+		let len = match first {
+			0x00..=0x7f => return Ok(Some(char::from(first))),
+			0xc0..=0xdf => 2,
+			0xe0..=0xef => 3,
+			0xf0..=0xf7 => 4,
+			_ => return Err(Error::with_point(ErrorCode::InvalidUtf8, self.pos)),
+		};
+
+		let mut buf = [first, 0, 0, 0];
+		for byte in &mut buf[1..len] {
+			let next = self.next_byte()?;
+			*byte = next.ok_or(Error::with_point(ErrorCode::InvalidUtf8, self.pos))?;
+		}
+
+		let Ok(str) = std::str::from_utf8(&buf[..len]) else {
+			return Err(Error::with_point(ErrorCode::InvalidUtf8, self.pos));
+		};
+		Ok(str.chars().next())
+	}
+}
+
+impl IoRead<std::io::Empty> {
+	/// a reader for a source that is already in memory, with the id `source`
+	pub fn source(text: String, source: usize) -> Self {
+		IoRead {
+			bytes: std::io::Read::bytes(std::io::BufReader::new(std::io::empty())),
+			peek: None,
+			pos: Position::start(source),
+			history: text,
+			base: 0,
+			marks: 0,
+			retain: true,
+		}
+	}
+}
+
+impl<'de, R: std::io::Read> Read<'de> for IoRead<R> {
+	fn peek(&mut self) -> Result<Option<char>, Error> {
+		if let Some(replay) = self.replay() {
+			return Ok(Some(replay));
+		}
+
+		if self.peek.is_none() {
+			self.peek = self.decode()?;
+		}
+
+		Ok(self.peek)
+	}
+
+	fn next(&mut self) -> Result<Option<char>, Error> {
+		let next = self.peek()?;
+		self.discard();
+		Ok(next)
+	}
+
+	fn discard(&mut self) {
+		if let Some(replay) = self.replay() {
+			self.pos.next(replay);
+		} else if let Some(ch) = self.peek.take() {
+			self.pos.next(ch);
+
+			if self.marks > 0 || self.retain {
+				self.history.push(ch);
+			} else {
+				// nothing can rewind to before the current position
+				self.history.clear();
+				self.base = self.pos.index;
+			}
+		}
+	}
+
+	fn position(&mut self) -> Position {
+		self.pos
+	}
+
+	fn rewind(&mut self, pos: Position) {
+		debug_assert!(pos.index >= self.base, "the history was dropped");
+		self.pos = pos;
+	}
+
+	fn mark(&mut self) {
+		self.marks += 1;
+	}
+
+	fn commit(&mut self) {
+		self.marks -= 1;
+	}
+
+	fn num<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		scan_num(self, |ch| scratch.push(ch))?;
+		Ok(Ref::Scratch(scratch))
+	}
+
+	fn word<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		while let Some(peek) = self.peek()? {
 			if peek.is_ascii_alphanumeric() || peek == '_' || peek == '-' || peek == '+' {
+				scratch.push(peek);
 				self.discard();
-			} else if is_delimiter(peek) {
+			} else if is_delimiter(peek) || peek == '$' {
+				// a variable can continue the word
 				break;
 			} else {
 				let point = self.position();
@@ -160,65 +504,253 @@ impl<'de> Read<'de> for StrRead<'de> {
 			}
 		}
 
-		let borrow = self.slice(start);
-		let r#ref = Ref::Borrow(borrow);
-		Ok(r#ref)
+		Ok(Ref::Scratch(scratch))
 	}
 
-	fn str<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
-		let quote = self.next().ok_or(Error::EOF)?;
-		debug_assert!(matches!(quote, '"' | '\''), "is {:?}", quote);
+	fn comment<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		while let Some(peek) = self.peek()? {
+			if peek == '\n' {
+				break;
+			}
+
+			scratch.push(peek);
+			self.discard();
+		}
 
-		let mut start = self.position().index;
-		let r#ref = loop {
-			let peek = self.peek().ok_or(Error::EOF)?;
+		Ok(Ref::Scratch(scratch))
+	}
 
-			if peek == quote {
-				if scratch.is_empty() {
-					let borrow = self.slice(start);
+	fn str<'s>(&mut self, scratch: &'s mut String) -> Result<Ref<'de, 's, str>, Error> {
+		let quote = self.next()?.ok_or(Error::EOF)?;
+		debug_assert!(is_quote(quote), "is {:?}", quote);
+
+		if quote == '`' {
+			loop {
+				let peek = self.peek()?.ok_or(Error::EOF)?;
+				if peek == '`' {
 					self.discard();
+					break;
+				} else if is_raw_control(peek) {
+					let point = self.position();
+					let code = ErrorCode::UnescapedControl(peek);
+					return Err(Error::with_point(code, point));
+				}
 
-					break Ref::Borrow(borrow);
-				} else {
-					let slice = self.slice(start);
+				scratch.push(peek);
+				self.discard();
+			}
+		} else if self.peek()? == Some(quote) {
+			self.discard();
 
-					scratch.push_str(slice);
+			if self.peek()? == Some(quote) {
+				self.discard();
+				parse_multiline(self, quote, scratch)?;
+			}
+		} else {
+			loop {
+				let peek = self.peek()?.ok_or(Error::EOF)?;
+
+				if peek == quote {
+					self.discard();
+					break;
+				} else if peek.is_ascii_control() {
+					let point = self.position();
+					let code = ErrorCode::UnescapedControl(peek);
+					return Err(Error::with_point(code, point));
+				} else if peek == '\\' {
+					parse_escape(self, scratch)?;
+				} else {
+					scratch.push(peek);
 					self.discard();
-					break Ref::Scratch(scratch);
 				}
 			}
+		}
 
-			if peek.is_ascii_control() {
+		if let Some(peek) = self.peek()? {
+			if !is_delimiter(peek) {
 				let point = self.position();
-				let code = ErrorCode::UnescapedControl(peek);
+				let code = ErrorCode::ExpectedDelimiter(peek);
 				return Err(Error::with_point(code, point));
-			} else if peek == '\\' {
-				let slice = self.slice(start);
-				scratch.push_str(slice);
+			}
+		}
 
-				self.discard();
-				parse_escape(self, scratch)?;
+		Ok(Ref::Scratch(scratch))
+	}
+}
 
-				start = self.position().index;
-			} else {
-				self.discard();
+/// scan a number, passing every consumed char to `push`
+///
+/// integers can have a `0x`, `0o` or `0b` prefix and can contain `_` separators.
+/// the validity of the number is checked when parsing it.
+pub fn scan_num<'de, R: Read<'de>>(read: &mut R, mut push: impl FnMut(char)) -> Result<(), Error> {
+	if let Some(sign @ ('+' | '-')) = read.peek()? {
+		push(sign);
+		read.discard();
+	}
+
+	if let Some('.') = read.peek()? {
+		push('.');
+		read.discard();
+
+		if let Some('a'..='z' | 'A'..='Z') = read.peek()? {
+			while let Some(ch @ ('a'..='z' | 'A'..='Z')) = read.peek()? {
+				push(ch);
+				read.discard();
 			}
+
+			return Ok(());
+		}
+	}
+
+	let mut prefixed = false;
+	if let Some('0') = read.peek()? {
+		push('0');
+		read.discard();
+
+		if let Some(prefix @ ('x' | 'o' | 'b')) = read.peek()? {
+			push(prefix);
+			read.discard();
+			prefixed = true;
+		}
+	}
+
+	while let Some(peek) = read.peek()? {
+		let is_num = if prefixed {
+			peek.is_ascii_hexdigit() || peek == '_'
+		} else {
+			matches!(peek, '0'..='9' | '.' | 'e' | '-' | '+' | '_')
 		};
 
-		if let Some(peek) = self.peek() {
-			if !is_delimiter(peek) {
-				let point = self.position();
-				let code = ErrorCode::ExpectedDelimiter(peek);
+		if is_num {
+			push(peek);
+			read.discard();
+		} else if is_delimiter(peek) {
+			break;
+		} else {
+			let point = read.position();
+			let code = ErrorCode::ExpectedNumeric(peek);
+			return Err(Error::with_point(code, point));
+		}
+	}
+
+	Ok(())
+}
+
+/// parse the body of a multi-line string, starting after the opening quotes
+///
+/// the opening quotes have to be followed by a newline, and the closing quotes
+/// have to be on their own line. the whitespace in front of the closing quotes
+/// is stripped from the start of every line.
+fn parse_multiline<'de, R: Read<'de>>(
+	read: &mut R,
+	quote: char,
+	scratch: &mut String,
+) -> Result<(), Error> {
+	while let Some(' ' | '\t') = read.peek()? {
+		read.discard();
+	}
+
+	let peek = read.peek()?.ok_or(Error::EOF)?;
+	if peek == '\r' {
+		read.discard();
+	}
+
+	let peek = read.peek()?.ok_or(Error::EOF)?;
+	if peek != '\n' {
+		let point = read.position();
+		let code = ErrorCode::ExpectedNewline(peek);
+		return Err(Error::with_point(code, point));
+	}
+	read.discard();
+
+	// the leading whitespace and the content of every line
+	let mut lines = Vec::new();
+	let indent = 'lines: loop {
+		let start = read.position();
+
+		let mut prefix = String::new();
+		while let Some(ch @ (' ' | '\t')) = read.peek()? {
+			prefix.push(ch);
+			read.discard();
+		}
+
+		let mut content = String::new();
+		loop {
+			let peek = read.peek()?.ok_or(Error::EOF)?;
+
+			if peek == quote {
+				let point = read.position();
+
+				let mut count = 0;
+				while count < 3 && read.peek()? == Some(quote) {
+					read.discard();
+					count += 1;
+				}
+
+				if count == 3 {
+					if !content.is_empty() {
+						let code = ErrorCode::ExpectedNewline(quote);
+						return Err(Error::with_point(code, point));
+					}
+
+					break 'lines prefix;
+				}
+
+				content.extend(std::iter::repeat_n(quote, count));
+			} else if peek == '\n' {
+				read.discard();
+				break;
+			} else if peek == '\r' {
+				let point = read.position();
+				read.discard();
+
+				if read.peek()? == Some('\n') {
+					read.discard();
+					break;
+				}
+
+				let code = ErrorCode::UnescapedControl(peek);
 				return Err(Error::with_point(code, point));
+			} else if peek == '\\' {
+				parse_escape(read, &mut content)?;
+			} else if peek.is_ascii_control() && peek != '\t' {
+				let point = read.position();
+				let code = ErrorCode::UnescapedControl(peek);
+				return Err(Error::with_point(code, point));
+			} else {
+				content.push(peek);
+				read.discard();
 			}
 		}
 
-		Ok(r#ref)
+		lines.push((start, prefix, content));
+	};
+
+	for (idx, (start, prefix, content)) in lines.into_iter().enumerate() {
+		if idx != 0 {
+			scratch.push('\n');
+		}
+
+		if let Some(rest) = prefix.strip_prefix(&*indent) {
+			scratch.push_str(rest);
+		} else if !content.is_empty() || !indent.starts_with(&*prefix) {
+			let code = ErrorCode::InsufficientIndent;
+			return Err(Error::with_point(code, start));
+		}
+
+		scratch.push_str(&content);
 	}
+
+	Ok(())
 }
 
+/// parse an escape sequence, starting at the backslash
 fn parse_escape<'de, R: Read<'de>>(read: &mut R, scratch: &mut String) -> Result<(), Error> {
-	let peek = read.peek().ok_or(Error::EOF)?;
+	let start = read.position();
+	let backslash = read.next()?;
+	debug_assert_eq!(backslash, Some('\\'));
+
+	let peek = read.peek()?.ok_or(Error::EOF)?;
 	match peek {
 		'"' => scratch.push('"'),
 		'\'' => scratch.push('\''),
@@ -229,7 +761,11 @@ fn parse_escape<'de, R: Read<'de>>(read: &mut R, scratch: &mut String) -> Result
 		't' => scratch.push('\t'),
 		'b' => scratch.push('\x08'),
 		'f' => scratch.push('\x0c'),
-		'u' => todo!("unicode escape"),
+		'u' => {
+			let ch = parse_unicode_escape(read, start)?;
+			scratch.push(ch);
+			return Ok(());
+		}
 		_ => {
 			let point = read.position();
 			let code = ErrorCode::UnknownEscape(peek);
@@ -241,7 +777,109 @@ fn parse_escape<'de, R: Read<'de>>(read: &mut R, scratch: &mut String) -> Result
 	Ok(())
 }
 
-fn is_delimiter(ch: char) -> bool {
+/// parse a `\u{1F600}` or a `\uXXXX` unicode escape, optionally
+/// followed by a second `\uXXXX` escape for surrogate pairs
+fn parse_unicode_escape<'de, R: Read<'de>>(read: &mut R, start: Position) -> Result<char, Error> {
+	let u = read.next()?;
+	debug_assert_eq!(u, Some('u'));
+	let mut escape = String::from("\\u");
+
+	let code = if read.peek()? == Some('{') {
+		escape.push('{');
+		read.discard();
+
+		let mut code = 0;
+		let mut len = 0;
+		loop {
+			let ch = read.next()?.ok_or(Error::EOF)?;
+			escape.push(ch);
+
+			if ch == '}' && len != 0 {
+				break code;
+			}
+
+			match ch.to_digit(16) {
+				Some(digit) if len < 6 => {
+					code = code * 16 + digit;
+					len += 1;
+				}
+				_ => return Err(invalid_unicode_escape(read, escape, start)),
+			}
+		}
+	} else {
+		let high = parse_hex4(read, &mut escape, start)?;
+		if (0xd800..0xdc00).contains(&high) {
+			for expected in ['\\', 'u'] {
+				let ch = read.next()?.ok_or(Error::EOF)?;
+				escape.push(ch);
+				if ch != expected {
+					return Err(invalid_unicode_escape(read, escape, start));
+				}
+			}
+
+			let low = parse_hex4(read, &mut escape, start)?;
+			if !(0xdc00..0xe000).contains(&low) {
+				return Err(invalid_unicode_escape(read, escape, start));
+			}
+
+			0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+		} else {
+			high
+		}
+	};
+
+	char::from_u32(code).ok_or_else(|| invalid_unicode_escape(read, escape, start))
+}
+
+fn parse_hex4<'de, R: Read<'de>>(
+	read: &mut R,
+	escape: &mut String,
+	start: Position,
+) -> Result<u32, Error> {
+	let mut code = 0;
+	for _ in 0..4 {
+		let ch = read.next()?.ok_or(Error::EOF)?;
+		escape.push(ch);
+
+		let Some(digit) = ch.to_digit(16) else {
+			return Err(invalid_unicode_escape(read, std::mem::take(escape), start));
+		};
+		code = code * 16 + digit;
+	}
+
+	Ok(code)
+}
+
+fn invalid_unicode_escape<'de, R: Read<'de>>(
+	read: &mut R,
+	escape: String,
+	start: Position,
+) -> Error {
+	let span = Span::Span(start, read.position());
+	let code = ErrorCode::InvalidUnicodeEscape(escape);
+	Error::with_span(code, span)
+}
+
+/// peek next character on the current line, that isn't whitespace.
+///
+/// returns an [`ErrorCode::UnexpectedNewline`] error if nothing is found before a new line
+pub fn peek_line<'de, R: Read<'de>>(read: &mut R) -> Result<Option<char>, Error> {
+	while let Some(peek) = read.peek()? {
+		if is_whitespace_line(peek) {
+			read.discard();
+		} else if peek == '\n' || peek == '#' {
+			let point = read.position();
+			let code = ErrorCode::UnexpectedNewline;
+			return Err(Error::with_point(code, point));
+		} else {
+			return Ok(Some(peek));
+		}
+	}
+
+	Ok(None)
+}
+
+pub fn is_delimiter(ch: char) -> bool {
 	is_whitespace(ch)
 		|| ch == '='
 		|| ch == ','
@@ -252,6 +890,15 @@ fn is_delimiter(ch: char) -> bool {
 		|| ch == '#'
 }
 
+/// control characters that are not allowed in raw strings
+fn is_raw_control(ch: char) -> bool {
+	ch.is_ascii_control() && !matches!(ch, '\n' | '\r' | '\t')
+}
+
+pub fn is_quote(ch: char) -> bool {
+	matches!(ch, '"' | '\'' | '`')
+}
+
 pub fn is_whitespace(ch: char) -> bool {
 	matches!(ch, ' ' | '\t' | '\r' | '\n')
 }
@@ -262,7 +909,7 @@ pub fn is_whitespace_line(ch: char) -> bool {
 
 #[cfg(test)]
 mod test {
-	use super::{Read, StrRead};
+	use super::{IoRead, Read, StrRead};
 
 	#[test]
 	fn str() {
@@ -295,4 +942,29 @@ mod test {
 		let p4 = s4.str(&mut scratch).unwrap();
 		assert_eq!(&*p4, r#"t\\""\"t"#);
 	}
+
+	#[test]
+	fn history() {
+		let mut read = IoRead::new("abcdef".as_bytes());
+		let next = |read: &mut IoRead<_>| Read::next(read).unwrap();
+
+		next(&mut read);
+		assert_eq!(read.text(), "");
+
+		let start = Read::position(&mut read);
+		Read::mark(&mut read);
+		next(&mut read);
+		next(&mut read);
+		assert_eq!(read.text(), "bc");
+
+		Read::rewind(&mut read, start);
+		Read::commit(&mut read);
+		assert_eq!(next(&mut read), Some('b'));
+		assert_eq!(next(&mut read), Some('c'));
+
+		// the history is dropped once it was replayed
+		assert_eq!(next(&mut read), Some('d'));
+		assert_eq!(read.text(), "");
+		assert_eq!(Read::position(&mut read).index, 4);
+	}
 }
@@ -0,0 +1,205 @@
+//! error recovery by skipping broken map entries.
+//!
+//! errors before the key of an entry is read are skipped in place, up to the
+//! next entry. after that the visitor of the map can't continue, so the error
+//! unwinds to the top, leaving a [`Frame`] for every value it passes through.
+//! the type is then deserialized again from the frames, which resume the open
+//! maps and sequences at the current position, so no input is read twice.
+
+use super::{access::MapState, parse_bool, read::Read, visit_number};
+use crate::error::{Error, ErrorCode, Position, Span};
+use serde_core::{
+	de::{value::SeqDeserializer, IntoDeserializer},
+	forward_to_deserialize_any,
+};
+use std::collections::HashSet;
+
+#[derive(Default)]
+pub struct Recovery {
+	pub errors: Vec<Error>,
+	/// the values an error is unwinding through, innermost first
+	pub frames: Vec<Frame>,
+	/// the values that are resumed, innermost first
+	pub resume: Vec<Frame>,
+	/// whether an error is unwinding to the top, to resume from its frames
+	pub unwinding: bool,
+	/// whether the unwinding error has to be reported
+	pub report: bool,
+	/// whether the value that just ended had an error skipped in it,
+	/// so errors like a missing field are expected
+	pub tainted: bool,
+	/// the positions that errors unwound at, to never resume twice at the same one
+	pub resumed: HashSet<Position>,
+}
+
+/// an open value that an error unwound through
+pub enum Frame {
+	Map(Box<MapState>),
+	/// the entry of the map before it, with the value after it
+	Entry(Key),
+	/// the sequence with the element at the index, which is the value after it
+	Seq(usize),
+}
+
+/// whether the input can't be read past `err`
+pub fn is_fatal(err: &Error) -> bool {
+	matches!(
+		err.code,
+		ErrorCode::Eof | ErrorCode::Io(_) | ErrorCode::InvalidUtf8
+	)
+}
+
+/// skip the rest of a map entry after an error, up to the next linebreak
+/// or `}` that isn't inside of one of the `depth` values that are still open
+pub fn resync<'de, R: Read<'de>>(read: &mut R, mut depth: usize) -> Result<(), Error> {
+	while let Some(ch) = read.peek()? {
+		match ch {
+			'\n' | '}' if depth == 0 => return Ok(()),
+			_ => read.discard(),
+		}
+
+		match ch {
+			'{' | '[' => depth += 1,
+			'}' | ']' => depth = depth.saturating_sub(1),
+			'#' => {
+				while read.peek()?.is_some_and(|ch| ch != '\n') {
+					read.discard();
+				}
+			}
+			'`' => while read.next()?.is_some_and(|ch| ch != '`') {},
+			'"' | '\'' => quoted(read, ch)?,
+			_ => {}
+		}
+	}
+
+	Ok(())
+}
+
+/// skip a string, after its opening `quote`
+fn quoted<'de, R: Read<'de>>(read: &mut R, quote: char) -> Result<(), Error> {
+	if read.peek()? == Some(quote) {
+		read.discard();
+		if read.peek()? != Some(quote) {
+			return Ok(());
+		}
+
+		read.discard();
+		while let Some(ch) = read.next()? {
+			if ch == '\\' {
+				read.discard();
+			} else if ch == quote && is_closing(read, quote)? {
+				return Ok(());
+			}
+		}
+
+		return Ok(());
+	}
+
+	while let Some(ch) = read.peek()?.filter(|&ch| ch != '\n') {
+		read.discard();
+		if ch == '\\' {
+			if read.peek()?.is_some_and(|ch| ch != '\n') {
+				read.discard();
+			}
+		} else if ch == quote {
+			break;
+		}
+	}
+
+	Ok(())
+}
+
+/// whether the two chars after the `quote` close a multiline string
+fn is_closing<'de, R: Read<'de>>(read: &mut R, quote: char) -> Result<bool, Error> {
+	for _ in 0..2 {
+		if read.peek()? != Some(quote) {
+			return Ok(false);
+		}
+		read.discard();
+	}
+
+	Ok(true)
+}
+
+/// the key of a resumed map entry, deserialized again from its scalars
+pub struct Key {
+	pub scalars: Vec<String>,
+	pub span: Span,
+}
+
+impl<'de> serde_core::de::Deserializer<'de> for Key {
+	type Error = Error;
+
+	fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		if self.scalars.len() != 1 {
+			return self.deserialize_seq(visitor);
+		}
+
+		let scalar = self.scalars.remove(0);
+		if super::is_valid_number(&scalar) {
+			visit_number(&scalar, self.span, visitor)
+		} else if let Ok(b) = parse_bool(&scalar, self.span) {
+			visitor.visit_bool(b)
+		} else {
+			visitor.visit_string(scalar)
+		}
+	}
+
+	fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		if self.scalars.len() != 1 {
+			return self.deserialize_seq(visitor);
+		}
+
+		visitor.visit_string(self.scalars.remove(0))
+	}
+
+	fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		self.deserialize_string(visitor)
+	}
+
+	fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		self.deserialize_string(visitor)
+	}
+
+	fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: serde_core::de::Visitor<'de>,
+	{
+		let span = self.span;
+		let scalars = self.scalars.into_iter().map(|scalar| Key {
+			scalars: vec![scalar],
+			span,
+		});
+
+		let mut seq = SeqDeserializer::new(scalars);
+		let val = visitor.visit_seq(&mut seq)?;
+		seq.end()?;
+		Ok(val)
+	}
+
+	forward_to_deserialize_any! {
+		bool u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64
+		char bytes byte_buf option unit unit_struct newtype_struct
+		tuple tuple_struct map struct enum ignored_any
+	}
+}
+
+impl IntoDeserializer<'_, Error> for Key {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self::Deserializer {
+		self
+	}
+}
@@ -0,0 +1,162 @@
+//! variables, defined with `$name = value` and substituted with `$name`.
+//!
+//! variables are scoped to the map they are defined in, and can only hold
+//! scalar values.
+//!
+//! the parsing is shared by the [`Deserializer`] and the event [`Parser`](crate::events::Parser).
+
+use super::{
+	read::{self, Read},
+	Deserializer,
+};
+use crate::error::{Error, ErrorCode, Span};
+
+#[derive(Debug, Clone)]
+pub struct Variable {
+	pub name: String,
+	pub value: String,
+	pub kind: Kind,
+}
+
+/// the kind of scalar a variable was defined as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+	Str,
+	Num,
+	Word,
+}
+
+/// a parser with variables in scope
+pub trait Scope<'de> {
+	type Read: Read<'de>;
+
+	fn read(&mut self) -> &mut Self::Read;
+
+	/// read a `$name` reference and look up its variable, with [`variable`]
+	fn variable(&mut self) -> Result<(Variable, Span), Error>;
+
+	/// read a scalar of the `kind`, as the value of a variable definition
+	fn scalar(&mut self, kind: Kind) -> Result<String, Error>;
+}
+
+impl<'de, R: Read<'de>> Scope<'de> for Deserializer<R> {
+	type Read = super::Sources<R>;
+
+	fn read(&mut self) -> &mut Self::Read {
+		&mut self.read
+	}
+
+	fn variable(&mut self) -> Result<(Variable, Span), Error> {
+		variable(&mut self.read, &self.variables)
+	}
+
+	fn scalar(&mut self, kind: Kind) -> Result<String, Error> {
+		let scalar = match kind {
+			Kind::Num => self.num()?.0,
+			Kind::Str => self.str()?.0,
+			Kind::Word => self.word()?.0,
+		};
+		Ok(scalar.to_owned())
+	}
+}
+
+/// read the name of a variable, after the `$`
+fn variable_name<'de, R: Read<'de>>(read: &mut R) -> Result<String, Error> {
+	let mut name = String::new();
+	while let Some(peek) = read.peek()? {
+		if !peek.is_ascii_alphanumeric() && peek != '_' {
+			break;
+		}
+
+		name.push(peek);
+		read.discard();
+	}
+
+	Ok(name)
+}
+
+/// read a `$name` reference and look up its variable
+pub fn variable<'de, R: Read<'de>>(
+	read: &mut R,
+	variables: &[Variable],
+) -> Result<(Variable, Span), Error> {
+	let start = read.position();
+	debug_assert_eq!(read.peek()?, Some('$'));
+	read.discard();
+
+	let name = variable_name(read)?;
+	let span = Span::Span(start, read.position());
+	if name.is_empty() {
+		let peek = read.peek()?.ok_or(Error::EOF)?;
+		let point = read.position();
+		let code = ErrorCode::ExpectedAsciiAlphanumeric(peek);
+		return Err(Error::with_point(code, point));
+	}
+
+	let variable = variables.iter().rev().find(|var| var.name == name);
+	match variable {
+		Some(variable) => Ok((variable.clone(), span)),
+		None => {
+			let code = ErrorCode::UndefinedVariable(name);
+			Err(Error::with_span(code, span))
+		}
+	}
+}
+
+/// read the `$name =` of a variable definition at the current position,
+/// returns `None` if there is none
+pub fn definition<'de, R: Read<'de>>(read: &mut R) -> Result<Option<String>, Error> {
+	if read.peek()? != Some('$') {
+		return Ok(None);
+	}
+
+	let start = read.position();
+	read.mark();
+
+	let found = assignment(read);
+	if let Ok(None) = found {
+		read.rewind(start);
+	}
+
+	read.commit();
+	found
+}
+
+/// read the `$name =`, returns `None` if it isn't followed by a `=`
+fn assignment<'de, R: Read<'de>>(read: &mut R) -> Result<Option<String>, Error> {
+	read.discard();
+	let name = variable_name(read)?;
+
+	while read.peek()?.is_some_and(read::is_whitespace_line) {
+		read.discard();
+	}
+
+	// keys can start with a variable too, like `$mod+q`
+	if name.is_empty() || read.peek()? != Some('=') {
+		return Ok(None);
+	}
+
+	read.discard();
+	Ok(Some(name))
+}
+
+/// read the value of a variable definition, after the `=`
+pub fn define<'de, S: Scope<'de>>(scope: &mut S, name: String) -> Result<Variable, Error> {
+	let peek = read::peek_line(scope.read())?.ok_or(Error::EOF)?;
+	let (value, kind) = match peek {
+		'$' => {
+			let (variable, _) = scope.variable()?;
+			(variable.value, variable.kind)
+		}
+		'0'..='9' | '.' | '-' | '+' => (scope.scalar(Kind::Num)?, Kind::Num),
+		ch if read::is_quote(ch) => (scope.scalar(Kind::Str)?, Kind::Str),
+		ch if ch.is_ascii_alphabetic() => (scope.scalar(Kind::Word)?, Kind::Word),
+		_ => {
+			let point = scope.read().position();
+			let code = ErrorCode::ExpectedQuote(peek);
+			return Err(Error::with_point(code, point));
+		}
+	};
+
+	Ok(Variable { name, value, kind })
+}
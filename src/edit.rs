@@ -0,0 +1,603 @@
+//! format-preserving editing of mayfig documents.
+//!
+//! a [`Document`] is edited in place, so comments, blank lines, the order
+//! of entries and the formatting of everything that isn't edited stay as
+//! they are.
+//!
+//! ```
+//! use mayfig::edit::Document;
+//!
+//! let text = "cursor {\n\t# the size of the cursor\n\txcursor-size = 24 # px\n}\n";
+//! let mut doc = text.parse::<Document>().unwrap();
+//!
+//! doc.set("cursor.xcursor-size", &32).unwrap();
+//! doc.insert("cursor.xcursor-theme", "Adwaita").unwrap();
+//!
+//! let edited = "cursor {\n\t# the size of the cursor\n\txcursor-size = 32 # px\n\txcursor-theme = \"Adwaita\"\n}\n";
+//! assert_eq!(doc.to_string(), edited);
+//! ```
+
+use crate::{
+	error::{ErrorCode, Path, Segment},
+	syntax::{self, NodeKind, SyntaxElement, SyntaxNode, TokenKind},
+	Error, Serializer,
+};
+use serde_core::{ser::SerializeMap, Serialize};
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+/// an editable mayfig document
+///
+/// paths are written like they are displayed in a
+/// [`Path`], like `input.keyboard.repeat-rate`, `bind."mod+t"[0]` or `offsets[1]`.
+/// an index into a tagged value, like `"spawn" [ "kitty" ]`, indexes its values.
+#[derive(Debug, Clone)]
+pub struct Document {
+	text: String,
+	root: SyntaxNode,
+}
+
+/// a value in the document that a path resolves to
+#[derive(Clone, Copy)]
+enum Target<'a> {
+	/// a category or a pair
+	Entry(&'a SyntaxNode),
+	/// a value in a sequence
+	Item(&'a SyntaxNode),
+}
+
+impl<'a> Target<'a> {
+	fn value(self) -> Option<&'a SyntaxNode> {
+		match self {
+			Target::Entry(entry) => entry.value(),
+			Target::Item(item) => Some(item),
+		}
+	}
+}
+
+impl Document {
+	/// parse the `text` into a document
+	///
+	/// # errors
+	///
+	/// returns the first syntax error in the text.
+	pub fn parse(text: &str) -> Result<Self, Error> {
+		let parse = syntax::parse(text);
+		if let Some(err) = parse.errors.into_iter().next() {
+			return Err(err);
+		}
+
+		Ok(Document {
+			text: text.to_owned(),
+			root: parse.root,
+		})
+	}
+
+	/// the syntax tree of the document
+	pub fn root(&self) -> &SyntaxNode {
+		&self.root
+	}
+
+	/// returns the value at the `path`, or the map of a category
+	pub fn get(&self, path: &str) -> Option<&SyntaxNode> {
+		let path = path.parse::<Path>().ok()?;
+		resolve(&self.root, path.segments())?.value()
+	}
+
+	/// replace the value at the `path`, which has to exist
+	///
+	/// # errors
+	///
+	/// returns an error if the path is invalid or doesn't exist,
+	/// or if the value can't be serialized.
+	pub fn set<T: ?Sized + Serialize>(&mut self, path: &str, value: &T) -> Result<(), Error> {
+		let path = path.parse::<Path>()?;
+		let target = resolve(&self.root, path.segments()).ok_or_else(|| unknown(&path))?;
+
+		let (range, replacement) = match target {
+			Target::Entry(entry) => {
+				let indent = self.indent(entry.span().range().start);
+
+				// everything after the key, like ` = 32` or ` { ... }`
+				let serialized = self.serialize(value)?;
+				let parse = syntax::parse(&serialized);
+				let new = parse.root.entries().next().expect("serialized an entry");
+				let rest = key_end(new)..new.span().range().end;
+
+				let range = key_end(entry)..entry.span().range().end;
+				(range, reindent(&serialized[rest], &indent))
+			}
+			Target::Item(item) => {
+				let range = item.span().range();
+				let indent = self.indent(range.start);
+				let mut value = reindent(&self.serialize_value(value)?, &indent);
+
+				// keep the values around it from tagging each other
+				if is_tag(&value, &self.text[range.end..]) {
+					value.push(',');
+				}
+				let before = self.text[..range.start].trim_end_matches([' ', '\t']);
+				if is_tag(before, &value) {
+					value.insert_str(0, ", ");
+					(before.len()..range.end, value)
+				} else {
+					(range, value)
+				}
+			}
+		};
+
+		self.splice(range, &replacement);
+		Ok(())
+	}
+
+	/// insert the value at the `path`
+	///
+	/// if the path ends in a key, the entry is added at the end of its
+	/// category, creating the categories that don't exist yet, or its value
+	/// is replaced if it already exists. if the path ends in an index, the
+	/// value is inserted into the sequence at that index.
+	///
+	/// # errors
+	///
+	/// returns an error if the path is invalid, if the sequence or a value
+	/// on the way to the path isn't a category, or if the value can't be serialized.
+	pub fn insert<T: ?Sized + Serialize>(&mut self, path: &str, value: &T) -> Result<(), Error> {
+		let parsed = path.parse::<Path>()?;
+		let segments = parsed.segments();
+		let (last, parents) = segments.split_last().expect("paths aren't empty");
+
+		if let Segment::Index(index) = *last {
+			let seq = resolve(&self.root, parents)
+				.and_then(Target::value)
+				.and_then(|value| container(value, NodeKind::Seq))
+				.ok_or_else(|| unknown(&parsed))?;
+			return self.insert_item(seq.clone(), index, value, &parsed);
+		}
+
+		if let Some(Target::Entry(_)) = resolve(&self.root, segments) {
+			return self.set(path, value);
+		}
+
+		// the longest prefix of the path that already exists
+		let (depth, map) = (1..=parents.len())
+			.rev()
+			.find_map(|depth| {
+				let target = resolve(&self.root, &parents[..depth])?;
+				Some((
+					depth,
+					target.value().and_then(|v| container(v, NodeKind::Map)),
+				))
+			})
+			.unwrap_or((0, Some(&self.root)));
+		let map = map.ok_or_else(|| unknown(&parsed))?;
+
+		let keys = segments[depth..]
+			.iter()
+			.map(|segment| match segment {
+				Segment::Key(key) => Ok(key.as_str()),
+				Segment::Index(_) => Err(unknown(&parsed)),
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let entry = self.serialize_entry(&keys, value)?;
+		self.insert_entry(map.clone(), &entry);
+		Ok(())
+	}
+
+	/// remove the entry or the sequence value at the `path`
+	///
+	/// # errors
+	///
+	/// returns an error if the path is invalid or doesn't exist.
+	pub fn remove(&mut self, path: &str) -> Result<(), Error> {
+		let path = path.parse::<Path>()?;
+		let target = resolve(&self.root, path.segments()).ok_or_else(|| unknown(&path))?;
+
+		let range = match target {
+			Target::Entry(entry) => self.line(entry.span().range(), false),
+			Target::Item(item) => {
+				let range = item.span().range();
+				if self.starts_line(range.start) {
+					self.line(range, true)
+				} else {
+					let range = self.inline_item(range);
+					let before = self.text[..range.start].trim_end_matches([' ', '\t']);
+					if is_tag(before, &self.text[range.end..]) {
+						// keep the values around it apart with a comma
+						self.splice(before.len()..range.end, ", ");
+						return Ok(());
+					}
+
+					range
+				}
+			}
+		};
+
+		self.splice(range, "");
+		Ok(())
+	}
+
+	/// replace the text in the `range` and parse the document again
+	fn splice(&mut self, range: Range<usize>, replacement: &str) {
+		self.text.replace_range(range, replacement);
+
+		let parse = syntax::parse(&self.text);
+		debug_assert!(parse.errors.is_empty(), "{:?}", parse.errors);
+		self.root = parse.root;
+	}
+
+	fn insert_item<T: ?Sized + Serialize>(
+		&mut self,
+		seq: SyntaxNode,
+		index: usize,
+		value: &T,
+		path: &Path,
+	) -> Result<(), Error> {
+		let items = seq
+			.nodes()
+			.filter(|node| node.kind() != NodeKind::Error)
+			.map(|node| node.span().range())
+			.collect::<Vec<_>>();
+		if index > items.len() {
+			return Err(unknown(path));
+		}
+
+		let is_multiline = items.iter().any(|item| self.starts_line(item.start));
+		let comma = if seq.tokens().any(|token| token.kind() == TokenKind::Comma) {
+			","
+		} else {
+			""
+		};
+
+		let (at, text) = if let Some(item) = items.get(index) {
+			let indent = self.indent(item.start);
+			let value = reindent(&self.serialize_value(value)?, &indent);
+			if is_multiline {
+				(item.start, format!("{value}{comma}\n{indent}"))
+			} else {
+				let sep = separator(comma, &value, &self.text[item.start..]);
+				let text = format!("{value}{sep} ");
+
+				// the value before can't tag the inserted one either
+				let prev = index.checked_sub(1).map(|prev| items[prev].end);
+				if let Some(prev) = prev.filter(|&prev| is_tag(&self.text[..prev], &value)) {
+					self.splice(item.start..item.start, &text);
+					self.splice(prev..prev, ",");
+					return Ok(());
+				}
+
+				(item.start, text)
+			}
+		} else if let Some(last) = items.last() {
+			let indent = self.indent(last.start);
+			let value = reindent(&self.serialize_value(value)?, &indent);
+			if is_multiline {
+				(last.end, format!("{comma}\n{indent}{value}"))
+			} else {
+				let sep = separator(comma, &self.text[..last.end], &value);
+				(last.end, format!("{sep} {value}"))
+			}
+		} else {
+			// replace the inside of the empty brackets
+			let range = seq.span().range();
+			let indent = self.indent(range.start);
+			let value = reindent(&self.serialize_value(value)?, &indent);
+			self.splice(range.start + 1..range.end - 1, &format!(" {value} "));
+			return Ok(());
+		};
+
+		self.splice(at..at, &text);
+		Ok(())
+	}
+
+	/// insert the serialized `entry` at the end of the `map`
+	fn insert_entry(&mut self, map: SyntaxNode, entry: &str) {
+		let last = map.entries().last().map(|last| last.span().range());
+		let range = map.span().range();
+
+		if let Some(last) = last {
+			let indent = self.indent(last.start);
+			let at = self.line_end(&map, last.end);
+			let entry = reindent(entry, &indent);
+			self.splice(at..at, &format!("\n{indent}{entry}"));
+		} else if map.kind() == NodeKind::Document {
+			let newline = if self.text.is_empty() || self.text.ends_with('\n') {
+				""
+			} else {
+				"\n"
+			};
+
+			let at = self.text.len();
+			self.splice(at..at, &format!("{newline}{entry}\n"));
+		} else {
+			let outer = self.indent(range.start);
+			let indent = format!("{outer}{}", self.indent_unit());
+			let entry = reindent(entry, &indent);
+
+			// the map is empty, so the inside is whitespace and comments
+			let inside = range.start + 1..range.end - 1;
+			if self.text[inside.clone()].contains('\n') {
+				self.splice(inside.start..inside.start, &format!("\n{indent}{entry}"));
+			} else {
+				self.splice(inside, &format!("\n{indent}{entry}\n{outer}"));
+			}
+		}
+	}
+
+	/// serialize the `value` as the entry `x = value`
+	fn serialize<T: ?Sized + Serialize>(&self, value: &T) -> Result<String, Error> {
+		let indent = self.indent_unit();
+
+		let mut vec = Vec::new();
+		let mut ser = Serializer::with_indent(&mut vec, indent.as_bytes());
+		Single(value).serialize(&mut ser)?;
+
+		let mut serialized = String::from_utf8(vec).expect("mayfig is valid utf8");
+		serialized.truncate(serialized.trim_end().len());
+		Ok(serialized)
+	}
+
+	/// serialize the `value` as a value in a sequence
+	fn serialize_value<T: ?Sized + Serialize>(&self, value: &T) -> Result<String, Error> {
+		let serialized = self.serialize(value)?;
+		let parse = syntax::parse(&serialized);
+		let entry = parse.root.entries().next().expect("serialized an entry");
+		let value = entry.value().expect("serialized a value");
+		Ok(value.to_string())
+	}
+
+	/// serialize the `value` as an entry, nested in categories for the `keys`
+	fn serialize_entry<T: ?Sized + Serialize>(
+		&self,
+		keys: &[&str],
+		value: &T,
+	) -> Result<String, Error> {
+		let (last, parents) = keys.split_last().expect("there is at least one key");
+		let serialized = self.serialize(value)?;
+
+		let mut entry = format!("{}{}", self.key(last)?, &serialized[1..]);
+		let indent = self.indent_unit();
+		for key in parents.iter().rev() {
+			let nested = reindent(&entry, &indent);
+			entry = format!("{} {{\n{indent}{nested}\n}}", self.key(key)?);
+		}
+
+		Ok(entry)
+	}
+
+	/// the `key`, quoted only if it has to be
+	fn key(&self, key: &str) -> Result<String, Error> {
+		let mut chars = key.chars();
+		let is_bare = chars
+			.next()
+			.is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+			&& chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '+'));
+		if is_bare {
+			Ok(key.to_owned())
+		} else {
+			self.serialize_value(key)
+		}
+	}
+
+	/// the indentation of the line the `index` is on
+	fn indent(&self, index: usize) -> String {
+		let start = self.text[..index].rfind('\n').map_or(0, |idx| idx + 1);
+		let line = &self.text[start..index];
+		let len = line.len() - line.trim_start_matches([' ', '\t']).len();
+		line[..len].to_owned()
+	}
+
+	/// the indentation of a single level, as used in the document
+	fn indent_unit(&self) -> String {
+		let nested = self
+			.root
+			.entries()
+			.filter(|entry| entry.kind() == NodeKind::Category)
+			.find_map(|category| category.entries().next());
+
+		match nested {
+			Some(entry) => {
+				let indent = self.indent(entry.span().range().start);
+				if indent.is_empty() {
+					"\t".to_owned()
+				} else {
+					indent
+				}
+			}
+			None => "\t".to_owned(),
+		}
+	}
+
+	/// returns true if only indentation is in front of the `index` on its line
+	fn starts_line(&self, index: usize) -> bool {
+		let start = self.text[..index].rfind('\n').map_or(0, |idx| idx + 1);
+		self.text[start..index]
+			.chars()
+			.all(|ch| ch == ' ' || ch == '\t')
+	}
+
+	/// the end of the comment after the entry that ends at `end`,
+	/// or `end` if there is no comment
+	fn line_end(&self, map: &SyntaxNode, end: usize) -> usize {
+		let after = map
+			.children()
+			.iter()
+			.skip_while(|child| child.span().range().end <= end);
+
+		let mut line_end = end;
+		for child in after {
+			match child {
+				SyntaxElement::Token(token) if token.kind() == TokenKind::Whitespace => {}
+				SyntaxElement::Token(token) if token.kind() == TokenKind::Comment => {
+					line_end = token.span().range().end;
+				}
+				_ => break,
+			}
+		}
+
+		line_end
+	}
+
+	/// extend the `range` over its whole line, including the comment after it
+	/// and a comma if `comma` is true, if nothing else is on the line
+	fn line(&self, range: Range<usize>, comma: bool) -> Range<usize> {
+		let mut end = range.end;
+		let rest = &self.text[end..];
+		let mut len = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+		if comma && rest[len..].starts_with(',') {
+			len += 1;
+			len += rest[len..].len() - rest[len..].trim_start_matches([' ', '\t']).len();
+		}
+		if rest[len..].starts_with('#') {
+			len += rest[len..].find('\n').unwrap_or(rest.len() - len);
+		}
+		end += len;
+
+		let rest = &self.text[end..];
+		if !self.starts_line(range.start) {
+			return range.start..end;
+		}
+
+		let start = self.text[..range.start]
+			.rfind('\n')
+			.map_or(0, |idx| idx + 1);
+		if rest.starts_with("\r\n") {
+			start..end + 2
+		} else if rest.starts_with('\n') || rest.is_empty() {
+			start..end + rest.len().min(1)
+		} else {
+			// something else, like a closing brace, is on the same line
+			range.start..end
+		}
+	}
+
+	/// the range of a value in a single line sequence, including the
+	/// separator to the next value or to the previous value if it's the last one
+	fn inline_item(&self, range: Range<usize>) -> Range<usize> {
+		let is_separator = |ch: char| ch == ' ' || ch == '\t' || ch == ',';
+
+		let rest = &self.text[range.end..];
+		let after = rest.len() - rest.trim_start_matches(is_separator).len();
+		if !rest[after..].starts_with(']') {
+			return range.start..range.end + after;
+		}
+
+		let before = self.text[..range.start].trim_end_matches(is_separator);
+		before.len()..range.end
+	}
+}
+
+impl FromStr for Document {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Document::parse(s)
+	}
+}
+
+impl Display for Document {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.text)
+	}
+}
+
+/// whether `left` ends with a string and `right` starts with a sequence or map,
+/// which would be read as a tagged value if only whitespace was between them
+fn is_tag(left: &str, right: &str) -> bool {
+	let left = left.trim_end_matches([' ', '\t']);
+	let right = right.trim_start_matches([' ', '\t']);
+	left.ends_with(['"', '\'', '`']) && right.starts_with(['[', '{'])
+}
+
+/// the separator between the values `left` and `right` in a single line sequence
+fn separator<'a>(comma: &'a str, left: &str, right: &str) -> &'a str {
+	if is_tag(left, right) {
+		","
+	} else {
+		comma
+	}
+}
+
+/// serializes the value as the only entry of a map, with the key `x`
+struct Single<'a, T: ?Sized>(&'a T);
+
+impl<T: ?Sized + Serialize> Serialize for Single<'_, T> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde_core::Serializer,
+	{
+		let mut map = serializer.serialize_map(Some(1))?;
+		map.serialize_entry("x", self.0)?;
+		map.end()
+	}
+}
+
+/// resolve the `segments` of a path in the `node`
+fn resolve<'a>(node: &'a SyntaxNode, segments: &[Segment]) -> Option<Target<'a>> {
+	let (first, rest) = segments.split_first()?;
+
+	let target = match first {
+		Segment::Key(key) => {
+			let map = container(node, NodeKind::Map)?;
+			let mut entries = map.entries().filter(|entry| {
+				entry.kind() != NodeKind::Include && entry.name().as_ref() == Some(key)
+			});
+
+			// with merged categories, the path can be in any of them
+			if !rest.is_empty() {
+				return entries.find_map(|entry| resolve(entry.value()?, rest));
+			}
+
+			Target::Entry(entries.next()?)
+		}
+		Segment::Index(index) => {
+			let seq = container(node, NodeKind::Seq)?;
+			let mut items = seq.nodes().filter(|node| node.kind() != NodeKind::Error);
+			Target::Item(items.nth(*index)?)
+		}
+	};
+
+	match rest {
+		[] => Some(target),
+		rest => resolve(target.value()?, rest),
+	}
+}
+
+/// returns the node if it is of the `kind`, or the value of a tagged value
+/// if it is of the `kind`, with documents counting as maps
+fn container(node: &SyntaxNode, kind: NodeKind) -> Option<&SyntaxNode> {
+	let node = match node.kind() {
+		NodeKind::Tagged => node.nodes().last()?,
+		_ => node,
+	};
+
+	match (node.kind(), kind) {
+		(NodeKind::Document, NodeKind::Map) => Some(node),
+		(found, kind) if found == kind => Some(node),
+		_ => None,
+	}
+}
+
+/// the end of the key of the entry
+fn key_end(entry: &SyntaxNode) -> usize {
+	let key = entry.key().expect("entries have a key");
+	key.span().range().end
+}
+
+/// indent every line after the first with the `indent`, except empty lines
+fn reindent(text: &str, indent: &str) -> String {
+	let mut lines = text.split('\n');
+	let mut reindented = lines.next().unwrap_or_default().to_owned();
+	for line in lines {
+		reindented.push('\n');
+		if !line.is_empty() {
+			reindented.push_str(indent);
+		}
+		reindented.push_str(line);
+	}
+
+	reindented
+}
+
+fn unknown(path: &Path) -> Error {
+	Error::new(ErrorCode::UnknownPath(path.to_string()))
+}
@@ -2,11 +2,19 @@
 
 use std::{cmp::Ordering, fmt::Display};
 
+#[cfg(feature = "diagnostics")]
+mod report;
+
+#[cfg(feature = "diagnostics")]
+pub use report::Report;
+
 /// a `mayfig::Error`
 #[derive(Debug)]
 pub struct Error {
 	pub(crate) code: ErrorCode,
 	pub(crate) span: Option<Span>,
+	/// boxed to keep the error small
+	pub(crate) path: Option<Box<Path>>,
 }
 
 impl Error {
@@ -21,34 +29,52 @@ impl Error {
 	pub fn span(&self) -> Option<Span> {
 		self.span
 	}
+
+	/// returns the path of map keys and sequence indices to the value
+	/// where the error happened
+	///
+	/// only ever returns `Some` when deserializing.
+	pub fn path(&self) -> Option<&Path> {
+		self.path.as_deref()
+	}
 }
 
 impl Error {
 	pub(crate) const EOF: Error = Error::new(ErrorCode::Eof);
 
 	pub(crate) const fn new(code: ErrorCode) -> Self {
-		Error { code, span: None }
+		Error {
+			code,
+			span: None,
+			path: None,
+		}
 	}
 
 	pub(crate) const fn with_point(code: ErrorCode, point: Position) -> Self {
 		let span = Some(Span::Point(point));
-		Error { code, span }
+		Error {
+			code,
+			span,
+			path: None,
+		}
 	}
 
 	pub(crate) const fn with_span(code: ErrorCode, span: Span) -> Self {
 		Error {
 			code,
 			span: Some(span),
+			path: None,
 		}
 	}
 }
 
 impl Display for Error {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		if let Some(span) = self.span {
-			write!(f, "{} at {}", self.code, span)
-		} else {
-			Display::fmt(&self.code, f)
+		match (&self.path, self.span) {
+			(Some(path), Some(span)) => write!(f, "{} at {}, {}", self.code, path, span),
+			(Some(path), None) => write!(f, "{} at {}", self.code, path),
+			(None, Some(span)) => write!(f, "{} at {}", self.code, span),
+			(None, None) => Display::fmt(&self.code, f),
 		}
 	}
 }
@@ -64,6 +90,16 @@ impl serde_core::de::Error for Error {
 		let code = ErrorCode::Custom(msg);
 		Error::new(code)
 	}
+
+	fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+		let code = ErrorCode::UnknownField(Box::new(Unknown::new(field, expected)));
+		Error::new(code)
+	}
+
+	fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+		let code = ErrorCode::UnknownVariant(Box::new(Unknown::new(variant, expected)));
+		Error::new(code)
+	}
 }
 
 impl serde_core::ser::Error for Error {
@@ -88,9 +124,10 @@ impl From<std::io::Error> for Error {
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ErrorCode {
-	/// failed to write into a [`Write`](std::io::Write).
+	/// failed to read from a [`Read`](std::io::Read) or write into a [`Write`](std::io::Write).
 	///
-	/// can currently only occur when serializing with [`to_writer`](crate::to_writer).
+	/// can currently only occur when deserializing with [`from_reader`](crate::from_reader)
+	/// or when serializing with [`to_writer`](crate::to_writer).
 	Io(std::io::Error),
 
 	/// unexpected end of file while parsing
@@ -100,9 +137,13 @@ pub enum ErrorCode {
 
 	/// unknown escape sequence in string
 	UnknownEscape(char),
+	/// invalid unicode escape sequence in string
+	InvalidUnicodeEscape(String),
 	/// unescaped control character in string
 	UnescapedControl(char),
 
+	/// line in a multi-line string is indented less than its closing quotes
+	InsufficientIndent,
 	/// expected newline, found value
 	ExpectedNewline(char),
 	/// expected value, found newline
@@ -149,6 +190,29 @@ pub enum ErrorCode {
 	/// unsupported map key
 	UnsupportedMapKey(&'static str),
 
+	/// unknown struct field
+	UnknownField(Box<Unknown>),
+	/// unknown enum variant
+	UnknownVariant(Box<Unknown>),
+	/// key occurs more than once in the same map
+	DuplicateKey(Box<Duplicate>),
+
+	/// included source couldn't be loaded
+	Include(Box<Include>),
+	/// source includes itself, directly or through other sources
+	IncludeCycle(String),
+	/// variable is not defined in the current scope
+	UndefinedVariable(String),
+	/// environment variable is not set
+	UndefinedEnvVar(String),
+	/// environment variable in a string is missing its closing `}`
+	UnterminatedEnvVar,
+
+	/// path couldn't be parsed
+	InvalidPath(String),
+	/// path doesn't exist in the document
+	UnknownPath(String),
+
 	/// custom serde error
 	Custom(String),
 }
@@ -157,6 +221,7 @@ impl std::error::Error for ErrorCode {
 	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
 		match self {
 			ErrorCode::Io(source) => Some(source),
+			ErrorCode::Include(include) => Some(&include.error),
 			_ => None,
 		}
 	}
@@ -169,13 +234,17 @@ impl Display for ErrorCode {
 			ErrorCode::Eof => f.write_str("end of file"),
 			ErrorCode::InvalidUtf8 => f.write_str("invalid utf8"),
 			ErrorCode::UnknownEscape(t) => write!(f, "unknown escape sequence {t:?}"),
+			ErrorCode::InvalidUnicodeEscape(t) => write!(f, "invalid unicode escape {t:?}"),
 			ErrorCode::UnescapedControl(ch) => write!(f, "unescaped control character {ch:?}"),
+			ErrorCode::InsufficientIndent => {
+				f.write_str("insufficient indentation in multi-line string")
+			}
 			ErrorCode::ExpectedNewline(t) => write!(f, "expected newline, found {t:?} first"),
 			ErrorCode::UnexpectedNewline => f.write_str("unexpected newline"),
 			ErrorCode::InvalidBool(t) => write!(f, "invalid boolean {t:?}"),
 			ErrorCode::InvalidNum(t) => write!(f, "invalid number {t:?}"),
 			ErrorCode::UnexpectedWord(t) => write!(f, "unexpected word {t:?}"),
-			ErrorCode::ExpectedQuote(t) => write!(f, "expected quote \" or ', got {t:?}"),
+			ErrorCode::ExpectedQuote(t) => write!(f, "expected quote \", ' or `, got {t:?}"),
 			ErrorCode::ExpectedValue(t) => {
 				write!(f, "expected value assignment '=' or '{{', got {t:?}")
 			}
@@ -183,7 +252,7 @@ impl Display for ErrorCode {
 			ErrorCode::ExpectedSeq(t) => write!(f, "expected seq '[', got {t:?}"),
 			ErrorCode::ExpectedSeqEnd(t) => write!(f, "expected end of seq ']', got {t:?}"),
 			ErrorCode::ExpectedEnum(t) => write!(f, "expected tagged enum, got {t:?}"),
-			ErrorCode::ExpectedBytes(t) => write!(f, "expected quote ', \" or seq, got {t:?}"),
+			ErrorCode::ExpectedBytes(t) => write!(f, "expected quote \", ', ` or seq, got {t:?}"),
 			ErrorCode::ExpectedDelimiter(t) => {
 				write!(f, "expected delimiter after string, got {t:?}")
 			}
@@ -200,13 +269,146 @@ impl Display for ErrorCode {
 			ErrorCode::UnsupportedNaN => f.write_str("unsupported nan"),
 			ErrorCode::UnsupportedNone => f.write_str("unsupported none"),
 			ErrorCode::UnsupportedMapKey(t) => write!(f, "unsupported map key type {t}"),
+			ErrorCode::DuplicateKey(duplicate) => {
+				let Duplicate { key, first, .. } = &**duplicate;
+				write!(f, "duplicate key `{key}` (first defined at {first})")
+			}
+			ErrorCode::Include(include) => {
+				let Include { path, error } = &**include;
+				write!(f, "failed to include {path:?}: {error}")
+			}
+			ErrorCode::IncludeCycle(name) => write!(f, "{name:?} includes itself"),
+			ErrorCode::UndefinedVariable(name) => write!(f, "undefined variable `${name}`"),
+			ErrorCode::UndefinedEnvVar(name) => {
+				write!(f, "environment variable `{name}` is not set")
+			}
+			ErrorCode::UnterminatedEnvVar => f.write_str("unterminated environment variable"),
+			ErrorCode::InvalidPath(path) => write!(f, "invalid path {path:?}"),
+			ErrorCode::UnknownPath(path) => write!(f, "path {path} doesn't exist"),
+			ErrorCode::UnknownField(unknown) => {
+				write!(f, "unknown field `{}`", unknown.name)?;
+				unknown.fmt_expected(f, "fields")
+			}
+			ErrorCode::UnknownVariant(unknown) => {
+				write!(f, "unknown variant `{}`", unknown.name)?;
+				unknown.fmt_expected(f, "variants")
+			}
 			ErrorCode::Custom(t) => write!(f, "{t}"),
 		}
 	}
 }
 
+/// find the closest candidate to `name`, if there is one that is close enough.
+/// like rustc, up to a third of `name` may be edited, but never all of it
+fn suggest(name: &str, candidates: &'static [&'static str]) -> Option<&'static str> {
+	let len = name.chars().count();
+	let max = len.max(3) / 3;
+	candidates
+		.iter()
+		.map(|&candidate| (distance(name, candidate), candidate))
+		.filter(|&(distance, _)| distance <= max && distance < len)
+		.min_by_key(|&(distance, _)| distance)
+		.map(|(_, candidate)| candidate)
+}
+
+/// the edit distance between two strings, in chars, counting
+/// swapped adjacent characters as a single edit
+fn distance(one: &str, two: &str) -> usize {
+	let one = one.chars().collect::<Vec<_>>();
+	let two = two.chars().collect::<Vec<_>>();
+
+	// rows of the distance matrix, `dist[i][j]` is the distance
+	// between `one[..i]` and `two[..j]`
+	let mut dist = vec![vec![0; two.len() + 1]; one.len() + 1];
+	for (i, row) in dist.iter_mut().enumerate() {
+		row[0] = i;
+	}
+	for (j, cell) in dist[0].iter_mut().enumerate() {
+		*cell = j;
+	}
+
+	for i in 1..=one.len() {
+		for j in 1..=two.len() {
+			let cost = usize::from(one[i - 1] != two[j - 1]);
+			let mut min = (dist[i - 1][j] + 1)
+				.min(dist[i][j - 1] + 1)
+				.min(dist[i - 1][j - 1] + cost);
+			if i > 1 && j > 1 && one[i - 1] == two[j - 2] && one[i - 2] == two[j - 1] {
+				min = min.min(dist[i - 2][j - 2] + 1);
+			}
+			dist[i][j] = min;
+		}
+	}
+
+	dist[one.len()][two.len()]
+}
+
+/// a key that occurs more than once in the same map
+#[derive(Debug)]
+pub struct Duplicate {
+	/// the key, with the values of complex keys separated by spaces
+	pub key: String,
+	/// the span of the first occurrence of the key
+	pub first: Span,
+	/// the span of the second occurrence of the key
+	pub second: Span,
+}
+
+/// an included source that couldn't be loaded
+#[derive(Debug)]
+pub struct Include {
+	/// the path of the `include` directive
+	pub path: String,
+	/// the error of the [`Resolver`](crate::de::Resolver)
+	pub error: std::io::Error,
+}
+
+/// an unknown struct field or enum variant
+#[derive(Debug)]
+pub struct Unknown {
+	/// the name that was found
+	pub name: String,
+	/// the names of the fields or variants that were expected
+	pub expected: &'static [&'static str],
+	/// the expected name closest to the one that was found, if any is close enough
+	pub suggestion: Option<&'static str>,
+}
+
+impl Unknown {
+	pub(crate) fn new(name: &str, expected: &'static [&'static str]) -> Self {
+		Unknown {
+			name: name.to_owned(),
+			expected,
+			suggestion: suggest(name, expected),
+		}
+	}
+
+	/// write the list of expected names like serde does, and the suggestion if there is one
+	fn fmt_expected(&self, f: &mut std::fmt::Formatter, kind: &str) -> std::fmt::Result {
+		match self.expected {
+			[] => write!(f, ", there are no {kind}")?,
+			[one] => write!(f, ", expected `{one}`")?,
+			[one, two] => write!(f, ", expected `{one}` or `{two}`")?,
+			[first, rest @ ..] => {
+				write!(f, ", expected one of `{first}`")?;
+				for name in rest {
+					write!(f, ", `{name}`")?;
+				}
+			}
+		}
+
+		match self.suggestion {
+			Some(suggestion) => write!(f, "; did you mean `{suggestion}`?"),
+			None => Ok(()),
+		}
+	}
+}
+
 /// an error position
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// this is `#[non_exhaustive]`, create one with [`Position::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
 pub struct Position {
 	/// line at which the error occured
 	///
@@ -220,6 +422,23 @@ pub struct Position {
 	///
 	/// 0-indexed, in bytes
 	pub index: usize,
+	/// source the position is in
+	///
+	/// `0` is the main input, other sources are included with the `include` directive,
+	/// see [`Deserializer::source_name`](crate::Deserializer::source_name).
+	pub source: usize,
+}
+
+impl Position {
+	/// create a position in the main input
+	pub const fn new(line: usize, col: usize, index: usize) -> Self {
+		Position {
+			line,
+			col,
+			index,
+			source: 0,
+		}
+	}
 }
 
 impl Display for Position {
@@ -305,11 +524,189 @@ impl PartialOrd for Span {
 	}
 }
 
+/// the path of map keys and sequence indices to a value
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Path(pub(crate) Vec<Segment>);
+
+impl Path {
+	/// returns the segments of the path, starting at the top level
+	pub fn segments(&self) -> &[Segment] {
+		&self.0
+	}
+}
+
+impl Display for Path {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for (idx, segment) in self.0.iter().enumerate() {
+			match segment {
+				Segment::Key(key) => {
+					if idx != 0 {
+						f.write_str(".")?;
+					}
+
+					let mut chars = key.chars();
+					let is_bare = chars
+						.next()
+						.is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+						&& chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+					if is_bare {
+						f.write_str(key)?;
+					} else {
+						write!(f, "{key:?}")?;
+					}
+				}
+				Segment::Index(index) => write!(f, "[{index}]")?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// parses a path in the format it is displayed in, like
+/// `input.keyboard.repeat-rate`, `bind."mod+t"[0]` or `offsets[1]`
+impl std::str::FromStr for Path {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || Error::new(ErrorCode::InvalidPath(s.to_owned()));
+
+		let mut segments = Vec::new();
+		let mut chars = s.chars().peekable();
+		while let Some(&peek) = chars.peek() {
+			match peek {
+				'[' => {
+					chars.next();
+
+					let mut index = String::new();
+					loop {
+						match chars.next().ok_or_else(invalid)? {
+							']' => break,
+							ch => index.push(ch),
+						}
+					}
+
+					let index = index.parse().map_err(|_| invalid())?;
+					segments.push(Segment::Index(index));
+					continue;
+				}
+				'.' if !segments.is_empty() => {
+					chars.next();
+				}
+				_ if !segments.is_empty() => return Err(invalid()),
+				_ => {}
+			}
+
+			let mut key = String::new();
+			if chars.peek() == Some(&'"') {
+				chars.next();
+				loop {
+					match chars.next().ok_or_else(invalid)? {
+						'"' => break,
+						'\\' => {
+							let escaped = match chars.next().ok_or_else(invalid)? {
+								'n' => '\n',
+								'r' => '\r',
+								't' => '\t',
+								'0' => '\0',
+								'u' => {
+									let mut hex = String::new();
+									if chars.next() != Some('{') {
+										return Err(invalid());
+									}
+									for ch in chars.by_ref().take_while(|&ch| ch != '}') {
+										hex.push(ch);
+									}
+
+									u32::from_str_radix(&hex, 16)
+										.ok()
+										.and_then(char::from_u32)
+										.ok_or_else(invalid)?
+								}
+								ch => ch,
+							};
+							key.push(escaped);
+						}
+						ch => key.push(ch),
+					}
+				}
+			} else {
+				while let Some(&ch) = chars.peek() {
+					if ch == '.' || ch == '[' {
+						break;
+					}
+
+					key.push(ch);
+					chars.next();
+				}
+
+				if key.is_empty() {
+					return Err(invalid());
+				}
+			}
+
+			segments.push(Segment::Key(key));
+		}
+
+		if segments.is_empty() {
+			return Err(invalid());
+		}
+
+		Ok(Path(segments))
+	}
+}
+
+/// a segment of a [`Path`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+	/// a map key
+	///
+	/// complex keys like sequences or tagged enums are represented
+	/// by their first value.
+	Key(String),
+	/// an index into a sequence
+	Index(usize),
+}
+
 #[cfg(test)]
 mod test {
-	use super::{Position, Span};
+	use super::{distance, suggest, Path, Position, Segment, Span};
 	use std::cmp::Ordering;
 
+	#[test]
+	fn parse_path() {
+		let path = "input.keyboard.repeat-rate".parse::<Path>().unwrap();
+		let keys = ["input", "keyboard", "repeat-rate"].map(|key| Segment::Key(key.to_owned()));
+		assert_eq!(path.segments(), keys);
+
+		let path = Path(vec![
+			Segment::Key("bind".to_owned()),
+			Segment::Key("mod+t \"\u{1b}".to_owned()),
+			Segment::Index(0),
+			Segment::Index(12),
+		]);
+		let displayed = path.to_string();
+		assert_eq!(displayed, r#"bind."mod+t \"\u{1b}"[0][12]"#);
+		assert_eq!(displayed.parse::<Path>().unwrap(), path);
+
+		for invalid in ["", ".a", "a.", "a[b]", "a[0", "\"a", "\"a\"b"] {
+			assert!(invalid.parse::<Path>().is_err(), "{invalid:?}");
+		}
+	}
+
+	#[test]
+	fn suggestion() {
+		assert_eq!(distance("spwan", "spawn"), 1);
+		assert_eq!(distance("", "abc"), 3);
+		assert_eq!(distance("repeat-rte", "repeat-rate"), 1);
+
+		let fields = &["repeat-rate", "repeat-delay", "layout"];
+		assert_eq!(suggest("repeat-delya", fields), Some("repeat-delay"));
+		assert_eq!(suggest("lay", fields), None);
+		assert_eq!(suggest("x", &["y"]), None);
+		assert_eq!(suggest("xy", &["x", "yz"]), Some("x"));
+	}
+
 	#[test]
 	fn ord_span() {
 		let span = Span::Span(
@@ -317,11 +714,13 @@ mod test {
 				line: 2,
 				col: 6,
 				index: 15,
+				source: 0,
 			},
 			Position {
 				line: 2,
 				col: 16,
 				index: 25,
+				source: 0,
 			},
 		);
 
@@ -329,6 +728,7 @@ mod test {
 			line: 1,
 			col: 6,
 			index: 5,
+			source: 0,
 		});
 
 		assert_eq!(span.cmp(&p1), Ordering::Greater);
@@ -338,6 +738,7 @@ mod test {
 			line: 2,
 			col: 6,
 			index: 15,
+			source: 0,
 		});
 
 		assert_eq!(span.cmp(&p2), Ordering::Equal);
@@ -347,6 +748,7 @@ mod test {
 			line: 2,
 			col: 11,
 			index: 20,
+			source: 0,
 		});
 
 		assert_eq!(span.cmp(&p3), Ordering::Equal);
@@ -356,6 +758,7 @@ mod test {
 			line: 2,
 			col: 16,
 			index: 25,
+			source: 0,
 		});
 
 		assert_eq!(span.cmp(&p4), Ordering::Equal);
@@ -365,6 +768,7 @@ mod test {
 			line: 3,
 			col: 6,
 			index: 30,
+			source: 0,
 		});
 
 		assert_eq!(span.cmp(&p5), Ordering::Less);
@@ -0,0 +1,140 @@
+use super::{Error, Span};
+use crate::{de::read::Read, Deserializer};
+use annotate_snippets::{AnnotationKind, Group, Level, Renderer, Snippet};
+use std::fmt::Display;
+
+impl Error {
+	/// render the error as a [`Report`], with a snippet of the `source`
+	/// it was deserialized from, that was read from the file at `path`.
+	///
+	/// the report is plain text, use [`Report::set_styled`] to color it.
+	///
+	/// ```
+	/// # #[derive(Debug, serde::Deserialize)]
+	/// # struct Config { size: u32 }
+	/// let source = "size = \"24\"\n";
+	/// let err = mayfig::from_str::<Config>(source).unwrap_err();
+	///
+	/// let report = err.render(source, "config.mf");
+	/// assert!(report.to_string().contains("--> config.mf:1:8"));
+	/// ```
+	///
+	/// errors in included sources are rendered without a snippet,
+	/// use [`Error::render_with`] to show them.
+	pub fn render<'a>(&'a self, source: &'a str, path: &'a str) -> Report<'a> {
+		Report {
+			error: self,
+			id: 0,
+			source,
+			path,
+			styled: false,
+		}
+	}
+
+	/// like [`Error::render`], but errors in sources that were included by the
+	/// deserializer `de` are rendered with a snippet of the included source.
+	///
+	/// ```
+	/// # use mayfig::de::MemoryResolver;
+	/// # use std::collections::HashMap;
+	/// let mut resolver = MemoryResolver::new();
+	/// resolver.insert("binds.mf", "mod+q = 1\n");
+	///
+	/// let source = "include \"binds.mf\"\n";
+	/// let mut de = mayfig::Deserializer::from_str(source);
+	/// de.set_resolver(resolver);
+	///
+	/// let err = <HashMap<String, String> as serde::Deserialize>::deserialize(&mut de).unwrap_err();
+	/// let report = err.render_with(&de, source, "config.mf");
+	/// assert!(report.to_string().contains("--> binds.mf:1:9"));
+	/// ```
+	pub fn render_with<'a, 'de, R: Read<'de>>(
+		&'a self,
+		de: &'a Deserializer<R>,
+		source: &'a str,
+		path: &'a str,
+	) -> Report<'a> {
+		let mut report = self.render(source, path);
+		let id = match self.span {
+			Some(Span::Point(pos) | Span::Span(pos, _)) => pos.source,
+			None => 0,
+		};
+
+		if let (Some(path), Some(source)) = (de.source_name(id), de.source_text(id)) {
+			report.id = id;
+			report.source = source;
+			report.path = path;
+		}
+
+		report
+	}
+}
+
+/// an [`Error`] rendered with a snippet of the source it happened in.
+///
+/// created by [`Error::render`].
+pub struct Report<'a> {
+	error: &'a Error,
+	/// the id of the `source`, as in [`Position::source`](super::Position::source)
+	id: usize,
+	source: &'a str,
+	path: &'a str,
+	styled: bool,
+}
+
+impl Report<'_> {
+	/// set whether the report is rendered with colors
+	pub fn set_styled(&mut self, styled: bool) {
+		self.styled = styled;
+	}
+}
+
+impl Display for Report<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let code = self.error.code.to_string();
+		let title = Level::ERROR.primary_title(code);
+
+		// a span in another source doesn't fit the text of this one
+		let span = self.error.span.filter(|span| {
+			let (Span::Point(pos) | Span::Span(pos, _)) = span;
+			pos.source == self.id
+		});
+
+		let mut group = if let Some(span) = span {
+			let range = match span {
+				// underline the character at the point, if there is one
+				Span::Point(pos) => {
+					let len = self.source.get(pos.index..).and_then(|rest| {
+						let ch = rest.chars().next()?;
+						(ch != '\n').then(|| ch.len_utf8())
+					});
+					pos.index..pos.index + len.unwrap_or(0)
+				}
+				Span::Span(..) => span.range(),
+			};
+
+			// positions past the end of the source can't be shown
+			let range = range.start.min(self.source.len())..range.end.min(self.source.len());
+			let snippet = Snippet::source(self.source)
+				.path(self.path)
+				.fold(true)
+				.annotation(AnnotationKind::Primary.span(range));
+			title.element(snippet)
+		} else {
+			Group::with_title(title)
+		};
+
+		if let Some(path) = &self.error.path {
+			let note = Level::NOTE.message(format!("in `{path}`"));
+			group = group.element(note);
+		}
+
+		let renderer = if self.styled {
+			Renderer::styled()
+		} else {
+			Renderer::plain()
+		};
+
+		f.write_str(&renderer.render(&[group]))
+	}
+}
@@ -0,0 +1,567 @@
+//! a pull parser, that reads mayfig text as a stream of [`Event`]s.
+//!
+//! the parser uses the same reader as the [`Deserializer`](crate::Deserializer), but it
+//! doesn't go through serde or build a `Value`, so it can scan large
+//! documents cheaply. maps and sequences that aren't needed can be skipped with
+//! [`Parser::skip_to_end`], without producing events for their contents.
+//!
+//! ```
+//! use mayfig::events::{Event, Parser, Scalar};
+//!
+//! let mut parser = Parser::from_str("input {\n    tap = true\n}\n");
+//! assert!(matches!(parser.next(), Some(Ok(Event::MapStart(Some(key), _))) if key == "input"));
+//! assert!(matches!(parser.next(), Some(Ok(Event::Key(key, _))) if key == "tap"));
+//! assert!(matches!(parser.next(), Some(Ok(Event::Scalar(Scalar::Bool(true), _)))));
+//! assert!(matches!(parser.next(), Some(Ok(Event::MapEnd(_)))));
+//! assert!(parser.next().is_none());
+//! ```
+
+use crate::{
+	de::{
+		self, include, parse_bool,
+		read::{
+			self, is_quote, is_whitespace, is_whitespace_line, IoRead, Read, Ref, SliceRead,
+			StrRead,
+		},
+		variable::{self, Kind, Scope, Variable},
+	},
+	error::{Error, ErrorCode, Span},
+};
+use std::{borrow::Cow, marker::PhantomData};
+
+/// an event of the [`Parser`]
+///
+/// the top level map has no start and end events.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'de> {
+	/// the start of a map, with the key of its entry and the span of the key.
+	///
+	/// maps that are the value of an entry, like `key { .. }` or `key = { .. }`,
+	/// have no [`Event::Key`] before them. maps without a key, like in
+	/// sequences, have the span of their `{` instead.
+	///
+	/// like in [`Segment::Key`](crate::error::Segment::Key), tagged keys
+	/// are represented by their tag, but the span covers the whole key.
+	MapStart(Option<Cow<'de, str>>, Span),
+	/// the end of a map, with the span of its `}`
+	MapEnd(Span),
+	/// the key of a `key = value` entry, followed by the events of the value
+	Key(Cow<'de, str>, Span),
+	/// the start of a sequence, with the span of its `[`
+	SeqStart(Span),
+	/// the end of a sequence, with the span of its `]`
+	SeqEnd(Span),
+	/// a scalar value
+	Scalar(Scalar<'de>, Span),
+	/// the tag of a tagged value, like `"spawn" [ "kitty" ]` or `"move" { x = 1 }`,
+	/// followed by the events of the sequence or map it tags
+	TagStart(Cow<'de, str>, Span),
+	/// a comment, without the `#`
+	Comment(Cow<'de, str>),
+	/// an `include "path"` directive, with the path
+	///
+	/// the included source isn't read by the parser.
+	Include(Cow<'de, str>, Span),
+}
+
+/// a scalar value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scalar<'de> {
+	/// a string, with its escapes resolved
+	String(Cow<'de, str>),
+	/// a number, as it's written
+	Number(Cow<'de, str>),
+	/// a boolean
+	Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+	Map {
+		/// the top level map isn't surrounded by braces
+		is_top: bool,
+		is_first: bool,
+		/// the number of variables that were in scope before the map
+		variables: usize,
+	},
+	Seq,
+}
+
+/// a mayfig pull parser.
+///
+/// the parser is an [`Iterator`] over the [`Event`]s of the text, which stops after the
+/// first error. variables are substituted like in the deserializer, but strings
+/// aren't expanded with an [`Environment`](crate::de::Environment).
+pub struct Parser<'de, R> {
+	read: R,
+	scratch: String,
+	stack: Vec<Frame>,
+	variables: Vec<Variable>,
+	/// the next event is a value, after a key or a tag
+	value: bool,
+	/// whether a newline was read since the last entry of a map
+	newline: bool,
+	done: bool,
+	marker: PhantomData<&'de ()>,
+}
+
+impl<'de> Parser<'de, StrRead<'de>> {
+	/// create a mayfig parser from a `&str`.
+	#[expect(clippy::should_implement_trait)]
+	pub fn from_str(input: &'de str) -> Self {
+		let read = StrRead::new(input);
+		Parser::new(read)
+	}
+}
+
+impl<'de> Parser<'de, SliceRead<'de>> {
+	/// create a mayfig parser from a `&[u8]`.
+	///
+	/// the input is validated as utf8 while parsing.
+	pub fn from_slice(input: &'de [u8]) -> Self {
+		let read = SliceRead::new(input);
+		Parser::new(read)
+	}
+}
+
+impl<R: std::io::Read> Parser<'_, IoRead<R>> {
+	/// create a mayfig parser from an io stream.
+	///
+	/// the reader is buffered internally.
+	pub fn from_reader(reader: R) -> Self {
+		let read = IoRead::new(reader);
+		Parser::new(read)
+	}
+}
+
+impl<'de, R: Read<'de>> Parser<'de, R> {
+	fn new(read: R) -> Self {
+		Parser {
+			read,
+			scratch: String::new(),
+			stack: vec![Frame::Map {
+				is_top: true,
+				is_first: true,
+				variables: 0,
+			}],
+			variables: Vec::new(),
+			value: false,
+			newline: false,
+			done: false,
+			marker: PhantomData,
+		}
+	}
+
+	/// read the next event, or `None` at the end of the input
+	///
+	/// # errors
+	///
+	/// returns an error if the text isn't valid mayfig.
+	/// after an error, no more events are returned.
+	pub fn next_event(&mut self) -> Result<Option<Event<'de>>, Error> {
+		if self.done {
+			return Ok(None);
+		}
+
+		let event = if std::mem::take(&mut self.value) {
+			self.value().map(Some)
+		} else {
+			match self.stack.last().copied() {
+				Some(Frame::Map {
+					is_top, is_first, ..
+				}) => self.map_event(is_top, is_first),
+				Some(Frame::Seq) => self.seq_event().map(Some),
+				None => Ok(None),
+			}
+		};
+
+		if !matches!(event, Ok(Some(_))) {
+			self.done = true;
+		}
+		event
+	}
+
+	/// skip the rest of the innermost map or sequence that hasn't ended yet, including its end.
+	///
+	/// the skipped text is only scanned for brackets, strings and comments, so it isn't
+	/// validated. skipping the top level map skips the rest of the input.
+	///
+	/// # errors
+	///
+	/// returns an error if the input ends before the map or sequence does,
+	/// or if a string in it is invalid.
+	pub fn skip_to_end(&mut self) -> Result<(), Error> {
+		if self.done {
+			return Ok(());
+		}
+
+		self.value = false;
+		let Some(frame) = self.stack.pop() else {
+			return Ok(());
+		};
+
+		let is_top = matches!(frame, Frame::Map { is_top: true, .. });
+		if let Frame::Map { variables, .. } = frame {
+			self.variables.truncate(variables);
+		}
+
+		self.newline = false;
+		let skipped = self.scan_to_end(is_top);
+		if is_top || skipped.is_err() {
+			self.done = true;
+		}
+		skipped
+	}
+
+	/// skip to the closing bracket of the current block, or to the end of the input
+	fn scan_to_end(&mut self, is_top: bool) -> Result<(), Error> {
+		let mut depth = 0_usize;
+		loop {
+			let Some(peek) = self.read.peek()? else {
+				return if is_top { Ok(()) } else { Err(Error::EOF) };
+			};
+
+			match peek {
+				'{' | '[' => {
+					depth += 1;
+					self.read.discard();
+				}
+				'}' | ']' => {
+					self.read.discard();
+					match depth.checked_sub(1) {
+						Some(sub) => depth = sub,
+						None if !is_top => return Ok(()),
+						None => {}
+					}
+				}
+				'#' => {
+					self.scratch.clear();
+					self.read.comment(&mut self.scratch)?;
+				}
+				ch if is_quote(ch) => {
+					self.scratch.clear();
+					self.read.str(&mut self.scratch)?;
+				}
+				_ => self.read.discard(),
+			}
+		}
+	}
+
+	fn map_event(&mut self, is_top: bool, mut is_first: bool) -> Result<Option<Event<'de>>, Error> {
+		loop {
+			let Some(peek) = self.read.peek()? else {
+				if is_top {
+					self.stack.pop();
+					return Ok(None);
+				}
+				return Err(Error::EOF);
+			};
+
+			match peek {
+				'\n' => {
+					self.newline = true;
+					self.read.discard();
+				}
+				ch if is_whitespace_line(ch) => self.read.discard(),
+				'#' => return self.comment().map(Some),
+				'}' if !is_top => {
+					let span = self.bracket();
+					self.end_map();
+					return Ok(Some(Event::MapEnd(span)));
+				}
+				_ if !is_first && !self.newline => {
+					let point = self.read.position();
+					let code = ErrorCode::ExpectedNewline(peek);
+					return Err(Error::with_point(code, point));
+				}
+				_ => {
+					if let Some(Frame::Map { is_first, .. }) = self.stack.last_mut() {
+						*is_first = false;
+					}
+					is_first = false;
+					self.newline = false;
+
+					if let Some(include) = self.include()? {
+						return Ok(Some(include));
+					} else if !self.define()? {
+						return self.entry().map(Some);
+					}
+				}
+			}
+		}
+	}
+
+	fn seq_event(&mut self) -> Result<Event<'de>, Error> {
+		loop {
+			let peek = self.read.peek()?.ok_or(Error::EOF)?;
+			match peek {
+				',' => self.read.discard(),
+				ch if is_whitespace(ch) => self.read.discard(),
+				'#' => return self.comment(),
+				']' => {
+					let span = self.bracket();
+					self.stack.pop();
+					self.newline = false;
+					return Ok(Event::SeqEnd(span));
+				}
+				_ => return self.value(),
+			}
+		}
+	}
+
+	fn end_map(&mut self) {
+		if let Some(Frame::Map { variables, .. }) = self.stack.pop() {
+			self.variables.truncate(variables);
+		}
+		self.newline = false;
+	}
+
+	fn start_map(&mut self) {
+		self.stack.push(Frame::Map {
+			is_top: false,
+			is_first: true,
+			variables: self.variables.len(),
+		});
+	}
+
+	fn comment(&mut self) -> Result<Event<'de>, Error> {
+		debug_assert_eq!(self.read.peek()?, Some('#'));
+		self.read.discard();
+
+		self.scratch.clear();
+		let comment = self.read.comment(&mut self.scratch)?;
+		Ok(Event::Comment(cow(comment)))
+	}
+
+	/// read an `include "path"` directive at the current position,
+	/// returns `None` if there is none
+	fn include(&mut self) -> Result<Option<Event<'de>>, Error> {
+		let start = self.read.position();
+		if !include::directive(&mut self.read, &mut self.scratch)? {
+			return Ok(None);
+		}
+
+		self.scratch.clear();
+		let path = cow(self.read.str(&mut self.scratch)?);
+		let span = Span::Span(start, self.read.position());
+		Ok(Some(Event::Include(path, span)))
+	}
+
+	/// read a `$name = value` variable definition at the current position,
+	/// returns `false` if there is none
+	fn define(&mut self) -> Result<bool, Error> {
+		let Some(name) = variable::definition(&mut self.read)? else {
+			return Ok(false);
+		};
+
+		let variable = variable::define(self, name)?;
+		self.variables.push(variable);
+		Ok(true)
+	}
+
+	/// read the key of an entry, and the `=` or `{` after it
+	fn entry(&mut self) -> Result<Event<'de>, Error> {
+		let start = self.read.position();
+		let peek = self.read.peek()?.ok_or(Error::EOF)?;
+		let key = match peek {
+			'0'..='9' | '.' | '-' | '+' => self.num()?.0,
+			ch if is_quote(ch) => self.str()?.0,
+			ch if ch.is_ascii_alphabetic() || ch == '_' || ch == '$' => self.word()?.0,
+			_ => {
+				let point = self.read.position();
+				let code = ErrorCode::ExpectedAsciiAlphabetic(peek);
+				return Err(Error::with_point(code, point));
+			}
+		};
+
+		// a tagged key, like `class [ "kitty" ]`
+		if self.peek_line()? == Some('[') {
+			self.read.discard();
+			self.scan_to_end(false)?;
+		}
+		let span = Span::Span(start, self.read.position());
+
+		let peek = self.peek_line()?.ok_or(Error::EOF)?;
+		match peek {
+			'=' => {
+				self.read.discard();
+				if self.peek_line()? == Some('{') {
+					self.read.discard();
+					self.start_map();
+					Ok(Event::MapStart(Some(key), span))
+				} else {
+					self.value = true;
+					Ok(Event::Key(key, span))
+				}
+			}
+			'{' => {
+				self.read.discard();
+				self.start_map();
+				Ok(Event::MapStart(Some(key), span))
+			}
+			_ => {
+				let point = self.read.position();
+				let code = ErrorCode::ExpectedValue(peek);
+				Err(Error::with_point(code, point))
+			}
+		}
+	}
+
+	/// read a value, which is either a scalar or the start of a map, sequence or tagged value
+	fn value(&mut self) -> Result<Event<'de>, Error> {
+		let peek = self.peek_line()?.ok_or(Error::EOF)?;
+		let start = self.read.position();
+		match peek {
+			'{' => {
+				let span = self.bracket();
+				self.start_map();
+				Ok(Event::MapStart(None, span))
+			}
+			'[' => {
+				let span = self.bracket();
+				self.stack.push(Frame::Seq);
+				Ok(Event::SeqStart(span))
+			}
+			'0'..='9' | '.' | '-' | '+' => {
+				let (num, span) = self.num()?;
+				Ok(Event::Scalar(number(num, span)?, span))
+			}
+			'$' => {
+				let (variable, span) = self.variable()?;
+				let scalar = match variable.kind {
+					Kind::Num => number(Cow::Owned(variable.value), span)?,
+					Kind::Str => Scalar::String(Cow::Owned(variable.value)),
+					Kind::Word => Scalar::Bool(parse_bool(&variable.value, span)?),
+				};
+				Ok(Event::Scalar(scalar, span))
+			}
+			ch if is_quote(ch) => {
+				let (str, span) = self.str()?;
+				while self.read.peek()?.is_some_and(is_whitespace_line) {
+					self.read.discard();
+				}
+
+				// a sequence or map on the same line is tagged by the string
+				if let Some('[' | '{') = self.read.peek()? {
+					self.value = true;
+					Ok(Event::TagStart(str, span))
+				} else {
+					Ok(Event::Scalar(Scalar::String(str), span))
+				}
+			}
+			ch if ch.is_ascii_alphabetic() => {
+				let (word, span) = self.word()?;
+				match parse_bool(&word, span) {
+					Ok(b) => Ok(Event::Scalar(Scalar::Bool(b), span)),
+					Err(_) => {
+						let code = ErrorCode::UnexpectedWord(word.into_owned());
+						Err(Error::with_span(code, span))
+					}
+				}
+			}
+			_ => {
+				let code = ErrorCode::ExpectedQuote(peek);
+				Err(Error::with_point(code, start))
+			}
+		}
+	}
+
+	fn peek_line(&mut self) -> Result<Option<char>, Error> {
+		read::peek_line(&mut self.read)
+	}
+
+	/// read the bracket at the current position, and return its span
+	fn bracket(&mut self) -> Span {
+		let start = self.read.position();
+		self.read.discard();
+		Span::Span(start, self.read.position())
+	}
+
+	fn num(&mut self) -> Result<(Cow<'de, str>, Span), Error> {
+		let start = self.read.position();
+		self.scratch.clear();
+
+		let num = cow(self.read.num(&mut self.scratch)?);
+		if num.is_empty() {
+			let peek = self.read.peek()?.ok_or(Error::EOF)?;
+
+			let point = self.read.position();
+			let code = ErrorCode::ExpectedNumeric(peek);
+			Err(Error::with_point(code, point))
+		} else {
+			Ok((num, Span::Span(start, self.read.position())))
+		}
+	}
+
+	fn str(&mut self) -> Result<(Cow<'de, str>, Span), Error> {
+		let start = self.read.position();
+		self.scratch.clear();
+
+		let str = cow(self.read.str(&mut self.scratch)?);
+		Ok((str, Span::Span(start, self.read.position())))
+	}
+
+	fn word(&mut self) -> Result<(Cow<'de, str>, Span), Error> {
+		let start = self.read.position();
+		self.scratch.clear();
+		let mut word = cow(self.read.word(&mut self.scratch)?);
+
+		// variables can be substituted anywhere in a word, like `$mod+q`
+		while self.read.peek()? == Some('$') {
+			let (variable, _) = self.variable()?;
+			let word = word.to_mut();
+			word.push_str(&variable.value);
+
+			self.scratch.clear();
+			word.push_str(&self.read.word(&mut self.scratch)?);
+		}
+
+		Ok((word, Span::Span(start, self.read.position())))
+	}
+}
+
+impl<'de, R: Read<'de>> Scope<'de> for Parser<'de, R> {
+	type Read = R;
+
+	fn read(&mut self) -> &mut R {
+		&mut self.read
+	}
+
+	fn variable(&mut self) -> Result<(Variable, Span), Error> {
+		variable::variable(&mut self.read, &self.variables)
+	}
+
+	fn scalar(&mut self, kind: Kind) -> Result<String, Error> {
+		let scalar = match kind {
+			Kind::Num => self.num()?.0,
+			Kind::Str => self.str()?.0,
+			Kind::Word => self.word()?.0,
+		};
+		Ok(scalar.into_owned())
+	}
+}
+
+impl<'de, R: Read<'de>> Iterator for Parser<'de, R> {
+	type Item = Result<Event<'de>, Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.next_event().transpose()
+	}
+}
+
+/// a number scalar, if the scanned `num` is valid like in the deserializer
+fn number(num: Cow<'_, str>, span: Span) -> Result<Scalar<'_>, Error> {
+	if de::is_valid_number(&num) {
+		Ok(Scalar::Number(num))
+	} else {
+		let code = ErrorCode::InvalidNum(num.into_owned());
+		Err(Error::with_span(code, span))
+	}
+}
+
+fn cow<'de>(r#ref: Ref<'de, '_, str>) -> Cow<'de, str> {
+	match r#ref {
+		Ref::Borrow(borrow) => Cow::Borrowed(borrow),
+		Ref::Scratch(scratch) => Cow::Owned(scratch.to_owned()),
+	}
+}
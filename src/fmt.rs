@@ -0,0 +1,413 @@
+//! format mayfig source text.
+//!
+//! the formatter puts every entry on its own line, indents categories and
+//! multi-line sequences, puts single spaces around `=`, removes the commas
+//! in sequences and collapses consecutive blank lines, while keeping all comments.
+//! formatting already formatted text doesn't change it.
+//!
+//! ```
+//! let text = "cursor{  # the cursor\n  xcursor-size=24\n\n\n  xcursor-theme = 'Adwaita'\n}\nsize = [24,24]";
+//! let formatted = mayfig::fmt::format(text).unwrap();
+//!
+//! let canonical = "cursor { # the cursor\n\txcursor-size = 24\n\n\txcursor-theme = \"Adwaita\"\n}\nsize = [ 24 24 ]\n";
+//! assert_eq!(formatted, canonical);
+//! ```
+
+use crate::{
+	syntax::{self, NodeKind, SyntaxElement, SyntaxNode, TokenKind},
+	Error,
+};
+use std::borrow::Cow;
+
+/// how quoted strings are formatted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteFormat {
+	/// keep the quotes as they are
+	Preserve,
+	/// use double quotes, if the string doesn't contain any
+	#[default]
+	Double,
+	/// use single quotes, if the string doesn't contain any
+	Single,
+}
+
+/// how sequences are wrapped
+///
+/// sequences with comments or with values that span multiple lines
+/// always have one value per line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SeqFormat {
+	/// keep single line sequences on one line, and put every value
+	/// on its own line for sequences that span multiple lines
+	#[default]
+	Preserve,
+	/// put sequences on a single line
+	Inline,
+	/// put every value on its own line
+	Multiline,
+	/// put sequences on a single line, if the line is at most this many chars long
+	MaxWidth(usize),
+}
+
+/// a mayfig formatter
+#[derive(Debug, Clone)]
+pub struct Formatter<'id> {
+	indent: &'id str,
+	quote_format: QuoteFormat,
+	seq_format: SeqFormat,
+}
+
+impl Formatter<'static> {
+	/// create a new mayfig formatter
+	///
+	/// mayfig is indented with a tab by default.
+	pub fn new() -> Self {
+		Formatter {
+			indent: "\t",
+			quote_format: QuoteFormat::Double,
+			seq_format: SeqFormat::Preserve,
+		}
+	}
+}
+
+impl Default for Formatter<'static> {
+	fn default() -> Self {
+		Formatter::new()
+	}
+}
+
+impl<'id> Formatter<'id> {
+	/// create a new mayfig formatter, which uses `indent` for indentation,
+	/// like [`Serializer::with_indent`](crate::Serializer::with_indent)
+	pub fn with_indent(indent: &'id str) -> Self {
+		Formatter {
+			indent,
+			quote_format: QuoteFormat::Double,
+			seq_format: SeqFormat::Preserve,
+		}
+	}
+
+	/// set how quoted strings are formatted
+	///
+	/// defaults to [`QuoteFormat::Double`].
+	pub fn set_quote_format(&mut self, format: QuoteFormat) {
+		self.quote_format = format;
+	}
+
+	/// set how sequences are wrapped
+	///
+	/// defaults to [`SeqFormat::Preserve`].
+	pub fn set_seq_format(&mut self, format: SeqFormat) {
+		self.seq_format = format;
+	}
+
+	/// format the mayfig `text`
+	///
+	/// # errors
+	///
+	/// returns the first syntax error in the text.
+	pub fn format(&self, text: &str) -> Result<String, Error> {
+		let parse = syntax::parse(text);
+		if let Some(err) = parse.errors.into_iter().next() {
+			return Err(err);
+		}
+
+		let mut printer = Printer::new(self, 0);
+		printer.body(parse.root.children());
+		if !printer.out.is_empty() {
+			printer.out.push('\n');
+		}
+
+		Ok(printer.out)
+	}
+}
+
+/// format the mayfig `text` with the default [`Formatter`]
+///
+/// # errors
+///
+/// returns the first syntax error in the text.
+pub fn format(text: &str) -> Result<String, Error> {
+	Formatter::new().format(text)
+}
+
+struct Printer<'f, 'id> {
+	formatter: &'f Formatter<'id>,
+	out: String,
+	/// the indentation level of new lines
+	depth: usize,
+}
+
+impl<'f, 'id> Printer<'f, 'id> {
+	fn new(formatter: &'f Formatter<'id>, depth: usize) -> Self {
+		Printer {
+			formatter,
+			out: String::new(),
+			depth,
+		}
+	}
+
+	fn indent(&mut self, depth: usize) {
+		for _ in 0..depth {
+			self.out.push_str(self.formatter.indent);
+		}
+	}
+
+	/// the length of the current line, in chars
+	fn column(&self) -> usize {
+		let start = self.out.rfind('\n').map_or(0, |idx| idx + 1);
+		self.out[start..].chars().count()
+	}
+
+	/// start a new line, after a blank line if `blank` is true
+	fn line(&mut self, blank: bool) {
+		if !self.out.is_empty() {
+			self.out.push('\n');
+			if blank {
+				self.out.push('\n');
+			}
+		}
+
+		self.indent(self.depth);
+	}
+
+	/// print the entries, values and comments in the `children` on their own lines
+	fn body(&mut self, children: &[SyntaxElement]) {
+		let mut is_first = true;
+		let mut is_blank = false;
+		let mut on_line = false;
+
+		for child in children {
+			match child {
+				SyntaxElement::Token(token) => match token.kind() {
+					TokenKind::LBrace | TokenKind::LBracket => {
+						self.out.push_str(token.text());
+						on_line = true;
+					}
+					TokenKind::Newline if on_line => on_line = false,
+					TokenKind::Newline => is_blank = !is_first,
+					TokenKind::Comment if on_line => {
+						self.out.push(' ');
+						self.out.push_str(token.text().trim_end());
+					}
+					TokenKind::Comment => {
+						self.line(is_blank);
+						self.out.push_str(token.text().trim_end());
+
+						(is_first, is_blank) = (false, false);
+						on_line = true;
+					}
+					_ => {}
+				},
+				SyntaxElement::Node(node) => {
+					self.line(is_blank);
+					self.node(node);
+
+					(is_first, is_blank) = (false, false);
+					on_line = true;
+				}
+			}
+		}
+	}
+
+	fn node(&mut self, node: &SyntaxNode) {
+		match node.kind() {
+			NodeKind::Category | NodeKind::Pair => {
+				for (idx, child) in node.nodes().enumerate() {
+					if idx != 0 {
+						match node.kind() {
+							NodeKind::Pair => self.out.push_str(" = "),
+							_ => self.out.push(' '),
+						}
+					}
+					self.node(child);
+				}
+			}
+			NodeKind::Include | NodeKind::Tagged => {
+				for (idx, child) in node.children().iter().enumerate() {
+					match child {
+						SyntaxElement::Token(token) if token.kind() == TokenKind::Whitespace => {}
+						child => {
+							if idx != 0 {
+								self.out.push(' ');
+							}
+							self.element(child, false);
+						}
+					}
+				}
+			}
+			NodeKind::Key => {
+				for child in node.children() {
+					match child {
+						SyntaxElement::Token(token) if token.kind() == TokenKind::Whitespace => {}
+						SyntaxElement::Token(_) => self.element(child, true),
+						SyntaxElement::Node(_) => {
+							self.out.push(' ');
+							self.element(child, true);
+						}
+					}
+				}
+			}
+			NodeKind::Scalar => {
+				for token in node.tokens() {
+					self.token(token.kind(), token.text());
+				}
+			}
+			NodeKind::Seq => self.seq(node, false),
+			NodeKind::Map => {
+				self.depth += 1;
+				self.body(node.children());
+				self.depth -= 1;
+
+				self.out.push('\n');
+				self.indent(self.depth);
+				self.out.push('}');
+			}
+			NodeKind::Document | NodeKind::Error => self.out.push_str(&node.to_string()),
+		}
+	}
+
+	fn element(&mut self, element: &SyntaxElement, inline: bool) {
+		match element {
+			SyntaxElement::Node(node) if node.kind() == NodeKind::Seq => self.seq(node, inline),
+			SyntaxElement::Node(node) => self.node(node),
+			SyntaxElement::Token(token) => self.token(token.kind(), token.text()),
+		}
+	}
+
+	fn token(&mut self, kind: TokenKind, text: &str) {
+		if kind != TokenKind::String {
+			self.out.push_str(text);
+			return;
+		}
+
+		let quote = text.chars().next().unwrap_or_default();
+		if quote != '`' && text.starts_with(&*[quote; 3].iter().collect::<String>()) {
+			let indent = self.formatter.indent.repeat(self.depth + 1);
+			self.out.push_str(&multiline(text, quote, &indent));
+		} else {
+			let quoted = requote(text, self.formatter.quote_format);
+			self.out.push_str(&quoted);
+		}
+	}
+
+	/// print a sequence, on a single line if `inline` is true
+	/// and it has no comments or multi-line values
+	fn seq(&mut self, seq: &SyntaxNode, inline: bool) {
+		let values = seq
+			.nodes()
+			.map(|value| {
+				let mut printer = Printer::new(self.formatter, self.depth + 1);
+				printer.node(value);
+				printer.out
+			})
+			.collect::<Vec<_>>();
+
+		let single = if values.is_empty() {
+			"[ ]".to_owned()
+		} else {
+			let mut single = "[ ".to_owned();
+			let nodes = seq.nodes().collect::<Vec<_>>();
+			for (idx, value) in values.iter().enumerate() {
+				if idx != 0 {
+					// a string followed by a sequence or map on the same line is a tagged value
+					let is_tag = is_string(nodes[idx - 1])
+						&& matches!(nodes[idx].kind(), NodeKind::Seq | NodeKind::Map);
+					single.push_str(if is_tag { ", " } else { " " });
+				}
+				single.push_str(value);
+			}
+			single.push_str(" ]");
+			single
+		};
+
+		let has_comment = seq.tokens().any(|token| token.kind() == TokenKind::Comment);
+		let is_inline = if has_comment || values.iter().any(|value| value.contains('\n')) {
+			false
+		} else if inline {
+			true
+		} else {
+			match self.formatter.seq_format {
+				SeqFormat::Preserve => {
+					!seq.tokens().any(|token| token.kind() == TokenKind::Newline)
+				}
+				SeqFormat::Inline => true,
+				SeqFormat::Multiline => values.is_empty(),
+				SeqFormat::MaxWidth(width) => self.column() + single.chars().count() <= width,
+			}
+		};
+
+		if is_inline {
+			self.out.push_str(&single);
+			return;
+		}
+
+		self.depth += 1;
+		self.body(seq.children());
+		self.depth -= 1;
+
+		self.out.push('\n');
+		self.indent(self.depth);
+		self.out.push(']');
+	}
+}
+
+/// whether the `node` is a scalar string, that would tag a sequence or map after it
+fn is_string(node: &SyntaxNode) -> bool {
+	node.kind() == NodeKind::Scalar && node.tokens().any(|token| token.kind() == TokenKind::String)
+}
+
+/// change the quotes of a single line string to the `format`,
+/// if the string doesn't contain the new quotes
+fn requote(text: &str, format: QuoteFormat) -> Cow<'_, str> {
+	let (from, to) = match format {
+		QuoteFormat::Preserve => return Cow::Borrowed(text),
+		QuoteFormat::Double => ('\'', '"'),
+		QuoteFormat::Single => ('"', '\''),
+	};
+
+	let Some(inner) = text
+		.strip_prefix(from)
+		.and_then(|text| text.strip_suffix(from))
+	else {
+		return Cow::Borrowed(text);
+	};
+
+	let escaped = format!("\\{from}");
+	if inner.contains(to) || inner.contains(&escaped) {
+		Cow::Borrowed(text)
+	} else {
+		Cow::Owned(format!("{to}{inner}{to}"))
+	}
+}
+
+/// indent the lines and the closing quotes of a multi-line string with the `indent`
+fn multiline(text: &str, quote: char, indent: &str) -> String {
+	let closing = [quote; 3].iter().collect::<String>();
+	let mut lines = text.split('\n').collect::<Vec<_>>();
+	let Some(last) = lines.pop() else {
+		return text.to_owned();
+	};
+	let Some(old) = last.strip_suffix(&*closing) else {
+		return text.to_owned();
+	};
+
+	let mut out = String::with_capacity(text.len());
+	for (idx, line) in lines.into_iter().enumerate() {
+		if idx == 0 {
+			out.push_str(line.trim_end());
+		} else if let Some(rest) = line.strip_prefix(old).filter(|rest| !rest.is_empty()) {
+			out.push_str(indent);
+			out.push_str(rest);
+		} else if !old.starts_with(line) {
+			// keep lines with insufficient indentation as they are
+			out.push_str(line);
+		}
+
+		out.push('\n');
+	}
+
+	out.push_str(indent);
+	out.push_str(&closing);
+	out
+}
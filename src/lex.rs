@@ -0,0 +1,204 @@
+//! split mayfig text into tokens, with the same lexical rules as the [`Deserializer`](crate::Deserializer).
+//!
+//! the lexer doesn't know about the structure of the text, so it can be used
+//! for syntax highlighting of incomplete or invalid text.
+//!
+//! ```
+//! use mayfig::lex::{Lexer, TokenKind};
+//!
+//! let tokens = Lexer::new("tap = true # on\n").filter(|token| !token.kind.is_trivia());
+//! let kinds = tokens.map(|token| token.kind).collect::<Vec<_>>();
+//! assert_eq!(kinds, [TokenKind::Ident, TokenKind::Equals, TokenKind::Bool]);
+//! ```
+
+use crate::{
+	de::{
+		self,
+		read::{is_delimiter, is_quote, is_whitespace_line, Read, StrRead},
+	},
+	error::{Position, Span},
+};
+
+/// the kind of a [`Token`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+	/// spaces, tabs and carriage returns
+	Whitespace,
+	/// a line feed
+	Newline,
+	/// a comment, from the `#` to the end of the line
+	Comment,
+	/// an unquoted identifier, like `xcursor-size` or `mod+q`
+	Ident,
+	/// `true` or `false`, in any case
+	Bool,
+	/// a number, like `-1`, `0x1f` or `.inf`
+	Number,
+	/// a quoted string, including its quotes
+	String,
+	/// a variable reference, like `$mod`
+	Variable,
+	/// `=`
+	Equals,
+	/// `,`
+	Comma,
+	/// `{`
+	LBrace,
+	/// `}`
+	RBrace,
+	/// `[`
+	LBracket,
+	/// `]`
+	RBracket,
+	/// an unterminated string or text that isn't valid mayfig
+	Error,
+}
+
+impl TokenKind {
+	/// returns true for whitespace, newlines and comments
+	pub fn is_trivia(self) -> bool {
+		matches!(
+			self,
+			TokenKind::Whitespace | TokenKind::Newline | TokenKind::Comment
+		)
+	}
+}
+
+/// a token, borrowed from the text it was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+	/// the kind of the token
+	pub kind: TokenKind,
+	/// the text of the token
+	pub text: &'a str,
+	/// the span of the token in the text
+	pub span: Span,
+}
+
+/// an iterator over the tokens of a text.
+///
+/// the lexer never fails, text that can't be tokenized is returned
+/// as a [`TokenKind::Error`] token, so the texts of all tokens
+/// concatenated are always the original text.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+	text: &'a str,
+	pos: Position,
+	prev: Option<TokenKind>,
+	/// the scratch buffer of the reader, reused for every token
+	scratch: String,
+}
+
+impl<'a> Lexer<'a> {
+	/// create a lexer over the `text`
+	pub fn new(text: &'a str) -> Self {
+		Lexer {
+			text,
+			pos: Position::start(0),
+			prev: None,
+			scratch: String::new(),
+		}
+	}
+
+	/// the position the next token starts at
+	pub fn position(&self) -> Position {
+		self.pos
+	}
+}
+
+impl<'a> Iterator for Lexer<'a> {
+	type Item = Token<'a>;
+
+	fn next(&mut self) -> Option<Token<'a>> {
+		let rest = &self.text[self.pos.index..];
+		let first = rest.chars().next()?;
+
+		let (kind, len) = match first {
+			' ' | '\t' | '\r' => (TokenKind::Whitespace, len_while(rest, is_whitespace_line)),
+			'\n' => (TokenKind::Newline, 1),
+			'#' => (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len())),
+			'=' => (TokenKind::Equals, 1),
+			',' => (TokenKind::Comma, 1),
+			'{' => (TokenKind::LBrace, 1),
+			'}' => (TokenKind::RBrace, 1),
+			'[' => (TokenKind::LBracket, 1),
+			']' => (TokenKind::RBracket, 1),
+			'$' => {
+				let name = len_while(&rest[1..], |ch| ch.is_ascii_alphanumeric() || ch == '_');
+				(TokenKind::Variable, 1 + name)
+			}
+			ch if is_quote(ch) => quoted(rest, &mut self.scratch),
+			_ => {
+				let len = len_while(rest, |ch| !is_delimiter(ch) && !is_quote(ch) && ch != '$');
+				let bare = &rest[..len];
+
+				self.scratch.clear();
+				let is_word = StrRead::new(bare).word(&mut self.scratch).is_ok();
+				let is_bool =
+					bare.eq_ignore_ascii_case("true") || bare.eq_ignore_ascii_case("false");
+
+				// a variable can be continued by a word, like in `$mod+q`
+				let kind = if is_word && self.prev == Some(TokenKind::Variable) {
+					TokenKind::Ident
+				} else if matches!(first, '0'..='9' | '.' | '-' | '+') {
+					// like in the deserializer, anything that starts like a number has to be one
+					let mut read = StrRead::new(bare);
+					self.scratch.clear();
+					let num = read.num(&mut self.scratch);
+					let is_num = num.is_ok_and(|num| de::is_valid_number(&num));
+					if is_num && read.position().index == len {
+						TokenKind::Number
+					} else {
+						TokenKind::Error
+					}
+				} else if is_bool {
+					TokenKind::Bool
+				} else if is_word {
+					TokenKind::Ident
+				} else {
+					TokenKind::Error
+				};
+
+				(kind, len)
+			}
+		};
+
+		let text = &rest[..len];
+		let start = self.pos;
+		for ch in text.chars() {
+			self.pos.next(ch);
+		}
+
+		self.prev = Some(kind);
+		Some(Token {
+			kind,
+			text,
+			span: Span::Span(start, self.pos),
+		})
+	}
+}
+
+/// lex a string, starting at its opening quote
+///
+/// an invalid string is an error up to the end of the line it is invalid on,
+/// or up to the end of the text if it isn't terminated.
+fn quoted(rest: &str, scratch: &mut String) -> (TokenKind, usize) {
+	let mut read = StrRead::new(rest);
+	scratch.clear();
+	let Err(err) = read.str(scratch) else {
+		return (TokenKind::String, read.position().index);
+	};
+
+	match err.span() {
+		Some(span) => {
+			let start = span.range().start;
+			let line = rest[start..].find('\n').unwrap_or(rest.len() - start);
+			(TokenKind::Error, (start + line).max(1))
+		}
+		None => (TokenKind::Error, rest.len()),
+	}
+}
+
+fn len_while(str: &str, f: impl Fn(char) -> bool) -> usize {
+	str.find(|ch| !f(ch)).unwrap_or(str.len())
+}
@@ -42,17 +42,26 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod de;
+pub mod edit;
 pub mod error;
+pub mod events;
+pub mod fmt;
+pub mod lex;
 pub mod ser;
+mod spanned;
+pub mod syntax;
 #[cfg(feature = "value")]
 pub mod value;
 
 #[doc(inline)]
-pub use de::{from_str, Deserializer};
+pub use de::{
+	from_reader, from_slice, from_str, from_str_recover, from_str_with_unused, Deserializer,
+};
 #[doc(inline)]
 pub use error::Error;
 #[doc(inline)]
 pub use ser::{to_string, to_vec, to_writer, Serializer};
+pub use spanned::Spanned;
 #[doc(inline)]
 #[cfg(feature = "value")]
 pub use value::Value;
@@ -10,11 +10,41 @@ use serde_core::Serialize;
 mod r#enum;
 mod map;
 
+/// how byte slices are serialized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BytesFormat {
+	/// serialize as a string if the bytes are valid utf8,
+	/// and as a sequence of numbers otherwise
+	#[default]
+	Auto,
+	/// always serialize as a string
+	///
+	/// errors if the bytes are not valid utf8.
+	String,
+	/// always serialize as a sequence of numbers
+	Seq,
+}
+
+/// how strings are serialized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringFormat {
+	/// always serialize as a quoted string, using escape sequences
+	#[default]
+	Quoted,
+	/// serialize strings that contain newlines as multi-line strings,
+	/// and strings that contain quotes or backslashes as raw strings.
+	///
+	/// map keys are always quoted.
+	Auto,
+}
+
 /// a mayfig serializer
 pub struct Serializer<'id, W> {
 	/// current level of indentation
 	indent_level: usize,
 	indent: &'id [u8],
+	bytes_format: BytesFormat,
+	string_format: StringFormat,
 	writer: W,
 }
 
@@ -26,6 +56,8 @@ impl<W: std::io::Write> Serializer<'static, W> {
 		Serializer {
 			indent_level: 0,
 			indent: b"\t",
+			bytes_format: BytesFormat::Auto,
+			string_format: StringFormat::Quoted,
 			writer,
 		}
 	}
@@ -38,12 +70,28 @@ impl<'id, W: std::io::Write> Serializer<'id, W> {
 		Serializer {
 			indent_level: 0,
 			indent,
+			bytes_format: BytesFormat::Auto,
+			string_format: StringFormat::Quoted,
 			writer,
 		}
 	}
 }
 
 impl<W: std::io::Write> Serializer<'_, W> {
+	/// set how byte slices are serialized
+	///
+	/// defaults to [`BytesFormat::Auto`].
+	pub fn set_bytes_format(&mut self, format: BytesFormat) {
+		self.bytes_format = format;
+	}
+
+	/// set how strings are serialized
+	///
+	/// defaults to [`StringFormat::Quoted`].
+	pub fn set_string_format(&mut self, format: StringFormat) {
+		self.string_format = format;
+	}
+
 	fn indent(&mut self) -> Result<(), Error> {
 		for _ in 0..self.indent_level {
 			self.writer.write_all(self.indent)?;
@@ -51,6 +99,94 @@ impl<W: std::io::Write> Serializer<'_, W> {
 
 		Ok(())
 	}
+
+	/// returns the bytes as a str, if they should be serialized as a string
+	fn bytes_str<'b>(&self, v: &'b [u8]) -> Result<Option<&'b str>, Error> {
+		match self.bytes_format {
+			BytesFormat::Auto => Ok(std::str::from_utf8(v).ok()),
+			BytesFormat::String => match std::str::from_utf8(v) {
+				Ok(s) => Ok(Some(s)),
+				Err(_) => Err(Error::new(ErrorCode::InvalidUtf8)),
+			},
+			BytesFormat::Seq => Ok(None),
+		}
+	}
+
+	fn quoted_str(&mut self, v: &str) -> Result<(), Error> {
+		self.writer.write_all(b"\"")?;
+		self.escaped_str(v, false)?;
+		self.writer.write_all(b"\"")?;
+		Ok(())
+	}
+
+	fn multiline_str(&mut self, v: &str) -> Result<(), Error> {
+		self.writer.write_all(b"\"\"\"\n")?;
+		self.indent_level += 1;
+
+		for line in v.split('\n') {
+			if !line.is_empty() {
+				self.indent()?;
+				self.escaped_str(line, true)?;
+			}
+			self.writer.write_all(b"\n")?;
+		}
+
+		self.indent()?;
+		self.indent_level -= 1;
+		self.writer.write_all(b"\"\"\"")?;
+		Ok(())
+	}
+
+	/// write the escaped string, without the surrounding quotes
+	///
+	/// in multi-line strings only every third consecutive quote is escaped
+	/// and tabs are written as-is.
+	fn escaped_str(&mut self, v: &str, multiline: bool) -> Result<(), Error> {
+		let mut start = 0;
+		let mut quotes = 0;
+		for (idx, ch) in v.char_indices() {
+			quotes = if ch == '"' { quotes + 1 } else { 0 };
+
+			let escape = match ch {
+				'"' if !multiline || quotes == 3 => {
+					quotes = 0;
+					Some("\\\"")
+				}
+				'\\' => Some("\\\\"),
+				'\n' => Some("\\n"),
+				'\r' => Some("\\r"),
+				'\t' if !multiline => Some("\\t"),
+				'\x08' => Some("\\b"),
+				'\x0c' => Some("\\f"),
+				ch if ch.is_control() && ch != '\t' => None,
+				_ => continue,
+			};
+
+			self.writer.write_all(&v.as_bytes()[start..idx])?;
+			match escape {
+				Some(escape) => self.writer.write_all(escape.as_bytes())?,
+				None => write!(self.writer, "\\u{{{:x}}}", u32::from(ch))?,
+			}
+			start = idx + ch.len_utf8();
+		}
+
+		self.writer.write_all(&v.as_bytes()[start..])?;
+		Ok(())
+	}
+
+	fn bytes_seq(&mut self, v: &[u8]) -> Result<(), Error> {
+		let mut buffer = itoa::Buffer::new();
+
+		self.writer.write_all(b"[ ")?;
+		for byte in v {
+			let s = buffer.format(*byte);
+			self.writer.write_all(s.as_bytes())?;
+			self.writer.write_all(b" ")?;
+		}
+		self.writer.write_all(b"]")?;
+
+		Ok(())
+	}
 }
 
 impl<W: std::io::Write> serde_core::ser::Serializer for &mut Serializer<'_, W> {
@@ -99,6 +235,13 @@ impl<W: std::io::Write> serde_core::ser::Serializer for &mut Serializer<'_, W> {
 		Ok(())
 	}
 
+	fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+		let mut buffer = itoa::Buffer::new();
+		let s = buffer.format(v);
+		self.writer.write_all(s.as_bytes())?;
+		Ok(())
+	}
+
 	fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
 		let mut buffer = itoa::Buffer::new();
 		let s = buffer.format(v);
@@ -127,6 +270,13 @@ impl<W: std::io::Write> serde_core::ser::Serializer for &mut Serializer<'_, W> {
 		Ok(())
 	}
 
+	fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+		let mut buffer = itoa::Buffer::new();
+		let s = buffer.format(v);
+		self.writer.write_all(s.as_bytes())?;
+		Ok(())
+	}
+
 	fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
 		let mut buffer = zmij::Buffer::new();
 		let s = buffer.format(v);
@@ -148,14 +298,28 @@ impl<W: std::io::Write> serde_core::ser::Serializer for &mut Serializer<'_, W> {
 	}
 
 	fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-		let v = format!("{:?}", v);
-		self.writer.write_all(v.as_bytes())?;
-		Ok(())
+		if self.string_format == StringFormat::Quoted {
+			return self.quoted_str(v);
+		}
+
+		let is_whitespace_indent = self.indent.iter().all(|b| matches!(b, b' ' | b'\t'));
+		if v.contains('\n') && is_whitespace_indent {
+			self.multiline_str(v)
+		} else if v.contains(['"', '\\']) && !v.contains('`') && !v.contains(char::is_control) {
+			self.writer.write_all(b"`")?;
+			self.writer.write_all(v.as_bytes())?;
+			self.writer.write_all(b"`")?;
+			Ok(())
+		} else {
+			self.quoted_str(v)
+		}
 	}
 
-	#[expect(unused_variables)]
 	fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-		todo!()
+		match self.bytes_str(v)? {
+			Some(s) => self.serialize_str(s),
+			None => self.bytes_seq(v),
+		}
 	}
 
 	fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -61,6 +61,13 @@ impl<'a, 'id, W: std::io::Write> serde_core::ser::Serializer
 		Ok(())
 	}
 
+	fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+		self.ser.writer.write_all(b" [ ")?;
+		self.ser.serialize_i128(v)?;
+		self.ser.writer.write_all(b" ]")?;
+		Ok(())
+	}
+
 	fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
 		self.ser.writer.write_all(b" [ ")?;
 		self.ser.serialize_u8(v)?;
@@ -89,6 +96,13 @@ impl<'a, 'id, W: std::io::Write> serde_core::ser::Serializer
 		Ok(())
 	}
 
+	fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+		self.ser.writer.write_all(b" [ ")?;
+		self.ser.serialize_u128(v)?;
+		self.ser.writer.write_all(b" ]")?;
+		Ok(())
+	}
+
 	fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
 		self.ser.writer.write_all(b" [ ")?;
 		self.ser.serialize_f32(v)?;
@@ -117,10 +131,12 @@ impl<'a, 'id, W: std::io::Write> serde_core::ser::Serializer
 	}
 
 	fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-		self.ser.writer.write_all(b" [ ")?;
-		self.ser.serialize_bytes(v)?;
-		self.ser.writer.write_all(b" ]")?;
-		Ok(())
+		if let Some(s) = self.ser.bytes_str(v)? {
+			self.serialize_str(s)
+		} else {
+			self.ser.writer.write_all(b" ")?;
+			self.ser.bytes_seq(v)
+		}
 	}
 
 	fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -46,6 +46,10 @@ impl<'s, 'id, W: std::io::Write> serde_core::ser::Serializer
 		self.ser.serialize_u64(v)
 	}
 
+	fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+		self.ser.serialize_u128(v)
+	}
+
 	fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
 		self.ser.serialize_i8(v)
 	}
@@ -62,6 +66,10 @@ impl<'s, 'id, W: std::io::Write> serde_core::ser::Serializer
 		self.ser.serialize_i64(v)
 	}
 
+	fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+		self.ser.serialize_i128(v)
+	}
+
 	fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
 		self.ser.serialize_f32(v)
 	}
@@ -83,7 +91,7 @@ impl<'s, 'id, W: std::io::Write> serde_core::ser::Serializer
 		if is_ident {
 			self.ser.writer.write_all(v.as_bytes())?;
 		} else {
-			self.ser.serialize_str(v)?;
+			self.ser.quoted_str(v)?;
 		}
 
 		Ok(())
@@ -246,6 +254,11 @@ impl<'a, 'id, W: std::io::Write> serde_core::ser::Serializer for MapValSerialize
 		self.ser.serialize_i64(v)
 	}
 
+	fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+		self.ser.writer.write_all(b" = ")?;
+		self.ser.serialize_i128(v)
+	}
+
 	fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
 		self.ser.writer.write_all(b" = ")?;
 		self.ser.serialize_u8(v)
@@ -266,6 +279,11 @@ impl<'a, 'id, W: std::io::Write> serde_core::ser::Serializer for MapValSerialize
 		self.ser.serialize_u64(v)
 	}
 
+	fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+		self.ser.writer.write_all(b" = ")?;
+		self.ser.serialize_u128(v)
+	}
+
 	fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
 		self.ser.writer.write_all(b" = ")?;
 		self.ser.serialize_f32(v)
@@ -287,6 +305,7 @@ impl<'a, 'id, W: std::io::Write> serde_core::ser::Serializer for MapValSerialize
 	}
 
 	fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+		self.ser.writer.write_all(b" = ")?;
 		self.ser.serialize_bytes(v)
 	}
 
@@ -0,0 +1,211 @@
+//! a value with the location it was deserialized from
+
+use crate::error::{Position, Span};
+use serde_core::{
+	de::{Error as _, MapAccess, Visitor},
+	Deserialize, Serialize,
+};
+use std::{
+	borrow::Borrow,
+	cmp::Ordering,
+	fmt::{Debug, Display},
+	hash::Hash,
+	marker::PhantomData,
+};
+
+pub(crate) const NAME: &str = "$__mayfig_private_Spanned";
+pub(crate) const START: &str = "$__mayfig_private_start";
+pub(crate) const VALUE: &str = "$__mayfig_private_value";
+pub(crate) const END: &str = "$__mayfig_private_end";
+pub(crate) const FIELDS: &[&str] = &[START, VALUE, END];
+
+/// returns true if the struct is a [`Spanned`]
+pub(crate) fn is_spanned(name: &str, fields: &[&str]) -> bool {
+	name == NAME && fields == FIELDS
+}
+
+/// a value with the start and end [`Position`] of its location in the source.
+///
+/// can be used for values, map keys and categories. only the mayfig
+/// [`Deserializer`](crate::Deserializer) can deserialize a `Spanned`.
+///
+/// comparing and hashing only takes the inner value into account.
+///
+/// ```
+/// # use mayfig::Spanned;
+/// # use std::collections::HashMap;
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     bind: HashMap<Spanned<String>, Spanned<String>>,
+/// }
+///
+/// let config = mayfig::from_str::<Config>("bind {\n\tmod+q = \"close\"\n}").unwrap();
+/// let (key, value) = config.bind.iter().next().unwrap();
+/// assert_eq!(key.start().line, 2);
+/// assert_eq!(value.span().range(), 16..23);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Spanned<T> {
+	start: Position,
+	end: Position,
+	value: T,
+}
+
+impl<T> Spanned<T> {
+	/// create a new `Spanned` from a value and its location
+	pub fn new(start: Position, end: Position, value: T) -> Self {
+		Spanned { start, end, value }
+	}
+
+	/// returns the start position of the value
+	pub fn start(&self) -> Position {
+		self.start
+	}
+
+	/// returns the end position of the value
+	pub fn end(&self) -> Position {
+		self.end
+	}
+
+	/// returns the [`Span`] of the value
+	pub fn span(&self) -> Span {
+		Span::Span(self.start, self.end)
+	}
+
+	/// returns a reference to the inner value
+	pub fn get_ref(&self) -> &T {
+		&self.value
+	}
+
+	/// returns a mutable reference to the inner value
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.value
+	}
+
+	/// consumes the `Spanned` and returns the inner value
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+}
+
+impl<T: Debug> Debug for Spanned<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Spanned({:?} @ {})", self.value, self.span())
+	}
+}
+
+impl<T: Display> Display for Spanned<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(&self.value, f)
+	}
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.value == other.value
+	}
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: PartialOrd> PartialOrd for Spanned<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		self.value.partial_cmp(&other.value)
+	}
+}
+
+impl<T: Ord> Ord for Spanned<T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.value.cmp(&other.value)
+	}
+}
+
+impl<T: Hash> Hash for Spanned<T> {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.value.hash(state);
+	}
+}
+
+impl Borrow<str> for Spanned<String> {
+	fn borrow(&self) -> &str {
+		&self.value
+	}
+}
+
+impl<T> AsRef<T> for Spanned<T> {
+	fn as_ref(&self) -> &T {
+		&self.value
+	}
+}
+
+impl<T> AsMut<T> for Spanned<T> {
+	fn as_mut(&mut self) -> &mut T {
+		&mut self.value
+	}
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde_core::Serializer,
+	{
+		self.value.serialize(serializer)
+	}
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde_core::Deserializer<'de>,
+	{
+		deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(PhantomData))
+	}
+}
+
+struct SpannedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SpannedVisitor<T> {
+	type Value = Spanned<T>;
+
+	fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.write_str("a spanned value")
+	}
+
+	fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+	where
+		A: MapAccess<'de>,
+	{
+		expect_key(&mut map, START)?;
+		let (line, col, index, source) = map.next_value()?;
+		let start = Position {
+			line,
+			col,
+			index,
+			source,
+		};
+
+		expect_key(&mut map, VALUE)?;
+		let value = map.next_value()?;
+
+		expect_key(&mut map, END)?;
+		let (line, col, index, source) = map.next_value()?;
+		let end = Position {
+			line,
+			col,
+			index,
+			source,
+		};
+
+		Ok(Spanned { start, end, value })
+	}
+}
+
+fn expect_key<'de, A: MapAccess<'de>>(map: &mut A, key: &str) -> Result<(), A::Error> {
+	if map.next_key::<&str>()? == Some(key) {
+		Ok(())
+	} else {
+		Err(A::Error::custom(
+			"spanned value is only supported by mayfig",
+		))
+	}
+}
@@ -0,0 +1,305 @@
+//! a lossless concrete syntax tree of mayfig.
+//!
+//! unlike the [`Deserializer`](crate::Deserializer), which reads the text
+//! straight into serde visitors, the syntax tree keeps every comment, every
+//! piece of whitespace and the span of every token, so that the text can be
+//! reproduced byte for byte.
+//!
+//! parsing never fails. text that isn't valid mayfig is kept in
+//! [`NodeKind::Error`] nodes and the errors are collected alongside the tree.
+//!
+//! ```
+//! use mayfig::syntax::{self, NodeKind};
+//!
+//! let text = "# comment\ncursor {\n\txcursor-size = 24\n}\n";
+//! let parse = syntax::parse(text);
+//! assert!(parse.errors().is_empty());
+//!
+//! let root = parse.root();
+//! assert_eq!(root.to_string(), text);
+//!
+//! let cursor = root.entries().next().unwrap();
+//! assert_eq!(cursor.kind(), NodeKind::Category);
+//! assert_eq!(cursor.key().unwrap().to_string(), "cursor");
+//!
+//! let size = cursor.entries().next().unwrap();
+//! assert_eq!(size.value().unwrap().to_string(), "24");
+//! ```
+
+use crate::{
+	de::read::{Read, StrRead},
+	error::{Error, Position, Span},
+};
+use std::fmt::Display;
+
+mod parser;
+
+pub use crate::lex::TokenKind;
+
+/// the kind of a [`SyntaxNode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+	/// the whole text
+	Document,
+	/// a key with a map, like `key { ... }`
+	Category,
+	/// a key with a value, like `key = value`
+	Pair,
+	/// an `include "path"` directive
+	Include,
+	/// the key of a category or a pair
+	Key,
+	/// a string, number, word or variable
+	Scalar,
+	/// a sequence, like `[ ... ]`
+	Seq,
+	/// a map, like `{ ... }`
+	Map,
+	/// a tagged value, like `"tag" [ ... ]`
+	Tagged,
+	/// text that couldn't be parsed
+	Error,
+}
+
+/// a node of the syntax tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+	kind: NodeKind,
+	span: Span,
+	children: Vec<SyntaxElement>,
+}
+
+/// a token of the syntax tree, the leaves of the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+	kind: TokenKind,
+	text: String,
+	span: Span,
+}
+
+/// a child of a [`SyntaxNode`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+	/// a node
+	Node(SyntaxNode),
+	/// a token
+	Token(SyntaxToken),
+}
+
+/// the result of [`parse`]
+#[derive(Debug)]
+pub struct Parse {
+	pub(crate) root: SyntaxNode,
+	pub(crate) errors: Vec<Error>,
+}
+
+/// parse the `text` into a syntax tree
+pub fn parse(text: &str) -> Parse {
+	let mut parser = parser::Parser::new(text);
+	let root = parser.document();
+
+	Parse {
+		root,
+		errors: parser.errors,
+	}
+}
+
+impl Parse {
+	/// the [`NodeKind::Document`] node of the text
+	pub fn root(&self) -> &SyntaxNode {
+		&self.root
+	}
+
+	/// the syntax errors in the text, in order
+	pub fn errors(&self) -> &[Error] {
+		&self.errors
+	}
+
+	/// returns the root node, discarding the errors
+	pub fn into_root(self) -> SyntaxNode {
+		self.root
+	}
+}
+
+impl SyntaxNode {
+	fn new(kind: NodeKind, children: Vec<SyntaxElement>, at: Position) -> Self {
+		let span = match (children.first(), children.last()) {
+			(Some(first), Some(last)) => Span::Span(start(first.span()), end(last.span())),
+			_ => Span::Point(at),
+		};
+
+		SyntaxNode {
+			kind,
+			span,
+			children,
+		}
+	}
+
+	/// the kind of the node
+	pub fn kind(&self) -> NodeKind {
+		self.kind
+	}
+
+	/// the span of the node, a [`Span::Point`] if the node is empty
+	pub fn span(&self) -> Span {
+		self.span
+	}
+
+	/// the nodes and tokens directly in this node
+	pub fn children(&self) -> &[SyntaxElement] {
+		&self.children
+	}
+
+	/// the nodes directly in this node
+	pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+		self.children.iter().filter_map(SyntaxElement::as_node)
+	}
+
+	/// the tokens directly in this node
+	pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+		self.children.iter().filter_map(SyntaxElement::as_token)
+	}
+
+	/// the categories, pairs and includes of a document, map or category
+	pub fn entries(&self) -> impl Iterator<Item = &SyntaxNode> {
+		let map = match self.kind {
+			NodeKind::Document | NodeKind::Map => Some(self),
+			NodeKind::Category => self.value(),
+			_ => None,
+		};
+
+		map.into_iter().flat_map(SyntaxNode::nodes).filter(|node| {
+			matches!(
+				node.kind,
+				NodeKind::Category | NodeKind::Pair | NodeKind::Include
+			)
+		})
+	}
+
+	/// the key of a category or a pair
+	pub fn key(&self) -> Option<&SyntaxNode> {
+		match self.kind {
+			NodeKind::Category | NodeKind::Pair => {
+				self.nodes().find(|node| node.kind == NodeKind::Key)
+			}
+			_ => None,
+		}
+	}
+
+	/// the value of a pair or the map of a category
+	pub fn value(&self) -> Option<&SyntaxNode> {
+		match self.kind {
+			NodeKind::Category | NodeKind::Pair => self
+				.nodes()
+				.find(|node| !matches!(node.kind, NodeKind::Key | NodeKind::Error)),
+			_ => None,
+		}
+	}
+
+	/// the name of a key, or of the key of a category or a pair,
+	/// as it appears in a [`Path`](crate::error::Path)
+	///
+	/// quoted keys are unescaped and tagged keys are named by their tag.
+	pub fn name(&self) -> Option<String> {
+		let key = match self.kind {
+			NodeKind::Key => self,
+			_ => self.key()?,
+		};
+
+		let mut tokens = key.tokens().filter(|token| !token.kind.is_trivia());
+		let first = tokens.next()?;
+		if first.kind == TokenKind::String && tokens.next().is_none() {
+			let mut scratch = String::new();
+			let mut read = StrRead::new(&first.text);
+			if let Ok(str) = read.str(&mut scratch) {
+				return Some(str.to_owned());
+			}
+		}
+
+		let name = key
+			.tokens()
+			.take_while(|token| !token.kind.is_trivia())
+			.map(SyntaxToken::text)
+			.collect();
+		Some(name)
+	}
+}
+
+impl Display for SyntaxNode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for child in &self.children {
+			Display::fmt(child, f)?;
+		}
+		Ok(())
+	}
+}
+
+impl SyntaxToken {
+	/// the kind of the token
+	pub fn kind(&self) -> TokenKind {
+		self.kind
+	}
+
+	/// the text of the token
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+
+	/// the span of the token
+	pub fn span(&self) -> Span {
+		self.span
+	}
+}
+
+impl Display for SyntaxToken {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.text)
+	}
+}
+
+impl SyntaxElement {
+	/// the span of the element
+	pub fn span(&self) -> Span {
+		match self {
+			SyntaxElement::Node(node) => node.span,
+			SyntaxElement::Token(token) => token.span,
+		}
+	}
+
+	/// returns the node, if this is a node
+	pub fn as_node(&self) -> Option<&SyntaxNode> {
+		match self {
+			SyntaxElement::Node(node) => Some(node),
+			SyntaxElement::Token(_) => None,
+		}
+	}
+
+	/// returns the token, if this is a token
+	pub fn as_token(&self) -> Option<&SyntaxToken> {
+		match self {
+			SyntaxElement::Node(_) => None,
+			SyntaxElement::Token(token) => Some(token),
+		}
+	}
+}
+
+impl Display for SyntaxElement {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SyntaxElement::Node(node) => Display::fmt(node, f),
+			SyntaxElement::Token(token) => f.write_str(&token.text),
+		}
+	}
+}
+
+fn start(span: Span) -> Position {
+	match span {
+		Span::Point(pos) | Span::Span(pos, _) => pos,
+	}
+}
+
+fn end(span: Span) -> Position {
+	match span {
+		Span::Point(pos) | Span::Span(_, pos) => pos,
+	}
+}
@@ -0,0 +1,337 @@
+use super::{NodeKind, SyntaxElement, SyntaxNode, SyntaxToken};
+use crate::{
+	error::{Error, ErrorCode, Position},
+	lex::{Lexer, Token, TokenKind},
+};
+
+pub struct Parser<'a> {
+	tokens: Vec<Token<'a>>,
+	idx: usize,
+	/// the position at the end of the text
+	end: Position,
+	pub errors: Vec<Error>,
+}
+
+impl<'a> Parser<'a> {
+	pub fn new(text: &'a str) -> Self {
+		let mut lexer = Lexer::new(text);
+		let tokens = lexer.by_ref().collect();
+
+		Parser {
+			tokens,
+			idx: 0,
+			end: lexer.position(),
+			errors: Vec::new(),
+		}
+	}
+
+	fn nth(&self, n: usize) -> Option<TokenKind> {
+		self.tokens.get(self.idx + n).map(|token| token.kind)
+	}
+
+	fn peek(&self) -> Option<TokenKind> {
+		self.nth(0)
+	}
+
+	fn position(&self) -> Position {
+		match self.tokens.get(self.idx) {
+			Some(token) => super::start(token.span),
+			None => self.end,
+		}
+	}
+
+	fn bump(&mut self, children: &mut Vec<SyntaxElement>) {
+		let token = self.tokens[self.idx];
+		self.idx += 1;
+
+		children.push(SyntaxElement::Token(SyntaxToken {
+			kind: token.kind,
+			text: token.text.to_owned(),
+			span: token.span,
+		}));
+	}
+
+	/// consume whitespace on the current line
+	fn whitespace(&mut self, children: &mut Vec<SyntaxElement>) {
+		while self.peek() == Some(TokenKind::Whitespace) {
+			self.bump(children);
+		}
+	}
+
+	/// consume whitespace and comments on the current line
+	fn trivia_line(&mut self, children: &mut Vec<SyntaxElement>) {
+		while let Some(TokenKind::Whitespace | TokenKind::Comment) = self.peek() {
+			self.bump(children);
+		}
+	}
+
+	/// consume whitespace, comments and newlines
+	fn trivia(&mut self, children: &mut Vec<SyntaxElement>) {
+		while self.peek().is_some_and(TokenKind::is_trivia) {
+			self.bump(children);
+		}
+	}
+
+	/// report the current token as unexpected, with the error `code`
+	/// for its first char
+	fn unexpected(&mut self, code: fn(char) -> ErrorCode) {
+		let Some(token) = self.tokens.get(self.idx) else {
+			self.errors
+				.push(Error::with_point(ErrorCode::Eof, self.end));
+			return;
+		};
+
+		let first = token.text.chars().next().unwrap_or_default();
+		let code = match token.kind {
+			TokenKind::Newline => ErrorCode::UnexpectedNewline,
+			TokenKind::Error if matches!(first, '"' | '\'' | '`') => {
+				if super::end(token.span) == self.end {
+					ErrorCode::Eof
+				} else {
+					ErrorCode::UnexpectedNewline
+				}
+			}
+			_ => code(first),
+		};
+
+		self.errors.push(Error::with_span(code, token.span));
+	}
+
+	/// wrap the rest of the line in an error node
+	fn recover(&mut self, nested: bool) -> Option<SyntaxNode> {
+		let at = self.position();
+		let mut children = Vec::new();
+		while let Some(kind) = self.peek() {
+			if kind == TokenKind::Newline || (nested && kind == TokenKind::RBrace) {
+				break;
+			}
+
+			self.bump(&mut children);
+		}
+
+		(!children.is_empty()).then(|| SyntaxNode::new(NodeKind::Error, children, at))
+	}
+
+	pub fn document(&mut self) -> SyntaxNode {
+		let at = self.position();
+		let mut children = Vec::new();
+		self.entries(&mut children, false);
+
+		SyntaxNode::new(NodeKind::Document, children, at)
+	}
+
+	/// parse entries until the end of the text, or until the closing `}`
+	/// if the entries are `nested` in a map
+	fn entries(&mut self, children: &mut Vec<SyntaxElement>, nested: bool) {
+		loop {
+			self.trivia(children);
+
+			match self.peek() {
+				None => return,
+				Some(TokenKind::RBrace) if nested => return,
+				Some(_) => {
+					let entry = self.entry(nested);
+					children.push(SyntaxElement::Node(entry));
+				}
+			}
+
+			self.trivia_line(children);
+			match self.peek() {
+				None | Some(TokenKind::Newline) => {}
+				Some(TokenKind::RBrace) if nested => {}
+				Some(_) => {
+					self.unexpected(ErrorCode::ExpectedNewline);
+					if let Some(rest) = self.recover(nested) {
+						children.push(SyntaxElement::Node(rest));
+					}
+				}
+			}
+		}
+	}
+
+	fn entry(&mut self, nested: bool) -> SyntaxNode {
+		let at = self.position();
+		let mut children = Vec::new();
+
+		let is_include = self.tokens.get(self.idx).is_some_and(|token| {
+			token.kind == TokenKind::Ident
+				&& token.text == "include"
+				&& self.nth(1) == Some(TokenKind::Whitespace)
+				&& self.nth(2) == Some(TokenKind::String)
+		});
+		if is_include {
+			for _ in 0..3 {
+				self.bump(&mut children);
+			}
+			return SyntaxNode::new(NodeKind::Include, children, at);
+		}
+
+		if !self.is_atom() {
+			self.unexpected(ErrorCode::ExpectedAsciiAlphabetic);
+			return self.recover(nested).expect("the entry isn't empty");
+		}
+
+		let key = self.key();
+		children.push(SyntaxElement::Node(key));
+		self.whitespace(&mut children);
+
+		match self.peek() {
+			Some(TokenKind::Equals) => {
+				self.bump(&mut children);
+				self.whitespace(&mut children);
+
+				if let Some(value) = self.value() {
+					children.push(SyntaxElement::Node(value));
+				} else {
+					self.unexpected(ErrorCode::ExpectedQuote);
+					if let Some(rest) = self.recover(nested) {
+						children.push(SyntaxElement::Node(rest));
+					}
+				}
+
+				SyntaxNode::new(NodeKind::Pair, children, at)
+			}
+			Some(TokenKind::LBrace) => {
+				let map = self.map();
+				children.push(SyntaxElement::Node(map));
+
+				SyntaxNode::new(NodeKind::Category, children, at)
+			}
+			_ => {
+				self.unexpected(ErrorCode::ExpectedValue);
+				if let Some(rest) = self.recover(nested) {
+					children.push(SyntaxElement::Node(rest));
+				}
+
+				SyntaxNode::new(NodeKind::Error, children, at)
+			}
+		}
+	}
+
+	/// returns true if the current token can be part of a key or a scalar
+	fn is_atom(&self) -> bool {
+		matches!(
+			self.peek(),
+			Some(
+				TokenKind::Ident
+					| TokenKind::Bool
+					| TokenKind::Number
+					| TokenKind::String
+					| TokenKind::Variable
+			)
+		)
+	}
+
+	fn key(&mut self) -> SyntaxNode {
+		let at = self.position();
+		let mut children = Vec::new();
+		while self.is_atom() {
+			self.bump(&mut children);
+		}
+
+		// a tagged key, like `class [ "kitty" ]`
+		let ws = usize::from(self.peek() == Some(TokenKind::Whitespace));
+		if self.nth(ws) == Some(TokenKind::LBracket) {
+			self.whitespace(&mut children);
+			let seq = self.seq();
+			children.push(SyntaxElement::Node(seq));
+		}
+
+		SyntaxNode::new(NodeKind::Key, children, at)
+	}
+
+	fn value(&mut self) -> Option<SyntaxNode> {
+		let at = self.position();
+		let mut children = Vec::new();
+
+		let node = match self.peek()? {
+			TokenKind::String => {
+				self.bump(&mut children);
+
+				let ws = usize::from(self.peek() == Some(TokenKind::Whitespace));
+				match self.nth(ws) {
+					Some(TokenKind::LBracket) => {
+						self.whitespace(&mut children);
+						children.push(SyntaxElement::Node(self.seq()));
+						SyntaxNode::new(NodeKind::Tagged, children, at)
+					}
+					Some(TokenKind::LBrace) => {
+						self.whitespace(&mut children);
+						children.push(SyntaxElement::Node(self.map()));
+						SyntaxNode::new(NodeKind::Tagged, children, at)
+					}
+					_ => SyntaxNode::new(NodeKind::Scalar, children, at),
+				}
+			}
+			TokenKind::Ident | TokenKind::Bool | TokenKind::Number | TokenKind::Variable => {
+				// a variable can be part of a word, like in `$mod+q`
+				while let Some(
+					TokenKind::Ident | TokenKind::Bool | TokenKind::Number | TokenKind::Variable,
+				) = self.peek()
+				{
+					self.bump(&mut children);
+				}
+
+				SyntaxNode::new(NodeKind::Scalar, children, at)
+			}
+			TokenKind::LBracket => self.seq(),
+			TokenKind::LBrace => self.map(),
+			_ => return None,
+		};
+
+		Some(node)
+	}
+
+	fn seq(&mut self) -> SyntaxNode {
+		let at = self.position();
+		let mut children = Vec::new();
+		self.bump(&mut children);
+
+		loop {
+			self.trivia(&mut children);
+
+			match self.peek() {
+				Some(TokenKind::RBracket) => {
+					self.bump(&mut children);
+					break;
+				}
+				Some(TokenKind::Comma) => self.bump(&mut children),
+				None | Some(TokenKind::RBrace) => {
+					self.unexpected(ErrorCode::ExpectedSeqEnd);
+					break;
+				}
+				Some(_) => {
+					if let Some(value) = self.value() {
+						children.push(SyntaxElement::Node(value));
+					} else {
+						self.unexpected(ErrorCode::ExpectedSeqEnd);
+
+						let at = self.position();
+						let mut invalid = Vec::new();
+						self.bump(&mut invalid);
+
+						let invalid = SyntaxNode::new(NodeKind::Error, invalid, at);
+						children.push(SyntaxElement::Node(invalid));
+					}
+				}
+			}
+		}
+
+		SyntaxNode::new(NodeKind::Seq, children, at)
+	}
+
+	fn map(&mut self) -> SyntaxNode {
+		let at = self.position();
+		let mut children = Vec::new();
+		self.bump(&mut children);
+
+		self.entries(&mut children, true);
+		if self.peek() == Some(TokenKind::RBrace) {
+			self.bump(&mut children);
+		} else {
+			self.unexpected(ErrorCode::ExpectedMap);
+		}
+
+		SyntaxNode::new(NodeKind::Map, children, at)
+	}
+}
@@ -151,6 +151,20 @@ impl From<i64> for Value {
 	}
 }
 
+impl From<u128> for Value {
+	fn from(value: u128) -> Self {
+		let number = Number::from(value);
+		Value::Number(number)
+	}
+}
+
+impl From<i128> for Value {
+	fn from(value: i128) -> Self {
+		let number = Number::from(value);
+		Value::Number(number)
+	}
+}
+
 impl From<i32> for Value {
 	fn from(value: i32) -> Self {
 		Value::from(i64::from(value))
@@ -45,6 +45,22 @@ impl<'de> Visitor<'de> for ValueVisitor {
 		Ok(Value::Number(num))
 	}
 
+	fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+	where
+		E: serde_core::de::Error,
+	{
+		let num = Number::from(v);
+		Ok(Value::Number(num))
+	}
+
+	fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E>
+	where
+		E: serde_core::de::Error,
+	{
+		let num = Number::from(v);
+		Ok(Value::Number(num))
+	}
+
 	fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
 	where
 		E: serde_core::de::Error,
@@ -15,6 +15,8 @@ impl Number {
 		match self.0 {
 			InternalNumber::PosInt(u) => u as f64,
 			InternalNumber::NegInt(i) => i as f64,
+			InternalNumber::PosInt128(u) => u as f64,
+			InternalNumber::NegInt128(i) => i as f64,
 			InternalNumber::Float(ff) => ff,
 		}
 	}
@@ -24,15 +26,42 @@ impl Number {
 		match self.0 {
 			InternalNumber::PosInt(u) => i64::try_from(u).ok(),
 			InternalNumber::NegInt(i) => Some(i),
-			InternalNumber::Float(_) => None,
+			InternalNumber::PosInt128(_)
+			| InternalNumber::NegInt128(_)
+			| InternalNumber::Float(_) => None,
 		}
 	}
 
-	/// returns a `u64` if the number is a positive integer.
+	/// returns a `u64` if the number is a positive integer and fits into a `u64`.
 	pub fn as_u64(&self) -> Option<u64> {
 		match self.0 {
 			InternalNumber::PosInt(u) => Some(u),
-			InternalNumber::NegInt(_) | InternalNumber::Float(_) => None,
+			InternalNumber::NegInt(_)
+			| InternalNumber::PosInt128(_)
+			| InternalNumber::NegInt128(_)
+			| InternalNumber::Float(_) => None,
+		}
+	}
+
+	/// returns an `i128` if the number is an integer and fits into an `i128`.
+	pub fn as_i128(&self) -> Option<i128> {
+		match self.0 {
+			InternalNumber::PosInt(u) => Some(i128::from(u)),
+			InternalNumber::NegInt(i) => Some(i128::from(i)),
+			InternalNumber::PosInt128(u) => i128::try_from(u).ok(),
+			InternalNumber::NegInt128(i) => Some(i),
+			InternalNumber::Float(_) => None,
+		}
+	}
+
+	/// returns a `u128` if the number is a positive integer.
+	pub fn as_u128(&self) -> Option<u128> {
+		match self.0 {
+			InternalNumber::PosInt(u) => Some(u128::from(u)),
+			InternalNumber::PosInt128(u) => Some(u),
+			InternalNumber::NegInt(_) | InternalNumber::NegInt128(_) | InternalNumber::Float(_) => {
+				None
+			}
 		}
 	}
 }
@@ -45,6 +74,8 @@ impl Serialize for Number {
 		match self.0 {
 			InternalNumber::PosInt(u) => serializer.serialize_u64(u),
 			InternalNumber::NegInt(i) => serializer.serialize_i64(i),
+			InternalNumber::PosInt128(u) => serializer.serialize_u128(u),
+			InternalNumber::NegInt128(i) => serializer.serialize_i128(i),
 			InternalNumber::Float(ff) => serializer.serialize_f64(ff),
 		}
 	}
@@ -66,6 +97,10 @@ impl Display for Number {
 pub enum InternalNumber {
 	PosInt(u64),
 	NegInt(i64),
+	/// only used if the value doesn't fit into a `u64`
+	PosInt128(u128),
+	/// only used if the value doesn't fit into an `i64`
+	NegInt128(i128),
 	Float(f64),
 }
 
@@ -74,6 +109,8 @@ impl Display for InternalNumber {
 		match self {
 			InternalNumber::PosInt(u) => Display::fmt(u, f),
 			InternalNumber::NegInt(i) => Display::fmt(i, f),
+			InternalNumber::PosInt128(u) => Display::fmt(u, f),
+			InternalNumber::NegInt128(i) => Display::fmt(i, f),
 			InternalNumber::Float(ff) => Display::fmt(ff, f),
 		}
 	}
@@ -88,6 +125,8 @@ impl Hash for InternalNumber {
 		match self {
 			InternalNumber::PosInt(u) => u.hash(state),
 			InternalNumber::NegInt(i) => i.hash(state),
+			InternalNumber::PosInt128(u) => u.hash(state),
+			InternalNumber::NegInt128(i) => i.hash(state),
 			InternalNumber::Float(ff) => {
 				let bits = if *ff == 0. {
 					// to hold the +0.0 == -0.0 => hash(+0.0) == hash(-0.0) contract
@@ -122,6 +161,34 @@ impl From<i64> for Number {
 	}
 }
 
+impl From<u128> for Number {
+	fn from(value: u128) -> Self {
+		let internal = if let Ok(pos) = u64::try_from(value) {
+			InternalNumber::PosInt(pos)
+		} else {
+			InternalNumber::PosInt128(value)
+		};
+
+		Number(internal)
+	}
+}
+
+impl From<i128> for Number {
+	fn from(value: i128) -> Self {
+		if let Ok(pos) = u128::try_from(value) {
+			return Number::from(pos);
+		}
+
+		let internal = if let Ok(neg) = i64::try_from(value) {
+			InternalNumber::NegInt(neg)
+		} else {
+			InternalNumber::NegInt128(value)
+		};
+
+		Number(internal)
+	}
+}
+
 impl From<i32> for Number {
 	fn from(value: i32) -> Self {
 		Number::from(i64::from(value))
@@ -1,7 +1,8 @@
-use serde::Deserialize;
+use mayfig::{error::ErrorCode, ser::BytesFormat};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct Byt<'a> {
 	#[serde(with = "serde_bytes")]
 	#[serde(borrow)]
@@ -11,7 +12,7 @@ struct Byt<'a> {
 	e: E<'a>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 enum E<'a> {
 	#[serde(borrow, with = "serde_bytes")]
@@ -44,3 +45,49 @@ fn bytes() {
 	assert_eq!(b2.e, E::Byt(Cow::Owned(vec![0, 1, 3, 4])));
 	assert!(matches!(b2.s, Cow::Owned(_)));
 }
+
+#[test]
+fn ser_bytes() {
+	let b1 = Byt {
+		s: Cow::Borrowed(b"test"),
+		v: vec![0xc3, 0x28],
+		e: E::Byt(Cow::Borrowed(&[0xff])),
+	};
+
+	let ser = mayfig::to_string(&b1).unwrap();
+	assert_eq!(ser, "s = \"test\"\nv = [ 195 40 ]\ne = \"byt\" [ 255 ]\n");
+
+	let de = mayfig::from_str::<Byt>(&ser).unwrap();
+	assert_eq!(de.s, b1.s);
+	assert_eq!(de.v, b1.v);
+	assert_eq!(de.e, b1.e);
+
+	let b2 = Byt {
+		s: Cow::Borrowed(b"test"),
+		v: "ä".as_bytes().to_vec(),
+		e: E::Byt(Cow::Borrowed(b"val")),
+	};
+
+	let mut seq = Vec::new();
+	let mut serializer = mayfig::Serializer::new(&mut seq);
+	serializer.set_bytes_format(BytesFormat::Seq);
+	b2.serialize(&mut serializer).unwrap();
+	assert_eq!(
+		String::from_utf8(seq).unwrap(),
+		"s = [ 116 101 115 116 ]\nv = [ 195 164 ]\ne = \"byt\" [ 118 97 108 ]\n"
+	);
+
+	let mut string = Vec::new();
+	let mut serializer = mayfig::Serializer::new(&mut string);
+	serializer.set_bytes_format(BytesFormat::String);
+	b2.serialize(&mut serializer).unwrap();
+	assert_eq!(
+		String::from_utf8(string).unwrap(),
+		"s = \"test\"\nv = \"ä\"\ne = \"byt\" [ \"val\" ]\n"
+	);
+
+	let mut serializer = mayfig::Serializer::new(Vec::new());
+	serializer.set_bytes_format(BytesFormat::String);
+	let err = b1.serialize(&mut serializer).unwrap_err();
+	assert!(matches!(err.code(), ErrorCode::InvalidUtf8));
+}
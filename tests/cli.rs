@@ -0,0 +1,175 @@
+use std::{
+	io::{ErrorKind, Write},
+	path::PathBuf,
+	process::{Command, Output, Stdio},
+};
+
+const C1: &str = r#"input {
+	keyboard {
+		repeat-rate = 25
+	}
+}
+size = [ 24 24 ]
+bind {
+	mod+t = "spawn" [ "kitty" ]
+}
+theme = "Adwaita"
+"#;
+
+/// run the `mayfig` binary with `args`, writing `stdin` to its stdin
+fn mayfig(args: &[&str], stdin: &str) -> Output {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_mayfig"))
+		.args(args)
+		.env("NO_COLOR", "1")
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	// the binary can exit before it reads stdin, like for invalid arguments
+	let mut pipe = child.stdin.take().unwrap();
+	match pipe.write_all(stdin.as_bytes()) {
+		Err(err) if err.kind() == ErrorKind::BrokenPipe => {}
+		result => result.unwrap(),
+	}
+	drop(pipe);
+
+	child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+	std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+	std::str::from_utf8(&output.stderr).unwrap()
+}
+
+fn file(name: &str, text: &str) -> PathBuf {
+	let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+	std::fs::write(&path, text).unwrap();
+	path
+}
+
+#[test]
+fn check() {
+	let o1 = mayfig(&["check"], C1);
+	assert!(o1.status.success());
+	assert_eq!(stdout(&o1), "");
+
+	let o2 = mayfig(&["check"], "a = ]\nb = [ 1\n");
+	assert!(!o2.status.success());
+	let e2 = stderr(&o2);
+	assert!(e2.contains("error: expected quote \", ' or `, got ']'\n --> <stdin>:1:5\n"));
+	assert!(e2.contains("error: end of file\n"));
+
+	// errors in included files are shown with their source
+	file("cli-include.mf", "y = ]\n");
+	let main = file("cli-main.mf", "x = 1\ninclude \"cli-include.mf\"\n");
+	let o3 = mayfig(&["check", main.to_str().unwrap()], "");
+	assert!(!o3.status.success());
+	assert!(stderr(&o3).contains("cli-include.mf:1:5\n"));
+
+	// every error is reported, not just the first one
+	let o4 = mayfig(&["check"], "a = $x\nb {\n\tc = $y\n}\n");
+	assert!(!o4.status.success());
+	let e4 = stderr(&o4);
+	assert!(e4.contains("error: undefined variable `$x`\n --> <stdin>:1:5\n"));
+	assert!(e4.contains("error: undefined variable `$y`\n --> <stdin>:3:6\n"));
+}
+
+#[test]
+fn fmt() {
+	let o1 = mayfig(&["fmt"], "a{b=1}\nc = [1,2]");
+	assert!(o1.status.success());
+	assert_eq!(stdout(&o1), "a {\n\tb = 1\n}\nc = [ 1 2 ]\n");
+
+	let o2 = mayfig(&["fmt", "--check"], C1);
+	assert!(o2.status.success());
+
+	let path = file("cli-fmt.mf", "a{b=1}\n");
+	let o3 = mayfig(&["fmt", "--check", path.to_str().unwrap()], "");
+	assert!(!o3.status.success());
+	assert!(stderr(&o3).ends_with("cli-fmt.mf is not formatted\n"));
+
+	let o4 = mayfig(&["fmt", path.to_str().unwrap()], "");
+	assert!(o4.status.success());
+	assert_eq!(std::fs::read_to_string(&path).unwrap(), "a {\n\tb = 1\n}\n");
+
+	let o5 = mayfig(&["fmt"], "a = \n");
+	assert!(!o5.status.success());
+	assert!(stderr(&o5).starts_with("error: unexpected newline\n --> <stdin>:1:5\n"));
+}
+
+#[test]
+fn get() {
+	let o1 = mayfig(&["get", "input.keyboard.repeat-rate"], C1);
+	assert_eq!(stdout(&o1), "25\n");
+	let o2 = mayfig(&["get", "theme"], C1);
+	assert_eq!(stdout(&o2), "Adwaita\n");
+	let o3 = mayfig(&["get", "bind.mod+t"], C1);
+	assert_eq!(stdout(&o3), "\"spawn\" [ \"kitty\" ]\n");
+	let o4 = mayfig(&["get", "bind.mod+t[0]"], C1);
+	assert_eq!(stdout(&o4), "kitty\n");
+	let o5 = mayfig(&["get", "size"], C1);
+	assert_eq!(stdout(&o5), "[ 24 24 ]\n");
+	let o6 = mayfig(&["get", "input"], C1);
+	// maps are printed like the serializer prints them
+	assert_eq!(stdout(&o6), "keyboard {\n\t\"repeat-rate\" = 25\n}\n");
+
+	let o7 = mayfig(&["get", "input.mouse"], C1);
+	assert!(!o7.status.success());
+	assert_eq!(
+		stderr(&o7),
+		"error: path input.mouse doesn't exist in <stdin>\n"
+	);
+
+	let o8 = mayfig(&["get", "size["], C1);
+	assert!(!o8.status.success());
+}
+
+#[test]
+fn convert() {
+	let o1 = mayfig(&["convert"], C1);
+	assert!(o1.status.success());
+	let j1 = stdout(&o1);
+	assert_eq!(
+		j1,
+		r#"{
+  "input": {
+    "keyboard": {
+      "repeat-rate": 25
+    }
+  },
+  "size": [
+    24,
+    24
+  ],
+  "bind": {
+    "mod+t": {
+      "spawn": [
+        "kitty"
+      ]
+    }
+  },
+  "theme": "Adwaita"
+}
+"#
+	);
+
+	let o2 = mayfig(
+		&["convert", "--to", "mayfig"],
+		r#"{ "a": { "b": [ 1, 2 ] }, "c": true }"#,
+	);
+	assert!(o2.status.success());
+	assert_eq!(stdout(&o2), "a {\n\tb = [ 1 2 ]\n}\nc = true\n");
+
+	// json files are converted to mayfig by default
+	let path = file("cli-convert.json", r#"{ "size": 24 }"#);
+	let o3 = mayfig(&["convert", path.to_str().unwrap()], "");
+	assert_eq!(stdout(&o3), "size = 24\n");
+
+	let o4 = mayfig(&["convert", "--to", "mayfig"], "{ \"a\": null }");
+	assert!(!o4.status.success());
+}
@@ -0,0 +1,96 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[expect(dead_code)]
+struct Config {
+	cursor: Cursor,
+}
+
+#[derive(Debug, Deserialize)]
+#[expect(dead_code)]
+struct Cursor {
+	size: u32,
+}
+
+const D1: &str = r#"
+cursor {
+	size = "24"
+}
+"#;
+
+const D2: &str = r#"
+cursor {
+	theme = "Adwaita"
+}
+"#;
+
+#[test]
+fn render() {
+	let e1 = mayfig::from_str::<Config>(D1).unwrap_err();
+	// reports are plain unless they are styled
+	let mut r1 = e1.render(D1, "config.mf");
+	assert_eq!(
+		r1.to_string(),
+		"error: expected ascii numeric, got '\"'
+ --> config.mf:3:9
+  |
+3 |     size = \"24\"
+  |            ^
+  |
+  = note: in `cursor.size`"
+	);
+
+	r1.set_styled(true);
+	assert!(r1.to_string().contains('\x1b'));
+}
+
+#[test]
+fn render_multiline() {
+	let e2 = mayfig::from_str::<Config>(D2).unwrap_err();
+	let r2 = e2.render(D2, "config.mf");
+	assert_eq!(
+		r2.to_string(),
+		"error: missing field `size`
+ --> config.mf:2:8
+  |
+2 |   cursor {
+  |  ________^
+3 | |     theme = \"Adwaita\"
+4 | | }
+  | |_^
+  |
+  = note: in `cursor`"
+	);
+}
+
+#[test]
+fn render_included() {
+	let mut resolver = mayfig::de::MemoryResolver::new();
+	resolver.insert("cursor.mf", "size = \"24\"\n");
+
+	let main = "cursor {\n\tinclude \"cursor.mf\"\n}\n";
+	let mut de = mayfig::Deserializer::from_str(main);
+	de.set_resolver(resolver);
+	let e1 = Config::deserialize(&mut de).unwrap_err();
+
+	// the span is in the included source, so the main input has no snippet of it
+	let r1 = e1.render(main, "config.mf");
+	assert_eq!(
+		r1.to_string(),
+		"error: expected ascii numeric, got '\"'
+  |
+  = note: in `cursor.size`"
+	);
+
+	let r2 = e1.render_with(&de, main, "config.mf");
+	assert_eq!(
+		r2.to_string(),
+		"error: expected ascii numeric, got '\"'
+ --> cursor.mf:1:8
+  |
+1 | size = \"24\"
+  |        ^
+  |
+  = note: in `cursor.size`"
+	);
+}
@@ -0,0 +1,181 @@
+use mayfig::{de::DuplicateKeys, error::ErrorCode, Deserializer};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Keyboard {
+	repeat_rate: u32,
+	repeat_delay: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+	keyboard: Keyboard,
+	#[serde(default)]
+	bind: HashMap<String, String>,
+}
+
+const D1: &str = r#"
+keyboard {
+	repeat-rate = 25
+	repeat-delay = 300
+	repeat-rate = 50
+}
+"#;
+
+const D2: &str = r#"
+keyboard {
+	repeat-rate = 25
+	repeat-delay = 300
+}
+
+bind {
+	mod+q = "close"
+	"mod+q" = "quit"
+	mod+t = "kitty"
+}
+"#;
+
+fn with_policy<'a, T: Deserialize<'a>>(input: &'a str, policy: DuplicateKeys) -> T {
+	try_with_policy(input, policy).unwrap()
+}
+
+fn try_with_policy<'a, T: Deserialize<'a>>(
+	input: &'a str,
+	policy: DuplicateKeys,
+) -> Result<T, mayfig::Error> {
+	let mut de = Deserializer::from_str(input);
+	de.set_duplicate_keys(policy);
+	T::deserialize(&mut de)
+}
+
+#[test]
+fn allow() {
+	// the default passes every key on, like before duplicates were detected
+	let e1 = mayfig::from_str::<Config>(D1).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::Custom(msg) if msg.contains("duplicate field")));
+
+	let d2 = mayfig::from_str::<Config>(D2).unwrap();
+	assert_eq!(d2.bind["mod+q"], "quit");
+	assert_eq!(d2.bind.len(), 2);
+
+	let mut de = Deserializer::from_reader(D2.as_bytes());
+	let r2 = Config::deserialize(&mut de).unwrap();
+	assert_eq!(r2, d2);
+}
+
+#[test]
+fn error() {
+	let e1 = try_with_policy::<Config>(D1, DuplicateKeys::Error).unwrap_err();
+	let ErrorCode::DuplicateKey(duplicate) = e1.code() else {
+		panic!("expected duplicate key, got {e1:?}");
+	};
+	assert_eq!(duplicate.key, "repeat-rate");
+	assert_eq!(duplicate.first.range(), 13..24);
+	assert_eq!(duplicate.second.range(), 51..62);
+	assert_eq!(e1.span().unwrap().range(), 51..62);
+	assert_eq!(e1.path().unwrap().to_string(), "keyboard");
+	assert_eq!(
+		e1.to_string(),
+		"duplicate key `repeat-rate` (first defined at line 3, columns 2 to 13) at keyboard, line 5, columns 2 to 13"
+	);
+
+	let e2 = try_with_policy::<Config>(D2, DuplicateKeys::Error).unwrap_err();
+	assert!(matches!(
+		e2.code(),
+		ErrorCode::DuplicateKey(duplicate) if duplicate.key == "mod+q"
+	));
+
+	assert_eq!(e2.span().unwrap().range(), 78..85);
+}
+
+#[test]
+fn last_wins() {
+	let d1 = with_policy::<Config>(D1, DuplicateKeys::LastWins);
+	assert_eq!(d1.keyboard.repeat_rate, 50);
+	assert_eq!(d1.keyboard.repeat_delay, 300);
+
+	let d2 = with_policy::<Config>(D2, DuplicateKeys::LastWins);
+	assert_eq!(d2.bind["mod+q"], "quit");
+	assert_eq!(d2.bind["mod+t"], "kitty");
+
+	let mut de = Deserializer::from_reader(D1.as_bytes());
+	de.set_duplicate_keys(DuplicateKeys::LastWins);
+	let r1 = Config::deserialize(&mut de).unwrap();
+	assert_eq!(r1, d1);
+}
+
+#[test]
+fn first_wins() {
+	let d1 = with_policy::<Config>(D1, DuplicateKeys::FirstWins);
+	assert_eq!(d1.keyboard.repeat_rate, 25);
+	assert_eq!(d1.keyboard.repeat_delay, 300);
+
+	let d2 = with_policy::<Config>(D2, DuplicateKeys::FirstWins);
+	assert_eq!(d2.bind["mod+q"], "close");
+	assert_eq!(d2.bind.len(), 2);
+
+	let mut de = Deserializer::from_reader(D1.as_bytes());
+	de.set_duplicate_keys(DuplicateKeys::FirstWins);
+	let r1 = Config::deserialize(&mut de).unwrap();
+	assert_eq!(r1, d1);
+
+	let mut de = Deserializer::from_str(D1);
+	de.set_duplicate_keys(DuplicateKeys::FirstWins);
+	Config::deserialize(&mut de).unwrap();
+	assert!(de.unused_keys().is_empty());
+}
+
+const D3: &str = r#"
+a {
+	b {
+		c = 1
+		c = 2
+	}
+	l = [
+		{
+			d = 1
+			d = 2
+		}
+		{
+			d = 3
+		}
+	]
+	b {
+		c = 3
+		c = 4
+	}
+}
+"#;
+
+#[test]
+fn nested() {
+	#[derive(Debug, Deserialize)]
+	struct Inner {
+		b: HashMap<String, u32>,
+		l: Vec<HashMap<String, u32>>,
+	}
+
+	let d3 = with_policy::<HashMap<String, Inner>>(D3, DuplicateKeys::LastWins);
+	assert_eq!(d3["a"].b["c"], 4);
+	assert_eq!(d3["a"].l[0]["d"], 2);
+	assert_eq!(d3["a"].l[1]["d"], 3);
+
+	let d3 = with_policy::<HashMap<String, Inner>>(D3, DuplicateKeys::FirstWins);
+	assert_eq!(d3["a"].b["c"], 1);
+	assert_eq!(d3["a"].l[0]["d"], 1);
+
+	let mut de = Deserializer::from_reader(D3.as_bytes());
+	de.set_duplicate_keys(DuplicateKeys::LastWins);
+	let r3 = HashMap::<String, Inner>::deserialize(&mut de).unwrap();
+	assert_eq!(r3["a"].b["c"], 4);
+
+	// both `b` categories are merged, and then the first `c` wins
+	let mut de = Deserializer::from_str(D3);
+	de.set_duplicate_keys(DuplicateKeys::FirstWins);
+	de.set_merge_categories(true);
+	let m3 = HashMap::<String, Inner>::deserialize(&mut de).unwrap();
+	assert_eq!(m3["a"].b["c"], 1);
+	assert_eq!(m3["a"].b.len(), 1);
+}
@@ -0,0 +1,245 @@
+use mayfig::{edit::Document, error::ErrorCode};
+use serde::Serialize;
+
+const D1: &str = r#"# my config
+input {
+	keyboard {
+		# xkb-file = "~/.config/keymap/may.xkb"
+
+		repeat-delay = 600 # ms
+		repeat-rate = 25
+	}
+}
+
+cursor {
+	xcursor-size = 24
+}
+
+size = [ 24, 24 ]
+exceptions = [
+	"com.system76.CosmicFiles" # files
+	"blueman-manager"
+]
+
+bind {
+	"mod+q" = "close"
+	mod+t = "spawn" [ "kitty" ]
+}
+"#;
+
+#[test]
+fn get() {
+	let d1 = D1.parse::<Document>().unwrap();
+	assert_eq!(d1.to_string(), D1);
+
+	let delay = d1.get("input.keyboard.repeat-delay").unwrap();
+	assert_eq!(delay.to_string(), "600");
+	assert_eq!(&D1[delay.span().range()], "600");
+
+	let cursor = d1.get("cursor").unwrap();
+	assert_eq!(cursor.to_string(), "{\n\txcursor-size = 24\n}");
+
+	assert_eq!(d1.get("size[1]").unwrap().to_string(), "24");
+	assert_eq!(
+		d1.get("exceptions[1]").unwrap().to_string(),
+		"\"blueman-manager\""
+	);
+	assert_eq!(d1.get("bind.\"mod+q\"").unwrap().to_string(), "\"close\"");
+	assert_eq!(d1.get("bind.mod+q").unwrap().to_string(), "\"close\"");
+	assert_eq!(d1.get("bind.mod+t[0]").unwrap().to_string(), "\"kitty\"");
+
+	assert!(d1.get("input.mouse").is_none());
+	assert!(d1.get("size[2]").is_none());
+	assert!(d1.get("cursor.xcursor-size.nested").is_none());
+	assert!(d1.get("input..keyboard").is_none());
+}
+
+#[test]
+fn set() {
+	let mut d1 = D1.parse::<Document>().unwrap();
+	d1.set("input.keyboard.repeat-delay", &300).unwrap();
+	d1.set("size[0]", &32).unwrap();
+	d1.set("bind.mod+t[0]", "firefox").unwrap();
+	d1.set("exceptions[0]", "org.gnome.Nautilus").unwrap();
+
+	let e1 = D1
+		.replace("repeat-delay = 600", "repeat-delay = 300")
+		.replace("[ 24, 24 ]", "[ 32, 24 ]")
+		.replace("[ \"kitty\" ]", "[ \"firefox\" ]")
+		.replace("com.system76.CosmicFiles", "org.gnome.Nautilus");
+	assert_eq!(d1.to_string(), e1);
+
+	#[derive(Serialize)]
+	struct Cursor {
+		theme: &'static str,
+		size: u32,
+	}
+
+	// the value is replaced by a category, and the other way around
+	let mut d1 = D1.parse::<Document>().unwrap();
+	let cursor = Cursor {
+		theme: "Adwaita",
+		size: 32,
+	};
+	d1.set("cursor", &cursor).unwrap();
+	d1.set("input.keyboard", &[1, 2]).unwrap();
+
+	let e1 = D1
+		.replace(
+			"cursor {\n\txcursor-size = 24\n}",
+			"cursor {\n\ttheme = \"Adwaita\"\n\tsize = 32\n}",
+		)
+		.replace(
+			"\tkeyboard {\n\t\t# xkb-file = \"~/.config/keymap/may.xkb\"\n\n\t\trepeat-delay = 600 # ms\n\t\trepeat-rate = 25\n\t}",
+			"\tkeyboard = [ 1 2 ]",
+		);
+	assert_eq!(d1.to_string(), e1);
+
+	let e1 = d1.set("cursor.theme.nested", &1).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::UnknownPath(path) if path == "cursor.theme.nested"));
+	let e2 = d1.set("cursor.[", &1).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::InvalidPath(path) if path == "cursor.["));
+}
+
+#[test]
+fn insert() {
+	let mut d1 = D1.parse::<Document>().unwrap();
+	d1.insert("cursor.xcursor-theme", "Adwaita").unwrap();
+	d1.insert("input.keyboard.layout", "us").unwrap();
+	d1.insert("input.touchpad.tap", &true).unwrap();
+	d1.insert("output.\"eDP-1\".scale", &1.5).unwrap();
+	d1.insert("bind.\"mod+shift+q\"", "quit").unwrap();
+
+	let e1 = D1
+		.replace(
+			"xcursor-size = 24\n",
+			"xcursor-size = 24\n\txcursor-theme = \"Adwaita\"\n",
+		)
+		.replace(
+			"repeat-rate = 25\n\t}\n",
+			"repeat-rate = 25\n\t\tlayout = \"us\"\n\t}\n\ttouchpad {\n\t\ttap = true\n\t}\n",
+		)
+		.replace(
+			"[ \"kitty\" ]\n",
+			"[ \"kitty\" ]\n\tmod+shift+q = \"quit\"\n",
+		) + "output {\n\teDP-1 {\n\t\tscale = 1.5\n\t}\n}\n";
+	assert_eq!(d1.to_string(), e1);
+
+	let mut d2 = D1.parse::<Document>().unwrap();
+	d2.insert("size[0]", &16).unwrap();
+	d2.insert("size[3]", &48).unwrap();
+	d2.insert("exceptions[1]", "kitty").unwrap();
+	d2.insert("exceptions[3]", "firefox").unwrap();
+	d2.insert("bind.mod+t[1]", "--single-instance").unwrap();
+
+	let e2 = D1
+		.replace("[ 24, 24 ]", "[ 16, 24, 24, 48 ]")
+		.replace(
+			"\t\"blueman-manager\"\n",
+			"\t\"kitty\"\n\t\"blueman-manager\"\n\t\"firefox\"\n",
+		)
+		.replace("[ \"kitty\" ]", "[ \"kitty\" \"--single-instance\" ]");
+	assert_eq!(d2.to_string(), e2);
+
+	let e1 = d2.insert("size[5]", &1).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::UnknownPath(path) if path == "size[5]"));
+	let e2 = d2.insert("cursor.xcursor-size.nested", &1).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::UnknownPath(_)));
+
+	// inserting an existing key replaces its value
+	let mut d3 = D1.parse::<Document>().unwrap();
+	d3.insert("cursor.xcursor-size", &32).unwrap();
+	assert_eq!(d3.to_string(), D1.replace("= 24\n", "= 32\n"));
+}
+
+#[test]
+fn insert_empty() {
+	let mut d1 = "".parse::<Document>().unwrap();
+	d1.insert("cursor.xcursor-size", &24).unwrap();
+	d1.insert("size", &[1, 2]).unwrap();
+	assert_eq!(
+		d1.to_string(),
+		"cursor {\n\txcursor-size = 24\n}\nsize = [ 1 2 ]\n"
+	);
+
+	let mut d2 = "a {}\nb {\n}\nc = []\n# end".parse::<Document>().unwrap();
+	d2.insert("a.x", &1).unwrap();
+	d2.insert("b.y", &2).unwrap();
+	d2.insert("c[0]", &3).unwrap();
+	d2.insert("z", &4).unwrap();
+	assert_eq!(
+		d2.to_string(),
+		"a {\n\tx = 1\n}\nb {\n\ty = 2\n}\nc = [ 3 ]\nz = 4\n# end"
+	);
+
+	// the indentation of the document is kept
+	let mut d3 = "a {\n  b = 1\n}\n".parse::<Document>().unwrap();
+	d3.insert("c.d.e", &1).unwrap();
+	assert_eq!(
+		d3.to_string(),
+		"a {\n  b = 1\n}\nc {\n  d {\n    e = 1\n  }\n}\n"
+	);
+}
+
+#[test]
+fn remove() {
+	let mut d1 = D1.parse::<Document>().unwrap();
+	d1.remove("input.keyboard.repeat-delay").unwrap();
+	d1.remove("cursor").unwrap();
+	d1.remove("size[0]").unwrap();
+	d1.remove("exceptions[0]").unwrap();
+	d1.remove("bind.mod+t[0]").unwrap();
+
+	let e1 = D1
+		.replace("\t\trepeat-delay = 600 # ms\n", "")
+		.replace("cursor {\n\txcursor-size = 24\n}\n", "")
+		.replace("[ 24, 24 ]", "[ 24 ]")
+		.replace("\t\"com.system76.CosmicFiles\" # files\n", "")
+		.replace("[ \"kitty\" ]", "[ ]");
+	assert_eq!(d1.to_string(), e1);
+
+	d1.remove("size[0]").unwrap();
+	assert!(d1.to_string().contains("size = [ ]\n"));
+
+	let e1 = d1.remove("cursor").unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::UnknownPath(path) if path == "cursor"));
+}
+
+#[test]
+fn tagged() {
+	// a string followed by a sequence would be read as a tagged value
+	let mut d1 = "a = [ \"s\" ]\n".parse::<Document>().unwrap();
+	d1.insert("a[1]", &[1]).unwrap();
+	assert_eq!(d1.to_string(), "a = [ \"s\", [ 1 ] ]\n");
+
+	let mut d2 = "a = [ [ 1 ] ]\n".parse::<Document>().unwrap();
+	d2.insert("a[0]", "s").unwrap();
+	assert_eq!(d2.to_string(), "a = [ \"s\", [ 1 ] ]\n");
+
+	let mut d3 = "a = [ \"s\" 2 ]\n".parse::<Document>().unwrap();
+	d3.insert("a[1]", &[1]).unwrap();
+	assert_eq!(d3.to_string(), "a = [ \"s\", [ 1 ] 2 ]\n");
+
+	let mut d4 = "a = [ \"s\" 2 [ 1 ] ]\n".parse::<Document>().unwrap();
+	d4.remove("a[1]").unwrap();
+	assert_eq!(d4.to_string(), "a = [ \"s\", [ 1 ] ]\n");
+
+	let mut d5 = "a = [ \"s\" 2 3 ]\n".parse::<Document>().unwrap();
+	d5.remove("a[1]").unwrap();
+	assert_eq!(d5.to_string(), "a = [ \"s\" 3 ]\n");
+
+	let mut d6 = "a = [ \"s\" 2 ]\n".parse::<Document>().unwrap();
+	d6.set("a[1]", &[1]).unwrap();
+	assert_eq!(d6.to_string(), "a = [ \"s\", [ 1 ] ]\n");
+
+	let mut d7 = "a = [ 2 [ 1 ] ]\n".parse::<Document>().unwrap();
+	d7.set("a[0]", "s").unwrap();
+	assert_eq!(d7.to_string(), "a = [ \"s\", [ 1 ] ]\n");
+}
+
+#[test]
+fn invalid() {
+	let e1 = "a = \nb = 2".parse::<Document>().unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::UnexpectedNewline));
+	assert_eq!(e1.span().unwrap().range(), 4..5);
+}
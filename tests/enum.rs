@@ -95,14 +95,7 @@ fn tagged() {
 
 	let t7 = mayfig::from_str::<Tag>(T7).unwrap_err();
 	assert!(matches!(t7.code(), ErrorCode::UnexpectedNewline));
-	assert_eq!(
-		t7.span(),
-		Some(Span::Point(Position {
-			line: 2,
-			col: 9,
-			index: 9
-		}))
-	);
+	assert_eq!(t7.span(), Some(Span::Point(Position::new(2, 9, 9))));
 
 	let t8 = mayfig::from_str::<Tag>(T8).unwrap();
 	assert_eq!(t8.t, Tagged::Un);
@@ -0,0 +1,84 @@
+use mayfig::{de::SystemEnvironment, error::ErrorCode, Deserializer};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Keyboard {
+	xkb_file: String,
+	layout: String,
+	variant: String,
+	raw: String,
+}
+
+const E1: &str = r#"
+xkb-file = "~/.config/keymap/may.xkb"
+layout = "${LAYOUT}"
+variant = "${VARIANT:-intl}-${LAYOUT}"
+raw = `~/${LAYOUT}`
+"#;
+
+fn env() -> HashMap<String, String> {
+	HashMap::from([
+		("HOME".to_owned(), "/home/may".to_owned()),
+		("LAYOUT".to_owned(), "us".to_owned()),
+		("VARIANT".to_owned(), String::new()),
+	])
+}
+
+fn expand<'a, T: Deserialize<'a>>(
+	input: &'a str,
+	env: HashMap<String, String>,
+) -> Result<T, mayfig::Error> {
+	let mut de = Deserializer::from_str(input);
+	de.set_environment(env);
+	T::deserialize(&mut de)
+}
+
+#[test]
+fn env_expand() {
+	let e1 = expand::<Keyboard>(E1, env()).unwrap();
+	assert_eq!(
+		e1,
+		Keyboard {
+			xkb_file: "/home/may/.config/keymap/may.xkb".to_owned(),
+			layout: "us".to_owned(),
+			variant: "intl-us".to_owned(),
+			raw: "~/${LAYOUT}".to_owned(),
+		}
+	);
+
+	let e2 = mayfig::from_str::<Keyboard>(E1).unwrap();
+	assert_eq!(e2.xkb_file, "~/.config/keymap/may.xkb");
+	assert_eq!(e2.layout, "${LAYOUT}");
+
+	let mut de = Deserializer::from_str("path = \"${PATH}\"");
+	de.set_environment(SystemEnvironment);
+	let e3 = HashMap::<String, String>::deserialize(&mut de).unwrap();
+	assert_eq!(e3["path"], std::env::var("PATH").unwrap());
+
+	// `$${` escapes the expansion
+	let e4 = expand::<HashMap<String, String>>(r#"a = "$${LAYOUT} ${LAYOUT}""#, env()).unwrap();
+	assert_eq!(e4["a"], "${LAYOUT} us");
+	let e5 = expand::<HashMap<String, String>>(r#"a = "$$${LAYOUT}}""#, env()).unwrap();
+	assert_eq!(e5["a"], "$${LAYOUT}}");
+}
+
+#[test]
+fn env_error() {
+	let mut env = env();
+	env.remove("LAYOUT");
+
+	let e1 = expand::<Keyboard>(E1, env.clone()).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::UndefinedEnvVar(name) if name == "LAYOUT"));
+	assert_eq!(e1.span().unwrap().range(), 48..59);
+	assert_eq!(e1.path().unwrap().to_string(), "layout");
+
+	env.remove("HOME");
+	let e2 = expand::<Keyboard>(E1, env).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::UndefinedEnvVar(name) if name == "HOME"));
+	assert_eq!(e2.span().unwrap().range(), 12..38);
+
+	let e3 = expand::<HashMap<String, String>>("a = \"${HOME\"", HashMap::new()).unwrap_err();
+	assert!(matches!(e3.code(), ErrorCode::UnterminatedEnvVar));
+}
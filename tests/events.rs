@@ -0,0 +1,284 @@
+use mayfig::{
+	error::{ErrorCode, Span},
+	events::{Event, Parser, Scalar},
+	Error,
+};
+use serde::Serialize;
+use std::{borrow::Cow, collections::HashMap};
+
+const E1: &str = r#"# my config
+include "binds.mf"
+input {
+	repeat-rate = 25 # ms
+	tap = TRUE
+}
+size = [ 24, 24 ]
+bind {
+	mod+q = "close"
+	mod+t = "spawn" [ "kitty" ]
+}
+windowrules {
+	class [ "kitty" ] {
+		opacity = 0.6
+	}
+}
+outputs = [ { name = 'DP-1' } ]
+"#;
+
+/// the events of the `text`, without their spans
+fn events(text: &str) -> Vec<String> {
+	Parser::from_str(text)
+		.map(|event| match event.unwrap() {
+			Event::MapStart(Some(key), _) => format!("{key} {{"),
+			Event::MapStart(None, _) => "{".to_owned(),
+			Event::MapEnd(_) => "}".to_owned(),
+			Event::Key(key, _) => format!("{key} ="),
+			Event::SeqStart(_) => "[".to_owned(),
+			Event::SeqEnd(_) => "]".to_owned(),
+			Event::Scalar(Scalar::String(str), _) => format!("{str:?}"),
+			Event::Scalar(Scalar::Number(num), _) => num.into_owned(),
+			Event::Scalar(Scalar::Bool(b), _) => b.to_string(),
+			Event::TagStart(tag, _) => format!("{tag:?} tag"),
+			Event::Comment(comment) => format!("#{comment}"),
+			Event::Include(path, _) => format!("include {path:?}"),
+		})
+		.collect()
+}
+
+fn error(text: &str) -> Error {
+	let mut parser = Parser::from_str(text);
+	let err = parser
+		.find_map(Result::err)
+		.expect("text should be invalid");
+	assert!(parser.next().is_none());
+	err
+}
+
+fn span(span: Span) -> (usize, usize) {
+	let Span::Span(start, end) = span else {
+		panic!("span should have a start and an end");
+	};
+	(start.index, end.index)
+}
+
+#[test]
+fn events_in_order() {
+	assert_eq!(
+		events(E1),
+		[
+			"# my config",
+			"include \"binds.mf\"",
+			"input {",
+			"repeat-rate =",
+			"25",
+			"# ms",
+			"tap =",
+			"true",
+			"}",
+			"size =",
+			"[",
+			"24",
+			"24",
+			"]",
+			"bind {",
+			"mod+q =",
+			"\"close\"",
+			"mod+t =",
+			"\"spawn\" tag",
+			"[",
+			"\"kitty\"",
+			"]",
+			"}",
+			"windowrules {",
+			"class {",
+			"opacity =",
+			"0.6",
+			"}",
+			"}",
+			"outputs =",
+			"[",
+			"{",
+			"name =",
+			"\"DP-1\"",
+			"}",
+			"]",
+		]
+	);
+
+	assert_eq!(events("a = { b = 1 }\n"), ["a {", "b =", "1", "}"]);
+	assert_eq!(events(""), [] as [&str; 0]);
+}
+
+#[test]
+fn tagged() {
+	#[derive(Serialize)]
+	enum Action {
+		Move { x: i32 },
+	}
+
+	let text = mayfig::to_string(&HashMap::from([("e", Action::Move { x: 1 })])).unwrap();
+	assert_eq!(events(&text), ["e =", "\"Move\" tag", "{", "x =", "1", "}"]);
+
+	assert_eq!(
+		events("f = [ \"B\" { }, \"C\"\n]\n"),
+		["f =", "[", "\"B\" tag", "{", "}", "\"C\"", "]"]
+	);
+}
+
+#[test]
+fn spans() {
+	let text = "windowrules {\n\tclass [ \"kitty\" ] {\n\t\topacity = 0.6\n\t}\n}\n";
+	let events = Parser::from_str(text)
+		.collect::<Result<Vec<_>, _>>()
+		.unwrap();
+
+	let Event::MapStart(Some(key), s1) = &events[1] else {
+		panic!("should be a map start");
+	};
+	assert_eq!(key, "class");
+	assert_eq!(&text[span(*s1).0..span(*s1).1], "class [ \"kitty\" ]");
+
+	let Event::Scalar(_, s2) = &events[3] else {
+		panic!("should be a scalar");
+	};
+	assert_eq!(&text[span(*s2).0..span(*s2).1], "0.6");
+
+	let Event::MapEnd(s3) = &events[4] else {
+		panic!("should be a map end");
+	};
+	assert_eq!(span(*s3), (52, 53));
+
+	// brackets have the span of their char
+	let brackets = Parser::from_str(
+		"a = [ 1 ]
+",
+	)
+	.filter_map(|event| match event.unwrap() {
+		Event::SeqStart(span) | Event::SeqEnd(span) => Some(self::span(span)),
+		_ => None,
+	})
+	.collect::<Vec<_>>();
+	assert_eq!(brackets, [(4, 5), (8, 9)]);
+}
+
+#[test]
+fn borrowed() {
+	let mut parser = Parser::from_str("key = \"value\"\nesc = \"\\n\"\n");
+	assert!(matches!(
+		parser.next(),
+		Some(Ok(Event::Key(Cow::Borrowed("key"), _)))
+	));
+	assert!(matches!(
+		parser.next(),
+		Some(Ok(Event::Scalar(Scalar::String(Cow::Borrowed("value")), _)))
+	));
+	parser.next();
+	assert!(matches!(
+		parser.next(),
+		Some(Ok(Event::Scalar(Scalar::String(Cow::Owned(s)), _))) if s == "\n"
+	));
+
+	// the reader has to copy everything
+	let mut parser = Parser::from_reader("key = 1\n".as_bytes());
+	assert!(matches!(parser.next(), Some(Ok(Event::Key(Cow::Owned(key), _))) if key == "key"));
+
+	let p1 = Parser::from_slice(E1.as_bytes()).collect::<Result<Vec<_>, _>>();
+	let p2 = Parser::from_reader(E1.as_bytes()).collect::<Result<Vec<_>, _>>();
+	let p3 = Parser::from_str(E1).collect::<Result<Vec<_>, _>>();
+	assert_eq!(p1.unwrap(), p3.as_ref().unwrap().clone());
+	assert_eq!(p2.unwrap(), p3.unwrap());
+}
+
+#[test]
+fn skip() {
+	let mut parser = Parser::from_str(E1);
+	let mut keys = Vec::new();
+	while let Some(event) = parser.next() {
+		match event.unwrap() {
+			Event::MapStart(key, _) => {
+				keys.extend(key);
+				parser.skip_to_end().unwrap();
+			}
+			Event::SeqStart(_) => parser.skip_to_end().unwrap(),
+			Event::Key(key, _) => keys.push(key),
+			_ => {}
+		}
+	}
+	assert_eq!(keys, ["input", "size", "bind", "windowrules", "outputs"]);
+
+	// skip the rest of a sequence
+	let mut parser = Parser::from_str("a = [ 1 [ \"]\" ] # ]\n]\nb = 2\n");
+	assert!(matches!(parser.next(), Some(Ok(Event::Key(..)))));
+	assert!(matches!(parser.next(), Some(Ok(Event::SeqStart(_)))));
+	parser.skip_to_end().unwrap();
+	assert!(matches!(parser.next(), Some(Ok(Event::Key(key, _))) if key == "b"));
+
+	// skipping the top level map skips everything
+	let mut parser = Parser::from_str(E1);
+	parser.next();
+	parser.skip_to_end().unwrap();
+	assert!(parser.next().is_none());
+
+	let mut parser = Parser::from_str("a {\n\tb = [ 1\n");
+	parser.next();
+	let e1 = parser.skip_to_end().unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::Eof));
+}
+
+#[test]
+fn variables() {
+	let text =
+		"$mod = \"super\"\n$on = true\nbind {\n\t$mod+q = $on\n\t$n = 1\n\tsize = [ $n $mod ]\n}\n";
+	assert_eq!(
+		events(text),
+		[
+			"bind {",
+			"super+q =",
+			"true",
+			"size =",
+			"[",
+			"1",
+			"\"super\"",
+			"]",
+			"}"
+		]
+	);
+
+	// variables are scoped to their map
+	let e1 = error("a {\n\t$n = 1\n}\nb = $n\n");
+	assert!(matches!(e1.code(), ErrorCode::UndefinedVariable(name) if name == "n"));
+
+	let mut parser = Parser::from_str("a {\n\t$n = 1\n\tb = 2\n}\nc = $n\n");
+	parser.next();
+	parser.skip_to_end().unwrap();
+	parser.next();
+	let e2 = parser.next().unwrap().unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::UndefinedVariable(name) if name == "n"));
+}
+
+#[test]
+fn errors() {
+	let e1 = error("a = 1 b = 2\n");
+	assert!(matches!(e1.code(), ErrorCode::ExpectedNewline('b')));
+	let e2 = error("a = # no\n");
+	assert!(matches!(e2.code(), ErrorCode::UnexpectedNewline));
+	let e3 = error("a = [ 1\n");
+	assert!(matches!(e3.code(), ErrorCode::Eof));
+	let e4 = error("a = yes\n");
+	assert!(matches!(e4.code(), ErrorCode::UnexpectedWord(word) if word == "yes"));
+	let e5 = error("a {\n\tb = 1 }\nc 1\n");
+	assert!(matches!(e5.code(), ErrorCode::ExpectedValue('1')));
+	let e6 = error("a = 1\n}\n");
+	assert!(matches!(e6.code(), ErrorCode::ExpectedAsciiAlphabetic('}')));
+
+	// numbers are validated, not just scanned
+	let e8 = error("a = 0x\n");
+	assert!(matches!(e8.code(), ErrorCode::InvalidNum(num) if num == "0x"));
+	assert_eq!(span(e8.span().unwrap()), (4, 6));
+	let e9 = error("$n = 1.2.3\na = $n\n");
+	assert!(matches!(e9.code(), ErrorCode::InvalidNum(num) if num == "1.2.3"));
+
+	// the same as the deserializer
+	let e7 = mayfig::from_str::<HashMap<String, u32>>("a = 1 b = 2\n").unwrap_err();
+	assert_eq!(e7.span(), e1.span());
+}
@@ -0,0 +1,166 @@
+use mayfig::{
+	error::ErrorCode,
+	fmt::{Formatter, QuoteFormat, SeqFormat},
+};
+use serde::de::IgnoredAny;
+
+const F1: &str = r#"
+
+# my config
+input{
+  keyboard   {   # the keyboard
+      repeat-delay=600    # ms
+      repeat-rate =  25
+
+
+
+      xkb-file = '~/.config/keymap/may.xkb'
+  }
+    # touchpad
+  touchpad { tap = true }
+  mouse {
+  }
+}
+size = [24,24]
+offsets = [ 0, -.inf,
+  1.5 ]
+exceptions = [ # apps
+    "com.system76.CosmicFiles"   # files
+
+    'blueman-manager',
+]
+text = """
+    multi "line"
+
+      indented
+    """
+
+bind{
+  "mod+q"='close'
+  mod+t="spawn" [ 'kitty' ]
+  mod+0 = "workspace"   [0]
+}
+windowrules {
+  class   [ "kitty" ]{
+    opacity = 0.6
+  }
+}
+$gaps = 10
+include   "other.mf"   # more
+"#;
+
+const E1: &str = r#"# my config
+input {
+	keyboard { # the keyboard
+		repeat-delay = 600 # ms
+		repeat-rate = 25
+
+		xkb-file = "~/.config/keymap/may.xkb"
+	}
+	# touchpad
+	touchpad {
+		tap = true
+	}
+	mouse {
+	}
+}
+size = [ 24 24 ]
+offsets = [
+	0
+	-.inf
+	1.5
+]
+exceptions = [ # apps
+	"com.system76.CosmicFiles" # files
+
+	"blueman-manager"
+]
+text = """
+	multi "line"
+
+	  indented
+	"""
+
+bind {
+	"mod+q" = "close"
+	mod+t = "spawn" [ "kitty" ]
+	mod+0 = "workspace" [ 0 ]
+}
+windowrules {
+	class [ "kitty" ] {
+		opacity = 0.6
+	}
+}
+$gaps = 10
+include "other.mf" # more
+"#;
+
+#[test]
+fn format() {
+	let f1 = mayfig::fmt::format(F1).unwrap();
+	assert_eq!(f1, E1);
+	assert_eq!(mayfig::fmt::format(&f1).unwrap(), f1);
+
+	mayfig::from_str::<IgnoredAny>(&f1.replace("include \"other.mf\"", "")).unwrap();
+
+	assert_eq!(mayfig::fmt::format("").unwrap(), "");
+	assert_eq!(mayfig::fmt::format("\n\n# a\n\n").unwrap(), "# a\n");
+}
+
+#[test]
+fn options() {
+	let mut fmt = Formatter::with_indent("  ");
+	fmt.set_quote_format(QuoteFormat::Single);
+	fmt.set_seq_format(SeqFormat::Inline);
+
+	let f1 = fmt.format(F1).unwrap();
+	let e1 = E1
+		.replace('\t', "  ")
+		.replace('"', "'")
+		.replace("'''", "\"\"\"")
+		.replace("'line'", "\"line\"")
+		.replace("[\n  0\n  -.inf\n  1.5\n]", "[ 0 -.inf 1.5 ]");
+	assert_eq!(f1, e1);
+	assert_eq!(fmt.format(&f1).unwrap(), f1);
+
+	let mut fmt = Formatter::new();
+	fmt.set_quote_format(QuoteFormat::Preserve);
+	fmt.set_seq_format(SeqFormat::Multiline);
+
+	let f2 = fmt.format("a = [ 'b' \"c\" ]\nd = \"tag\" [ ]\n").unwrap();
+	assert_eq!(f2, "a = [\n\t'b'\n\t\"c\"\n]\nd = \"tag\" [ ]\n");
+	assert_eq!(fmt.format(&f2).unwrap(), f2);
+
+	// quotes are only changed if the string doesn't contain the new quotes
+	let f3 = mayfig::fmt::format("a = 'say \"hi\"'\nb = 'it\\'s'\nc = `raw`\n").unwrap();
+	assert_eq!(f3, "a = 'say \"hi\"'\nb = 'it\\'s'\nc = `raw`\n");
+}
+
+#[test]
+fn max_width() {
+	let mut fmt = Formatter::new();
+	fmt.set_seq_format(SeqFormat::MaxWidth(20));
+
+	let f1 = fmt
+		.format("a {\n\tshort = [ 1 2 3 ]\n\tlong = [ 1 2 3 4 5 6 ]\n}\n")
+		.unwrap();
+	assert_eq!(
+		f1,
+		"a {\n\tshort = [ 1 2 3 ]\n\tlong = [\n\t\t1\n\t\t2\n\t\t3\n\t\t4\n\t\t5\n\t\t6\n\t]\n}\n"
+	);
+	assert_eq!(fmt.format(&f1).unwrap(), f1);
+}
+
+#[test]
+fn tagged() {
+	// the comma keeps the string from tagging the sequence after it
+	let f1 = mayfig::fmt::format("b = [ \"s\", [ ], 't' [ 1 ] ]\n").unwrap();
+	assert_eq!(f1, "b = [ \"s\", [ ] \"t\" [ 1 ] ]\n");
+	assert_eq!(mayfig::fmt::format(&f1).unwrap(), f1);
+}
+
+#[test]
+fn invalid() {
+	let e1 = mayfig::fmt::format("a = [ 1 2\n").unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::Eof));
+}
@@ -0,0 +1,214 @@
+use mayfig::{
+	de::{FsResolver, MemoryResolver},
+	error::{ErrorCode, Span},
+	Deserializer,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Keyboard {
+	repeat_rate: u32,
+	repeat_delay: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+	keyboard: Keyboard,
+	bind: HashMap<String, String>,
+	#[serde(default)]
+	window: HashMap<String, bool>,
+}
+
+const MAIN: &str = r#"
+include "keyboard.mf"
+
+bind {
+	mod+q = "close"
+	include "binds.mf"
+}
+"#;
+
+const KEYBOARD: &str = r#"
+keyboard {
+	repeat-rate = 25
+	repeat-delay = 300
+}
+"#;
+
+const BINDS: &str = r#"# more binds
+mod+t = "kitty"
+include "firefox.mf"
+"#;
+
+fn resolver() -> MemoryResolver {
+	let mut resolver = MemoryResolver::new();
+	resolver.insert("keyboard.mf", KEYBOARD);
+	resolver.insert("binds.mf", BINDS);
+	resolver.insert("firefox.mf", "mod+n = \"firefox\"");
+	resolver
+}
+
+fn include<'a, T: Deserialize<'a>>(
+	input: &'a str,
+	resolver: MemoryResolver,
+) -> Result<T, mayfig::Error> {
+	let mut de = Deserializer::from_str(input);
+	de.set_resolver(resolver);
+	T::deserialize(&mut de)
+}
+
+#[test]
+fn include_memory() {
+	let config = include::<Config>(MAIN, resolver()).unwrap();
+	assert_eq!(
+		config.keyboard,
+		Keyboard {
+			repeat_rate: 25,
+			repeat_delay: 300
+		}
+	);
+	assert_eq!(config.bind.len(), 3);
+	assert_eq!(config.bind["mod+t"], "kitty");
+	assert_eq!(config.bind["mod+n"], "firefox");
+
+	let mut de = Deserializer::from_reader(MAIN.as_bytes());
+	de.set_resolver(resolver());
+	assert_eq!(Config::deserialize(&mut de).unwrap(), config);
+}
+
+#[test]
+fn include_error() {
+	let mut resolver = resolver();
+	resolver.insert("firefox.mf", "\nmod+n = firefox");
+
+	let mut de = Deserializer::from_str(MAIN);
+	de.set_resolver(resolver);
+	let e1 = Config::deserialize(&mut de).unwrap_err();
+
+	let span = e1.span().unwrap();
+	let Span::Point(point) = span else {
+		panic!("expected point, got {span:?}");
+	};
+	assert_eq!(point.line, 2);
+	assert_eq!(point.col, 9);
+	assert_eq!(de.source_name(point.source), Some("firefox.mf"));
+	assert_eq!(de.source_text(point.source), Some("\nmod+n = firefox"));
+	assert_eq!(e1.path().unwrap().to_string(), "bind.\"mod+n\"");
+
+	let e2 = include::<Config>(MAIN, MemoryResolver::new()).unwrap_err();
+	let ErrorCode::Include(include) = e2.code() else {
+		panic!("expected include error, got {e2:?}");
+	};
+	assert_eq!(include.path, "keyboard.mf");
+	assert_eq!(include.error.kind(), std::io::ErrorKind::NotFound);
+	assert_eq!(e2.span().unwrap().range(), 1..22);
+
+	let e3 = mayfig::from_str::<Config>(MAIN).unwrap_err();
+	assert!(matches!(e3.code(), ErrorCode::Include(_)));
+}
+
+#[test]
+fn include_cycle() {
+	let mut resolver = resolver();
+	resolver.insert("firefox.mf", "include \"binds.mf\"");
+
+	let e1 = include::<Config>(MAIN, resolver).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::IncludeCycle(name) if name == "binds.mf"));
+
+	let mut resolver = MemoryResolver::new();
+	resolver.insert("a.mf", "b { include \"a.mf\" }");
+	let e2 = include::<HashMap<String, HashMap<String, String>>>("include \"a.mf\"", resolver);
+	assert!(matches!(e2.unwrap_err().code(), ErrorCode::IncludeCycle(_)));
+}
+
+#[test]
+fn include_cycle_main() {
+	// a cycle back to the main input is found at its first repeat
+	let mut resolver = MemoryResolver::new();
+	resolver.insert("main.mf", "include \"a.mf\"\n");
+	resolver.insert("a.mf", "include \"main.mf\"");
+	resolver.set_main_name("main.mf");
+
+	let mut de = Deserializer::from_str("include \"a.mf\"\n");
+	de.set_resolver(resolver.clone());
+	let e1 = HashMap::<String, String>::deserialize(&mut de).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::IncludeCycle(name) if name == "main.mf"));
+
+	let Some(Span::Span(start, _)) = e1.span() else {
+		panic!("the error should be at the directive");
+	};
+	assert_eq!(de.source_name(start.source), Some("a.mf"));
+
+	let e2 = include::<HashMap<String, String>>("include \"main.mf\"\n", resolver);
+	let e2 = e2.unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::IncludeCycle(_)));
+	assert_eq!(e2.span().unwrap().range(), 0..17);
+}
+
+#[test]
+fn include_unbalanced() {
+	let mut resolver = MemoryResolver::new();
+	resolver.insert("close.mf", "a = \"b\"\n}");
+
+	let e1 = include::<HashMap<String, HashMap<String, String>>>(
+		"bind {\n\tinclude \"close.mf\"\n}",
+		resolver,
+	);
+	assert!(e1.is_err());
+}
+
+#[test]
+fn include_fs() {
+	let dir = std::env::temp_dir().join(format!("mayfig-include-{}", std::process::id()));
+	std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+	std::fs::write(dir.join("conf.d/keyboard.mf"), KEYBOARD).unwrap();
+	std::fs::write(dir.join("conf.d/binds.mf"), "include \"../more.mf\"").unwrap();
+	std::fs::write(dir.join("more.mf"), "mod+t = \"kitty\"").unwrap();
+
+	let input = "include \"conf.d/keyboard.mf\"\nbind {\n\tinclude \"conf.d/binds.mf\"\n}\n";
+	let mut de = Deserializer::from_str(input);
+	de.set_resolver(FsResolver::new(&dir));
+	let config = Config::deserialize(&mut de);
+
+	std::fs::write(dir.join("more.mf"), "include \"conf.d/binds.mf\"").unwrap();
+	let mut de = Deserializer::from_str(input);
+	de.set_resolver(FsResolver::new(&dir));
+	let cycle = Config::deserialize(&mut de);
+
+	// the main input is known to the resolver when it's read from a file
+	std::fs::write(dir.join("main.mf"), "include \"conf.d/../main.mf\"").unwrap();
+	let mut de = Deserializer::from_str("include \"conf.d/../main.mf\"");
+	de.set_resolver(FsResolver::for_file(dir.join("main.mf")));
+	let main_cycle = HashMap::<String, String>::deserialize(&mut de);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+
+	let config = config.unwrap();
+	assert_eq!(config.keyboard.repeat_rate, 25);
+	assert_eq!(config.bind["mod+t"], "kitty");
+	assert!(matches!(
+		cycle.unwrap_err().code(),
+		ErrorCode::IncludeCycle(_)
+	));
+
+	let main_cycle = main_cycle.unwrap_err();
+	assert!(matches!(main_cycle.code(), ErrorCode::IncludeCycle(_)));
+	assert_eq!(main_cycle.span().unwrap().range(), 0..27);
+}
+
+#[test]
+fn include_merge() {
+	let mut resolver = resolver();
+	resolver.insert("binds.mf", "bind {\n\tmod+t = \"kitty\"\n}\n");
+
+	let input = "include \"keyboard.mf\"\ninclude \"binds.mf\"\nbind {\n\tmod+q = \"close\"\n}\n";
+	let mut de = Deserializer::from_str(input);
+	de.set_resolver(resolver);
+	de.set_merge_categories(true);
+
+	let config = Config::deserialize(&mut de).unwrap();
+	assert_eq!(config.bind.len(), 2);
+	assert_eq!(config.bind["mod+q"], "close");
+}
@@ -0,0 +1,157 @@
+use mayfig::lex::{Lexer, TokenKind};
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
+
+const L1: &str = r#"# my config
+input {
+	repeat-rate = 25 # ms
+	tap = TRUE
+	offsets = [ -.inf, 0x1f 1.5e3 ]
+	$mod+q = "close" [ 'now' ]
+	raw = `\n`
+	text = """
+	multi
+	"""
+}
+"#;
+
+fn kinds(text: &str) -> Vec<(TokenKind, &str)> {
+	Lexer::new(text)
+		.filter(|token| token.kind != TokenKind::Whitespace)
+		.map(|token| (token.kind, token.text))
+		.collect()
+}
+
+#[test]
+fn tokens() {
+	let l1 = Lexer::new(L1).map(|token| token.text).collect::<String>();
+	assert_eq!(l1, L1);
+
+	let l1 = kinds(L1);
+	assert_eq!(
+		l1,
+		[
+			(TokenKind::Comment, "# my config"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "input"),
+			(TokenKind::LBrace, "{"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "repeat-rate"),
+			(TokenKind::Equals, "="),
+			(TokenKind::Number, "25"),
+			(TokenKind::Comment, "# ms"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "tap"),
+			(TokenKind::Equals, "="),
+			(TokenKind::Bool, "TRUE"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "offsets"),
+			(TokenKind::Equals, "="),
+			(TokenKind::LBracket, "["),
+			(TokenKind::Number, "-.inf"),
+			(TokenKind::Comma, ","),
+			(TokenKind::Number, "0x1f"),
+			(TokenKind::Number, "1.5e3"),
+			(TokenKind::RBracket, "]"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Variable, "$mod"),
+			(TokenKind::Ident, "+q"),
+			(TokenKind::Equals, "="),
+			(TokenKind::String, "\"close\""),
+			(TokenKind::LBracket, "["),
+			(TokenKind::String, "'now'"),
+			(TokenKind::RBracket, "]"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "raw"),
+			(TokenKind::Equals, "="),
+			(TokenKind::String, "`\\n`"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "text"),
+			(TokenKind::Equals, "="),
+			(TokenKind::String, "\"\"\"\n\tmulti\n\t\"\"\""),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::RBrace, "}"),
+			(TokenKind::Newline, "\n"),
+		]
+	);
+}
+
+#[test]
+fn spans() {
+	let tokens = Lexer::new("a = \"ä\"\nb = 1").collect::<Vec<_>>();
+	for token in &tokens {
+		assert_eq!(&"a = \"ä\"\nb = 1"[token.span.range()], token.text);
+	}
+
+	let mayfig::error::Span::Span(start, end) = tokens[6].span else {
+		panic!("tokens have a start and an end");
+	};
+	assert_eq!(tokens[6].text, "b");
+	assert_eq!((start.line, start.col, start.index), (2, 1, 9));
+	assert_eq!((end.line, end.col, end.index), (2, 2, 10));
+}
+
+#[test]
+fn errors() {
+	// the same rules as the deserializer
+	assert_eq!(kinds("1.2.x"), [(TokenKind::Error, "1.2.x")]);
+	assert_eq!(kinds("1abc"), [(TokenKind::Error, "1abc")]);
+	assert_eq!(kinds("-q"), [(TokenKind::Error, "-q")]);
+	assert_eq!(kinds("a.b"), [(TokenKind::Error, "a.b")]);
+	assert_eq!(kinds("false"), [(TokenKind::Bool, "false")]);
+	assert_eq!(kinds("falsey"), [(TokenKind::Ident, "falsey")]);
+
+	assert_eq!(
+		kinds("a = \"open\nb"),
+		[
+			(TokenKind::Ident, "a"),
+			(TokenKind::Equals, "="),
+			(TokenKind::Error, "\"open"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "b"),
+		]
+	);
+	assert_eq!(kinds("`raw"), [(TokenKind::Error, "`raw")]);
+
+	assert_eq!(kinds("-"), [(TokenKind::Error, "-")]);
+	assert_eq!(kinds("1e999"), [(TokenKind::Error, "1e999")]);
+	assert_eq!(kinds("\"a\u{1}b\""), [(TokenKind::Error, "\"a\u{1}b\"")]);
+	assert_eq!(kinds("`a\u{1}b`"), [(TokenKind::Error, "`a\u{1}b`")]);
+	assert_eq!(
+		kinds("\"\"\"x\"\"\""),
+		[(TokenKind::Error, "\"\"\"x\"\"\"")]
+	);
+	assert_eq!(
+		kinds("\"a\"b = 1\nc"),
+		[
+			(TokenKind::Error, "\"a\"b = 1"),
+			(TokenKind::Newline, "\n"),
+			(TokenKind::Ident, "c"),
+		]
+	);
+	assert_eq!(
+		kinds("\"a\"[ 1 ]"),
+		[
+			(TokenKind::String, "\"a\""),
+			(TokenKind::LBracket, "["),
+			(TokenKind::Number, "1"),
+			(TokenKind::RBracket, "]"),
+		]
+	);
+
+	// every error is an error in the deserializer too
+	for text in [
+		"-",
+		"1e999",
+		"\"a\u{1}b\"",
+		"`a\u{1}b`",
+		"\"\"\"x\"\"\"",
+		"\"a\"b",
+	] {
+		let text = format!("a = {text}\n");
+		assert!(
+			mayfig::from_str::<HashMap<String, IgnoredAny>>(&text).is_err(),
+			"{text:?}"
+		);
+	}
+}
@@ -0,0 +1,272 @@
+use serde_json::{json, Value};
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+const URI: &str = "file:///config/mayland.mf";
+
+const L1: &str = r#"input {
+	keyboard {
+		repeat-rate = 25
+	}
+	touchpad { tap = true }
+}
+size = [
+	24
+	24
+]
+windowrules {
+	class [ "kitty" ] {
+		opacity = 0.6
+	}
+}
+"#;
+
+struct Client {
+	child: Child,
+	stdin: ChildStdin,
+	stdout: BufReader<ChildStdout>,
+	id: u64,
+}
+
+impl Client {
+	fn start() -> Self {
+		let mut child = Command::new(env!("CARGO_BIN_EXE_mayfig-lsp"))
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.spawn()
+			.unwrap();
+
+		let stdin = child.stdin.take().unwrap();
+		let stdout = BufReader::new(child.stdout.take().unwrap());
+		let mut client = Client {
+			child,
+			stdin,
+			stdout,
+			id: 0,
+		};
+
+		let capabilities = client.request("initialize", json!({ "capabilities": {} }));
+		assert_eq!(
+			capabilities["capabilities"]["documentFormattingProvider"],
+			true
+		);
+		client.notify("initialized", json!({}));
+
+		client
+	}
+
+	fn send(&mut self, message: Value) {
+		let message = message.to_string();
+		write!(
+			self.stdin,
+			"Content-Length: {}\r\n\r\n{message}",
+			message.len()
+		)
+		.unwrap();
+		self.stdin.flush().unwrap();
+	}
+
+	fn recv(&mut self) -> Value {
+		let mut len = 0;
+		loop {
+			let mut line = String::new();
+			self.stdout.read_line(&mut line).unwrap();
+			let line = line.trim_end();
+			if line.is_empty() {
+				break;
+			} else if let Some(value) = line.strip_prefix("Content-Length: ") {
+				len = value.parse().unwrap();
+			}
+		}
+
+		let mut content = vec![0; len];
+		self.stdout.read_exact(&mut content).unwrap();
+		serde_json::from_slice(&content).unwrap()
+	}
+
+	fn request(&mut self, method: &str, params: Value) -> Value {
+		self.id += 1;
+		let id = self.id;
+		self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+
+		let response = self.recv();
+		assert_eq!(response["id"], id);
+		response["result"].clone()
+	}
+
+	fn notify(&mut self, method: &str, params: Value) {
+		self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+	}
+
+	/// open or change the document and return the published diagnostics
+	fn open(&mut self, text: &str) -> Value {
+		self.notify(
+			"textDocument/didOpen",
+			json!({ "textDocument": { "uri": URI, "languageId": "mayfig", "version": 0, "text": text } }),
+		);
+
+		let diagnostics = self.recv();
+		assert_eq!(diagnostics["method"], "textDocument/publishDiagnostics");
+		assert_eq!(diagnostics["params"]["uri"], URI);
+		diagnostics["params"]["diagnostics"].clone()
+	}
+
+	fn shutdown(mut self) {
+		self.request("shutdown", Value::Null);
+		self.notify("exit", Value::Null);
+		assert!(self.child.wait().unwrap().success());
+	}
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+	json!({
+		"start": { "line": start.0, "character": start.1 },
+		"end": { "line": end.0, "character": end.1 },
+	})
+}
+
+#[test]
+fn diagnostics() {
+	let mut client = Client::start();
+
+	let d1 = client.open(L1);
+	assert_eq!(d1, json!([]));
+
+	// columns are counted in utf16 code units
+	let d2 = client.open("a = \"ä\"\nemoji = \"🦀🦀\" x\n");
+	assert_eq!(d2.as_array().unwrap().len(), 1);
+	assert_eq!(d2[0]["range"], range((1, 15), (1, 16)));
+	assert_eq!(d2[0]["message"], "expected newline, found 'x' first");
+	assert_eq!(d2[0]["severity"], 1);
+
+	client.notify(
+		"textDocument/didChange",
+		json!({
+			"textDocument": { "uri": URI, "version": 1 },
+			"contentChanges": [ { "text": "a = \nb = ]\n" } ],
+		}),
+	);
+	let d3 = client.recv();
+	assert_eq!(d3["params"]["version"], 1);
+	let d3 = d3["params"]["diagnostics"].as_array().unwrap();
+	assert_eq!(d3.len(), 2);
+
+	// errors of the deserializer
+	let d4 = client.open("a = 1\ninclude \"other.mf\"\n");
+	assert_eq!(d4.as_array().unwrap().len(), 1);
+	assert_eq!(d4[0]["range"], range((1, 0), (1, 18)));
+	assert!(d4[0]["message"].as_str().unwrap().contains("other.mf"));
+
+	// every error of the deserializer is published
+	let d5 = client.open("a = $x\n\"🦀\" = $y\n");
+	assert_eq!(d5.as_array().unwrap().len(), 2);
+	assert_eq!(d5[0]["range"], range((0, 4), (0, 6)));
+	assert_eq!(d5[1]["range"], range((1, 7), (1, 9)));
+	assert_eq!(d5[1]["message"], "undefined variable `$y`\nin `\"🦀\"`");
+
+	client.notify(
+		"textDocument/didClose",
+		json!({ "textDocument": { "uri": URI } }),
+	);
+	let d6 = client.recv();
+	assert_eq!(d6["params"]["diagnostics"], json!([]));
+
+	client.shutdown();
+}
+
+#[test]
+fn symbols() {
+	let mut client = Client::start();
+	client.open(L1);
+
+	let s1 = client.request(
+		"textDocument/documentSymbol",
+		json!({ "textDocument": { "uri": URI } }),
+	);
+	let s1 = s1.as_array().unwrap();
+	assert_eq!(s1.len(), 2);
+
+	assert_eq!(s1[0]["name"], "input");
+	assert_eq!(s1[0]["range"], range((0, 0), (5, 1)));
+	assert_eq!(s1[0]["selectionRange"], range((0, 0), (0, 5)));
+	let children = s1[0]["children"].as_array().unwrap();
+	let names = children.iter().map(|s| &s["name"]).collect::<Vec<_>>();
+	assert_eq!(names, ["keyboard", "touchpad"]);
+	assert_eq!(children[0]["children"], json!([]));
+
+	assert_eq!(s1[1]["name"], "windowrules");
+	let children = s1[1]["children"].as_array().unwrap();
+	assert_eq!(children.len(), 1);
+	assert_eq!(children[0]["name"], "class");
+	assert_eq!(children[0]["selectionRange"], range((11, 1), (11, 18)));
+
+	client.shutdown();
+}
+
+#[test]
+fn folding() {
+	let mut client = Client::start();
+	client.open(L1);
+
+	let f1 = client.request(
+		"textDocument/foldingRange",
+		json!({ "textDocument": { "uri": URI } }),
+	);
+	let f1 = f1
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|range| {
+			(
+				range["startLine"].as_u64().unwrap(),
+				range["endLine"].as_u64().unwrap(),
+			)
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(f1, [(0, 4), (1, 2), (6, 8), (10, 13), (11, 12)]);
+
+	client.shutdown();
+}
+
+#[test]
+fn formatting() {
+	let mut client = Client::start();
+	client.open("a{b=1}\nc = [1,2]");
+
+	let f1 = client.request(
+		"textDocument/formatting",
+		json!({
+			"textDocument": { "uri": URI },
+			"options": { "tabSize": 2, "insertSpaces": true },
+		}),
+	);
+	assert_eq!(
+		f1,
+		json!([{ "range": range((0, 0), (1, 9)), "newText": "a {\n  b = 1\n}\nc = [ 1 2 ]\n" }])
+	);
+
+	client.open("a {\n\tb = 1\n}\n");
+	let f2 = client.request(
+		"textDocument/formatting",
+		json!({
+			"textDocument": { "uri": URI },
+			"options": { "tabSize": 4, "insertSpaces": false },
+		}),
+	);
+	assert_eq!(f2, json!([]));
+
+	// documents with syntax errors aren't formatted
+	client.open("a = [\n");
+	let f3 = client.request(
+		"textDocument/formatting",
+		json!({
+			"textDocument": { "uri": URI },
+			"options": { "tabSize": 4, "insertSpaces": false },
+		}),
+	);
+	assert_eq!(f3, Value::Null);
+
+	client.shutdown();
+}
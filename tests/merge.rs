@@ -0,0 +1,126 @@
+use mayfig::{
+	de::DuplicateKeys,
+	error::{ErrorCode, Segment},
+	Deserializer,
+};
+use serde::{de::IgnoredAny, Deserialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Keyboard {
+	repeat_rate: u32,
+	repeat_delay: u32,
+	layout: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Input {
+	keyboard: Keyboard,
+	#[serde(default)]
+	touchpad: HashMap<String, bool>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+	input: Input,
+	bind: HashMap<String, String>,
+}
+
+const M1: &str = r#"
+bind {
+	mod+q = "quit"
+}
+
+input {
+	keyboard {
+		repeat-rate = 25
+	}
+}
+
+bind {
+	mod+t = "kitty"
+	mod+n = "firefox"
+}
+
+input {
+	touchpad {
+		tap = true
+	}
+	keyboard {
+		repeat-delay = 300
+	}
+}
+"#;
+
+const M2: &str = r#"
+bind {
+	mod+q = "quit"
+}
+bind {
+	mod+q = "close"
+}
+"#;
+
+fn merged<'a, T: Deserialize<'a>>(
+	input: &'a str,
+	policy: DuplicateKeys,
+) -> Result<T, mayfig::Error> {
+	let mut de = Deserializer::from_str(input);
+	de.set_merge_categories(true);
+	de.set_duplicate_keys(policy);
+	T::deserialize(&mut de)
+}
+
+#[test]
+fn merge() {
+	let m1 = merged::<Config>(M1, DuplicateKeys::Error).unwrap();
+	assert_eq!(
+		m1.input.keyboard,
+		Keyboard {
+			repeat_rate: 25,
+			repeat_delay: 300,
+			layout: None,
+		}
+	);
+	assert!(m1.input.touchpad["tap"]);
+	assert_eq!(m1.bind.len(), 3);
+	assert_eq!(m1.bind["mod+n"], "firefox");
+
+	let mut de = Deserializer::from_reader(M1.as_bytes());
+	de.set_merge_categories(true);
+	let r1 = Config::deserialize(&mut de).unwrap();
+	assert_eq!(r1, m1);
+
+	let m1 = merged::<HashMap<String, HashMap<String, IgnoredAny>>>(M1, DuplicateKeys::Error);
+	assert_eq!(m1.unwrap()["input"].len(), 2);
+}
+
+#[test]
+fn no_merge() {
+	let mut de = Deserializer::from_str(M1);
+	de.set_duplicate_keys(DuplicateKeys::Error);
+	let e1 = HashMap::<String, HashMap<String, IgnoredAny>>::deserialize(&mut de).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::DuplicateKey(_)));
+	assert_eq!(e1.span().unwrap().range(), 72..76);
+}
+
+#[test]
+fn merge_duplicate() {
+	let e2 = merged::<Config>(M2, DuplicateKeys::Error).unwrap_err();
+	let ErrorCode::DuplicateKey(duplicate) = e2.code() else {
+		panic!("expected duplicate key, got {e2:?}");
+	};
+	assert_eq!(duplicate.first.range(), 9..14);
+	assert_eq!(duplicate.second.range(), 34..39);
+	assert_eq!(
+		e2.path().unwrap().segments(),
+		&[Segment::Key("bind".to_owned())]
+	);
+
+	let m2 = merged::<HashMap<String, HashMap<String, String>>>(M2, DuplicateKeys::LastWins);
+	assert_eq!(m2.unwrap()["bind"]["mod+q"], "close");
+
+	let m2 = merged::<HashMap<String, HashMap<String, String>>>(M2, DuplicateKeys::FirstWins);
+	assert_eq!(m2.unwrap()["bind"]["mod+q"], "quit");
+}
@@ -39,23 +39,9 @@ two =
 fn is_not_newline() {
 	let e1 = mayfig::from_str::<Tst>(NO1).unwrap_err();
 	assert!(matches!(e1.code(), ErrorCode::ExpectedNewline('t')));
-	assert_eq!(
-		e1.span(),
-		Some(Span::Point(Position {
-			line: 2,
-			col: 10,
-			index: 10
-		}))
-	);
+	assert_eq!(e1.span(), Some(Span::Point(Position::new(2, 10, 10))));
 
 	let e2 = mayfig::from_str::<Tst>(NO2).unwrap_err();
 	assert!(matches!(e2.code(), ErrorCode::UnexpectedNewline));
-	assert_eq!(
-		e2.span(),
-		Some(Span::Point(Position {
-			line: 3,
-			col: 6,
-			index: 14
-		}))
-	);
+	assert_eq!(e2.span(), Some(Span::Point(Position::new(3, 6, 14))));
 }
@@ -0,0 +1,126 @@
+use mayfig::error::{ErrorCode, Segment};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[expect(dead_code)]
+struct Keyboard {
+	repeat_rate: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[expect(dead_code)]
+struct Input {
+	keyboard: Keyboard,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[expect(dead_code)]
+enum Action {
+	Workspace(u32),
+	Spawn(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[expect(dead_code)]
+struct Config {
+	input: Input,
+	#[serde(default)]
+	bind: HashMap<String, Action>,
+	#[serde(default)]
+	outputs: Vec<HashMap<String, u32>>,
+}
+
+const P1: &str = r#"
+input {
+	keyboard {
+		repeat-rate = "fast"
+	}
+}
+"#;
+
+const P2: &str = r#"
+input { keyboard { repeat-rate = 1 } }
+bind {
+	"mod+t" = "workspace" [ "one" ]
+}
+"#;
+
+const P3: &str = r#"
+input { keyboard { repeat-rate = 1 } }
+outputs = [ { scale = 1 } { scale = "x" } ]
+"#;
+
+const P4: &str = r#"
+input {
+	keyboard {}
+}
+"#;
+
+const P5: &str = r#"
+input { keyboard { repeat-rate = 1 } }
+bind {
+	mod+t = "spawn" [ "kitty" [ 2 ] ]
+}
+"#;
+
+const P6: &str = r#"
+input = [ 1 ]
+"#;
+
+#[test]
+fn path() {
+	let e1 = mayfig::from_str::<Config>(P1).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::ExpectedNumeric('"')));
+	let p1 = e1.path().unwrap();
+	assert_eq!(
+		p1.segments(),
+		&[
+			Segment::Key("input".to_owned()),
+			Segment::Key("keyboard".to_owned()),
+			Segment::Key("repeat-rate".to_owned())
+		]
+	);
+	assert_eq!(p1.to_string(), "input.keyboard.repeat-rate");
+	assert_eq!(
+		e1.to_string(),
+		"expected ascii numeric, got '\"' at input.keyboard.repeat-rate, line 4, column 17"
+	);
+
+	let e2 = mayfig::from_str::<Config>(P2).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::ExpectedNumeric('"')));
+	assert_eq!(e2.path().unwrap().to_string(), r#"bind."mod+t"[0]"#);
+
+	let e3 = mayfig::from_str::<Config>(P3).unwrap_err();
+	let p3 = e3.path().unwrap();
+	assert_eq!(p3.segments()[1], Segment::Index(1));
+	assert_eq!(p3.to_string(), "outputs[1].scale");
+
+	let e4 = mayfig::from_str::<Config>(P4).unwrap_err();
+	assert!(matches!(e4.code(), ErrorCode::Custom(_)));
+	assert_eq!(e4.path().unwrap().to_string(), "input.keyboard");
+
+	let e5 = mayfig::from_str::<Config>(P5).unwrap_err();
+	assert_eq!(e5.path().unwrap().to_string(), r#"bind."mod+t"[1]"#);
+
+	let e6 = mayfig::from_str::<Config>(P6).unwrap_err();
+	assert_eq!(e6.path().unwrap().to_string(), "input");
+
+	let r1 = mayfig::from_reader::<_, Config>(P1.as_bytes()).unwrap_err();
+	assert_eq!(r1.path(), e1.path());
+}
+
+#[test]
+fn no_path() {
+	let e1 = mayfig::from_str::<Config>("input {").unwrap_err();
+	assert!(e1.path().is_some());
+
+	let e2 = mayfig::from_str::<Config>("").unwrap_err();
+	assert!(e2.path().is_none());
+	assert_eq!(
+		e2.to_string(),
+		"missing field `input` at line 1, columns 1 to 1"
+	);
+}
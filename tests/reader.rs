@@ -0,0 +1,73 @@
+use mayfig::error::{ErrorCode, Position, Span};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct R {
+	s: String,
+	n: i32,
+	f: f64,
+	m: HashMap<String, Vec<String>>,
+}
+
+const R1: &str = r#"
+s = "t\"ä\"st"
+n = -20
+f = .inf
+m {
+	"mod t" = [ "kitty" "-e" ]
+}
+"#;
+
+const R2: &str = r#"
+s = "test"
+n = 2a
+"#;
+
+#[test]
+fn reader() {
+	let r1 = mayfig::from_reader::<_, R>(R1.as_bytes()).unwrap();
+	assert_eq!(r1, mayfig::from_str::<R>(R1).unwrap());
+	assert_eq!(r1.s, "t\"ä\"st");
+	assert_eq!(r1.n, -20);
+	assert_eq!(r1.f, f64::INFINITY);
+	assert_eq!(r1.m["mod t"], ["kitty", "-e"]);
+
+	let e2 = mayfig::from_reader::<_, R>(R2.as_bytes()).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::ExpectedNumeric('a')));
+	assert_eq!(e2.span(), Some(Span::Point(Position::new(3, 6, 17))));
+}
+
+const I1: &[u8] = b"
+s = \"t\xc3\xa4st\"
+n = \xc3\x28
+";
+
+#[test]
+fn invalid_utf8() {
+	let e1 = mayfig::from_reader::<_, R>(I1).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::InvalidUtf8));
+	assert_eq!(e1.span(), Some(Span::Point(Position::new(3, 5, 17))));
+}
+
+struct Failing<'a>(&'a [u8]);
+
+impl std::io::Read for Failing<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		if self.0.is_empty() {
+			Err(std::io::Error::other("connection reset"))
+		} else {
+			let len = buf.len().min(self.0.len());
+			buf[..len].copy_from_slice(&self.0[..len]);
+			self.0 = &self.0[len..];
+			Ok(len)
+		}
+	}
+}
+
+#[test]
+fn io_error() {
+	let e1 = mayfig::from_reader::<_, R>(Failing(b"\ns = \"test\"\nn = 2")).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::Io(_)));
+	assert_eq!(e1.span(), Some(Span::Point(Position::new(3, 6, 17))));
+}
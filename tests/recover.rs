@@ -0,0 +1,227 @@
+use mayfig::{
+	de::{MemoryResolver, SystemEnvironment},
+	error::ErrorCode,
+	Deserializer,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Keyboard {
+	repeat_rate: u32,
+	#[serde(default)]
+	layout: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Input {
+	keyboard: Keyboard,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Cursor {
+	size: u32,
+	theme: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+	input: Input,
+	cursor: Cursor,
+	#[serde(default)]
+	bind: HashMap<String, String>,
+}
+
+const R1: &str = r#"
+input {
+	keyboard {
+		repeat-rate = "fast"
+		layout = 1
+	}
+}
+
+cursor {
+	size = 24 24
+	theme = "Adwaita"
+}
+
+bind {
+	mod+q = "close"
+	mod+t = "kitty
+	mod+f = [ "firefox" ]
+}
+"#;
+
+const R2: &str = r#"
+input { keyboard { repeat-rate = 25 } }
+cursor {
+	size = 24
+	theme = "Adwaita"
+}
+"#;
+
+const R3: &str = r#"
+input { keyboard { repeat-rate = 25 layout = "us" } }
+cursor {
+	size = "24"
+	theme = """
+		adwaita }
+		"""
+}
+"#;
+
+#[test]
+fn recover() {
+	let e1 = mayfig::from_str_recover::<Config>(R1).unwrap_err();
+	assert_eq!(e1.len(), 5);
+
+	let paths = e1
+		.iter()
+		.map(|err| err.path().unwrap().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		paths,
+		[
+			"input.keyboard.repeat-rate",
+			"input.keyboard.layout",
+			"cursor.size",
+			r#"bind."mod+t""#,
+			r#"bind."mod+f""#,
+		]
+	);
+
+	assert!(matches!(e1[0].code(), ErrorCode::ExpectedNumeric('"')));
+	assert!(matches!(e1[1].code(), ErrorCode::ExpectedQuote('1')));
+	assert!(matches!(e1[2].code(), ErrorCode::ExpectedNewline('2')));
+	assert!(matches!(e1[3].code(), ErrorCode::UnescapedControl('\n')));
+	assert!(matches!(e1[4].code(), ErrorCode::ExpectedQuote('[')));
+
+	let e2 = mayfig::from_str::<Config>(R1).unwrap_err();
+	assert_eq!(e2.to_string(), e1[0].to_string());
+}
+
+#[test]
+fn recover_ok() {
+	let r2 = mayfig::from_str_recover::<Config>(R2).unwrap();
+	assert_eq!(r2, mayfig::from_str::<Config>(R2).unwrap());
+}
+
+#[test]
+fn recover_nested() {
+	let e3 = mayfig::from_str_recover::<Config>(R3).unwrap_err();
+	assert_eq!(e3.len(), 2);
+	assert!(matches!(e3[0].code(), ErrorCode::ExpectedNewline('l')));
+	assert_eq!(
+		e3[0].path().unwrap().to_string(),
+		"input.keyboard.repeat-rate"
+	);
+	assert!(matches!(e3[1].code(), ErrorCode::ExpectedNumeric('"')));
+	assert_eq!(e3[1].path().unwrap().to_string(), "cursor.size");
+}
+
+#[test]
+fn recover_after_value() {
+	// only the entry is skipped, so the rest of its map is still checked
+	let text = "cursor {\n\tsize = 24 24\n\ttheme = 1\n}\n";
+	let e4 = mayfig::from_str_recover::<HashMap<String, Cursor>>(text).unwrap_err();
+	assert_eq!(e4.len(), 2);
+	assert!(matches!(e4[0].code(), ErrorCode::ExpectedNewline('2')));
+	assert_eq!(e4[0].path().unwrap().to_string(), "cursor.size");
+	assert!(matches!(e4[1].code(), ErrorCode::ExpectedQuote('1')));
+	assert_eq!(e4[1].path().unwrap().to_string(), "cursor.theme");
+}
+
+#[test]
+fn recover_deserializer() {
+	let mut resolver = MemoryResolver::new();
+	resolver.insert("cursor.mf", "size = \"24\"\ntheme = 1\n");
+
+	let text = "input { keyboard { repeat-rate = x } }\ncursor {\n\tinclude \"cursor.mf\"\n}\n";
+	let mut de = Deserializer::from_reader(text.as_bytes());
+	de.set_resolver(resolver);
+	de.set_environment(SystemEnvironment);
+	let e5 = de.deserialize_recover::<Config>().unwrap_err();
+
+	let paths = e5
+		.iter()
+		.map(|err| err.path().unwrap().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		paths,
+		["input.keyboard.repeat-rate", "cursor.size", "cursor.theme"]
+	);
+
+	let Some(mayfig::error::Span::Point(pos)) = e5[1].span() else {
+		panic!("the error should be at a point");
+	};
+	assert_eq!(de.source_name(pos.source), Some("cursor.mf"));
+
+	let mut de = Deserializer::from_str(R2);
+	de.set_resolver(MemoryResolver::new());
+	assert_eq!(
+		de.deserialize_recover::<Config>().unwrap(),
+		mayfig::from_str(R2).unwrap()
+	);
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Output {
+	name: String,
+	scale: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Outputs {
+	outputs: Vec<Output>,
+	#[serde(default)]
+	scales: Vec<u32>,
+	size: u32,
+}
+
+const R4: &str = r#"
+outputs = [
+	{
+		name = 1
+		scale = "x"
+	}
+	{
+		name = "b"
+		scale = 2
+	}
+]
+scales = [
+	1
+	"two"
+	3
+]
+size = "24"
+"#;
+
+#[test]
+fn recover_seq() {
+	// errors in maps inside of a sequence resume it, an element that fails skips the rest of it
+	let e6 = mayfig::from_str_recover::<Outputs>(R4).unwrap_err();
+
+	let paths = e6
+		.iter()
+		.map(|err| err.path().unwrap().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		paths,
+		["outputs[0].name", "outputs[0].scale", "scales[1]", "size"]
+	);
+}
+
+#[test]
+fn recover_key() {
+	// the key of a resumed entry is deserialized again
+	let text = "1 {\n\tsize = x\n\ttheme = 2\n}\n";
+	let e7 = mayfig::from_str_recover::<HashMap<u32, Cursor>>(text).unwrap_err();
+
+	let paths = e7
+		.iter()
+		.map(|err| err.path().unwrap().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(paths, [r#""1".size"#, r#""1".theme"#]);
+}
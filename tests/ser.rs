@@ -192,6 +192,44 @@ fn more() {
 	assert_eq!(ser3, DE_SER_3);
 }
 
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Id {
+	Signed(i128),
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+struct DeSer4 {
+	id: u128,
+	neg: i128,
+	tagged: Id,
+	map: IndexMap<u128, i128>,
+}
+
+const DE_SER_4: &str = r#"id = 340282366920938463463374607431768211455
+neg = -170141183460469231731687303715884105728
+tagged = "signed" [ -18446744073709551616 ]
+map {
+	18446744073709551616 = 170141183460469231731687303715884105727
+}
+"#;
+
+#[test]
+fn int128() {
+	let ref4 = DeSer4 {
+		id: u128::MAX,
+		neg: i128::MIN,
+		tagged: Id::Signed(-(1 << 64)),
+		map: IndexMap::from([(1 << 64, i128::MAX)]),
+	};
+
+	let de4 = mayfig::from_str::<DeSer4>(DE_SER_4).unwrap();
+	assert_eq!(de4, ref4);
+
+	let ser4 = mayfig::to_string(&de4).unwrap();
+	assert_eq!(ser4, DE_SER_4);
+}
+
 #[cfg(feature = "value")]
 const R1: &str = r#"val = "test"
 test = 20
@@ -229,4 +267,13 @@ fn value() {
 
 	let ser = mayfig::to_string(&val).unwrap();
 	assert_eq!(ser, R1);
+
+	let val4 = mayfig::from_str::<mayfig::Value>(DE_SER_4).unwrap();
+	let map4 = val4.as_map().unwrap();
+	assert_eq!(map4[&Value::from("id")], Value::from(u128::MAX));
+	let neg = map4[&Value::from("neg")].as_number().unwrap();
+	assert_eq!(neg.as_i128(), Some(i128::MIN));
+
+	let ser4 = mayfig::to_string(&val4).unwrap();
+	assert_eq!(ser4, DE_SER_4);
 }
@@ -0,0 +1,39 @@
+use mayfig::error::{ErrorCode, Position, Span};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct S<'a> {
+	#[serde(borrow)]
+	s: &'a str,
+	n: u32,
+}
+
+const S1: &[u8] = br#"
+s = "test"
+n = 20
+"#;
+
+const S2: &[u8] = b"
+s = \"t\xffst\"
+n = 20
+";
+
+const S3: &[u8] = b"
+s = \"t\xc3\xa4st\"
+n = 20
+\xc3";
+
+#[test]
+fn slice() {
+	let s1 = mayfig::from_slice::<S>(S1).unwrap();
+	assert_eq!(s1.s, "test");
+	assert_eq!(s1.n, 20);
+
+	let e2 = mayfig::from_slice::<S>(S2).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::InvalidUtf8));
+	assert_eq!(e2.span(), Some(Span::Point(Position::new(2, 7, 7))));
+
+	let e3 = mayfig::from_slice::<S>(S3).unwrap_err();
+	assert!(matches!(e3.code(), ErrorCode::InvalidUtf8));
+	assert_eq!(e3.span(), Some(Span::Point(Position::new(4, 1, 20))));
+}
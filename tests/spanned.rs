@@ -0,0 +1,98 @@
+use mayfig::{
+	error::{Position, Span},
+	Spanned,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+	Close,
+	Spawn(Spanned<String>),
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+enum Match {
+	Class(Spanned<String>),
+}
+
+#[derive(Debug, Deserialize)]
+struct Cursor {
+	size: Spanned<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Config {
+	cursor: Spanned<Cursor>,
+	seq: Vec<Spanned<i32>>,
+	bind: HashMap<Spanned<String>, Spanned<Action>>,
+	window: HashMap<Match, bool>,
+}
+
+const S1: &str = r#"
+cursor {
+	size = 24
+}
+seq = [ 1 -20 ]
+
+bind {
+	mod+q = "close"
+	"mod t" = "spawn" [ "kitty" ]
+}
+
+window {
+	class [ "firefox" ] = true
+}
+"#;
+
+fn span(start: (usize, usize, usize), end: (usize, usize, usize)) -> Span {
+	Span::Span(
+		Position::new(start.0, start.1, start.2),
+		Position::new(end.0, end.1, end.2),
+	)
+}
+
+#[test]
+fn spanned() {
+	let s1 = mayfig::from_str::<Config>(S1).unwrap();
+
+	assert_eq!(s1.cursor.span(), span((2, 8, 8), (4, 2, 22)));
+	assert_eq!(
+		s1.cursor.get_ref().size.span(),
+		span((3, 9, 18), (3, 11, 20))
+	);
+	assert_eq!(*s1.cursor.get_ref().size.get_ref(), 24);
+
+	assert_eq!(s1.seq[0].span(), span((5, 9, 31), (5, 10, 32)));
+	assert_eq!(s1.seq[1].span(), span((5, 11, 33), (5, 14, 36)));
+
+	let (q, close) = s1.bind.get_key_value("mod+q").unwrap();
+	assert_eq!(q.span(), span((8, 2, 48), (8, 7, 53)));
+	assert_eq!(close.span(), span((8, 10, 56), (8, 17, 63)));
+	assert_eq!(*close.get_ref(), Action::Close);
+
+	let (t, spawn) = s1.bind.get_key_value("mod t").unwrap();
+	assert_eq!(t.span(), span((9, 2, 65), (9, 9, 72)));
+	assert_eq!(spawn.span(), span((9, 12, 75), (9, 31, 94)));
+	let Action::Spawn(kitty) = spawn.get_ref() else {
+		panic!("expected spawn action");
+	};
+	assert_eq!(kitty.get_ref(), "kitty");
+	assert_eq!(kitty.span(), span((9, 22, 85), (9, 29, 92)));
+
+	let (Match::Class(firefox), _) = s1.window.iter().next().unwrap();
+	assert_eq!(firefox.get_ref(), "firefox");
+	assert_eq!(firefox.span(), span((13, 10, 116), (13, 19, 125)));
+}
+
+#[test]
+fn top_level() {
+	let s1 = mayfig::from_str::<Spanned<Config>>(S1).unwrap();
+	assert_eq!(s1.span(), span((2, 1, 1), (15, 1, 137)));
+
+	let r1 = mayfig::from_reader::<_, Spanned<Config>>(S1.as_bytes()).unwrap();
+	assert_eq!(r1.span(), s1.span());
+	assert_eq!(r1.get_ref().seq, s1.get_ref().seq);
+}
@@ -99,18 +99,7 @@ fn custom_spans() {
 	assert!(matches!(e1.code(), ErrorCode::Custom(_)));
 	assert_eq!(
 		e1.span(),
-		Some(Span::Span(
-			Position {
-				line: 2,
-				col: 5,
-				index: 5,
-			},
-			Position {
-				line: 2,
-				col: 7,
-				index: 7
-			}
-		))
+		Some(Span::Span(Position::new(2, 5, 5), Position::new(2, 7, 7)))
 	);
 
 	let _ = mayfig::from_str::<N>(N2).unwrap();
@@ -120,17 +109,6 @@ fn custom_spans() {
 	assert!(matches!(e2.code(), ErrorCode::Custom(_)));
 	assert_eq!(
 		e2.span(),
-		Some(Span::Span(
-			Position {
-				line: 2,
-				col: 5,
-				index: 5
-			},
-			Position {
-				line: 2,
-				col: 13,
-				index: 13
-			}
-		))
+		Some(Span::Span(Position::new(2, 5, 5), Position::new(2, 13, 13)))
 	);
 }
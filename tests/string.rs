@@ -70,25 +70,11 @@ w = [ "one" "two" "three" ]
 fn delim() {
 	let e1 = mayfig::from_str::<V>(V1).unwrap_err();
 	assert!(matches!(e1.code(), ErrorCode::ExpectedDelimiter('2')));
-	assert_eq!(
-		e1.span(),
-		Some(Span::Point(Position {
-			line: 2,
-			col: 13,
-			index: 13
-		}))
-	);
+	assert_eq!(e1.span(), Some(Span::Point(Position::new(2, 13, 13))));
 
 	let e2 = mayfig::from_str::<W>(W1).unwrap_err();
 	assert!(matches!(e2.code(), ErrorCode::ExpectedDelimiter('"')));
-	assert_eq!(
-		e2.span(),
-		Some(Span::Point(Position {
-			line: 2,
-			col: 12,
-			index: 12
-		}))
-	);
+	assert_eq!(e2.span(), Some(Span::Point(Position::new(2, 12, 12))));
 
 	let v2 = mayfig::from_str::<V>(V2).unwrap();
 	assert_eq!(v2.t, ("test", 20, "test"));
@@ -102,3 +88,185 @@ fn delim() {
 	let w3 = mayfig::from_str::<W>(W3).unwrap();
 	assert_eq!(&w3.w, &["one", "two", "three"]);
 }
+
+const U1: &str = r#"
+t = "ä\u{1F600}😀"
+"#;
+
+const U2: &str = r#"
+t = "\u{110000}"
+"#;
+
+const U3: &str = r#"
+t = "\ud83d"
+"#;
+
+const U4: &str = r#"
+t = "\u00g4"
+"#;
+
+#[test]
+fn unicode_escape() {
+	let u1 = mayfig::from_str::<T>(U1).unwrap();
+	assert_eq!(u1.t, "ä😀😀");
+
+	let e2 = mayfig::from_str::<T>(U2).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::InvalidUnicodeEscape(e) if e == r"\u{110000}"));
+	assert_eq!(
+		e2.span(),
+		Some(Span::Span(Position::new(2, 6, 6), Position::new(2, 16, 16)))
+	);
+
+	let e3 = mayfig::from_str::<T>(U3).unwrap_err();
+	assert!(matches!(e3.code(), ErrorCode::InvalidUnicodeEscape(e) if e == r#"\ud83d""#));
+
+	let e4 = mayfig::from_str::<T>(U4).unwrap_err();
+	assert!(matches!(e4.code(), ErrorCode::InvalidUnicodeEscape(e) if e == r"\u00g"));
+}
+
+#[derive(Debug, serde::Serialize, Deserialize, PartialEq)]
+struct E {
+	e: String,
+}
+
+#[test]
+fn escape_roundtrip() {
+	let e = E {
+		e: "\"\\\u{0}\u{1b}\n\r\t\u{8}\u{c}\u{7f}\u{85}ä".to_owned(),
+	};
+
+	let ser = mayfig::to_string(&e).unwrap();
+	assert_eq!(
+		ser,
+		"e = \"\\\"\\\\\\u{0}\\u{1b}\\n\\r\\t\\b\\f\\u{7f}\\u{85}ä\"\n"
+	);
+
+	let de = mayfig::from_str::<E>(&ser).unwrap();
+	assert_eq!(de, e);
+}
+
+const M1: &str = r#"
+t = """
+	#!/bin/sh
+	echo "it's "" a \"""
+	
+		indented\t\u00e4
+	"""
+"#;
+
+const M2: &str = r#"
+bind {
+	mod+t = "spawn" [ '''
+		sh -c 'echo """'
+		''' ]
+}
+"#;
+
+const M3: &str = r#"
+t = """ text
+	"""
+"#;
+
+const M4: &str = r#"
+t = """
+	text
+  less
+	"""
+"#;
+
+const M5: &str = r#"
+t = """
+	text """
+"#;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+	Spawn(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+struct Bind {
+	bind: std::collections::HashMap<String, Action>,
+}
+
+#[test]
+fn multiline() {
+	let m1 = mayfig::from_str::<T>(M1).unwrap();
+	assert_eq!(
+		m1.t,
+		"#!/bin/sh\necho \"it's \"\" a \"\"\"\n\n\tindented\tä"
+	);
+	let r1 = mayfig::from_reader::<_, T>(M1.as_bytes()).unwrap();
+	assert_eq!(m1.t, r1.t);
+
+	let m2 = mayfig::from_str::<Bind>(M2).unwrap();
+	let spawn = Action::Spawn(vec!["sh -c 'echo \"\"\"'".to_owned()]);
+	assert_eq!(m2.bind["mod+t"], spawn);
+
+	let e3 = mayfig::from_str::<T>(M3).unwrap_err();
+	assert!(matches!(e3.code(), ErrorCode::ExpectedNewline('t')));
+
+	let e4 = mayfig::from_str::<T>(M4).unwrap_err();
+	assert!(matches!(e4.code(), ErrorCode::InsufficientIndent));
+	assert_eq!(e4.span(), Some(Span::Point(Position::new(4, 1, 15))));
+
+	let e5 = mayfig::from_str::<T>(M5).unwrap_err();
+	assert!(matches!(e5.code(), ErrorCode::ExpectedNewline('"')));
+	assert_eq!(e5.span(), Some(Span::Point(Position::new(3, 7, 15))));
+}
+
+const R1: &str = r#"
+t = `C:\Users\"may"\t`
+"#;
+
+const R2: &str = r#"
+t = `line
+	next`
+"#;
+
+#[test]
+fn raw() {
+	#[derive(Debug, Deserialize)]
+	struct B<'a> {
+		t: &'a str,
+	}
+
+	let r1 = mayfig::from_str::<B>(R1).unwrap();
+	assert_eq!(r1.t, r#"C:\Users\"may"\t"#);
+
+	let r2 = mayfig::from_str::<T>(R2).unwrap();
+	assert_eq!(r2.t, "line\n\tnext");
+	let i2 = mayfig::from_reader::<_, T>(R2.as_bytes()).unwrap();
+	assert_eq!(r2.t, i2.t);
+}
+
+#[test]
+fn ser_string_format() {
+	#[derive(Debug, serde::Serialize, Deserialize, PartialEq)]
+	struct S {
+		script: String,
+		path: String,
+		nested: std::collections::BTreeMap<String, Vec<String>>,
+	}
+
+	let s = S {
+		script: "#!/bin/sh\n\techo \"\"\"\n\n".to_owned(),
+		path: r"C:\Users".to_owned(),
+		nested: [("a\nb".to_owned(), vec!["x\ny".to_owned()])].into(),
+	};
+
+	let mut out = Vec::new();
+	let mut serializer = mayfig::Serializer::new(&mut out);
+	serializer.set_string_format(mayfig::ser::StringFormat::Auto);
+	serde::Serialize::serialize(&s, &mut serializer).unwrap();
+
+	let out = String::from_utf8(out).unwrap();
+	assert_eq!(
+		out,
+		"script = \"\"\"\n\t#!/bin/sh\n\t\techo \"\"\\\"\n\n\n\t\"\"\"\npath = `C:\\Users`\nnested {\n\t\"a\\nb\" = [ \"\"\"\n\t\tx\n\t\ty\n\t\t\"\"\" ]\n}\n"
+	);
+
+	let de = mayfig::from_str::<S>(&out).unwrap();
+	assert_eq!(de, s);
+}
@@ -78,18 +78,7 @@ fn char() {
 	assert!(matches!(e2.code(), ErrorCode::Custom(_)));
 	assert_eq!(
 		e2.span(),
-		Some(Span::Span(
-			Position {
-				line: 2,
-				col: 5,
-				index: 5
-			},
-			Position {
-				line: 2,
-				col: 9,
-				index: 9
-			}
-		))
+		Some(Span::Span(Position::new(2, 5, 5), Position::new(2, 9, 9)))
 	);
 
 	let e3 = mayfig::from_str::<V>(V3).unwrap_err();
@@ -183,6 +172,63 @@ fn f64() {
 	assert_eq!(f6.f, f64::NEG_INFINITY);
 }
 
+#[derive(Debug, Deserialize)]
+struct Int {
+	u: Vec<u32>,
+	i: Vec<i8>,
+	m: HashMap<u8, i64>,
+}
+
+const INT1: &str = r#"
+u = [ 0xff00ff 0o755 0b1010 1_000_000 0 0x_ff ]
+i = [ -0x80 -0b1 +0o17 -1_0 ]
+m {
+	0xff = -0x8000_0000_0000_0000
+}
+"#;
+
+const INT2: &str = r#"
+u = [ 0x1_0000_0000 ]
+i = []
+m {}
+"#;
+
+const INT3: &str = r#"
+u = []
+i = [ 0o8 ]
+m {}
+"#;
+
+const INT4: &str = r#"
+u = []
+i = []
+m {
+	0x100 = 0
+}
+"#;
+
+#[test]
+fn int() {
+	let i1 = mayfig::from_str::<Int>(INT1).unwrap();
+	assert_eq!(i1.u, [0xff00ff, 0o755, 0b1010, 1_000_000, 0, 0xff]);
+	assert_eq!(i1.i, [-0x80, -0b1, 0o17, -10]);
+	assert_eq!(i1.m[&0xff], i64::MIN);
+
+	let e2 = mayfig::from_str::<Int>(INT2).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::InvalidNum(n) if n == "0x1_0000_0000"));
+	assert_eq!(
+		e2.span(),
+		Some(Span::Span(Position::new(2, 7, 7), Position::new(2, 20, 20)))
+	);
+
+	let e3 = mayfig::from_str::<Int>(INT3).unwrap_err();
+	assert!(matches!(e3.code(), ErrorCode::InvalidNum(n) if n == "0o8"));
+
+	let e4 = mayfig::from_str::<Int>(INT4).unwrap_err();
+	assert!(matches!(e4.code(), ErrorCode::InvalidNum(n) if n == "0x100"));
+	assert_eq!(e4.span().map(|span| span.range()), Some(20..25));
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 enum Te {
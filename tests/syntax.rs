@@ -0,0 +1,188 @@
+use mayfig::{
+	error::ErrorCode,
+	syntax::{self, NodeKind, SyntaxNode, TokenKind},
+};
+
+const S1: &str = r#"# comment
+input {
+	keyboard {
+		# xkb-file = "~/.config/keymap/may.xkb"
+
+		repeat-delay = 600 # trailing
+		repeat-rate = 25
+	}
+}
+
+size = [ 24, 24 ]
+offsets = [
+	0 -.inf # comment
+	"str" 'str' `raw`
+]
+text = """
+	multi "line"
+	"""
+
+$mod = "super"
+bind {
+	$mod+q = "close"
+	mod+t = "spawn" [ "kitty" ]
+}
+
+windowrules {
+	class [ "kitty" ] {
+		opacity = 0.6
+	}
+}
+
+include "other.mf"
+"#;
+
+fn walk<'a>(node: &'a SyntaxNode, nodes: &mut Vec<&'a SyntaxNode>) {
+	nodes.push(node);
+	for child in node.nodes() {
+		walk(child, nodes);
+	}
+}
+
+#[test]
+fn lossless() {
+	let p1 = syntax::parse(S1);
+	assert!(p1.errors().is_empty(), "{:?}", p1.errors());
+	assert_eq!(p1.root().to_string(), S1);
+	assert_eq!(p1.root().span().range(), 0..S1.len());
+
+	let mut nodes = Vec::new();
+	walk(p1.root(), &mut nodes);
+	assert!(nodes.iter().all(|node| node.kind() != NodeKind::Error));
+
+	// every node spans exactly its text
+	for node in nodes {
+		assert_eq!(&S1[node.span().range()], node.to_string());
+		for token in node.tokens() {
+			assert_eq!(&S1[token.span().range()], token.text());
+		}
+	}
+}
+
+#[test]
+fn structure() {
+	let p1 = syntax::parse(S1);
+	let root = p1.root();
+
+	let entries = root.entries().collect::<Vec<_>>();
+	let kinds = entries.iter().map(|entry| entry.kind()).collect::<Vec<_>>();
+	assert_eq!(
+		kinds,
+		[
+			NodeKind::Category,
+			NodeKind::Pair,
+			NodeKind::Pair,
+			NodeKind::Pair,
+			NodeKind::Pair,
+			NodeKind::Category,
+			NodeKind::Category,
+			NodeKind::Include,
+		]
+	);
+
+	let keyboard = entries[0].entries().next().unwrap();
+	assert_eq!(keyboard.key().unwrap().to_string(), "keyboard");
+	let delay = keyboard.entries().next().unwrap();
+	assert_eq!(delay.to_string(), "repeat-delay = 600");
+	assert_eq!(delay.value().unwrap().kind(), NodeKind::Scalar);
+
+	let comments = root
+		.tokens()
+		.filter(|token| token.kind() == TokenKind::Comment)
+		.collect::<Vec<_>>();
+	assert_eq!(comments[0].text(), "# comment");
+	assert_eq!(comments[0].span().range(), 0..9);
+
+	let offsets = entries[2].value().unwrap();
+	assert_eq!(offsets.kind(), NodeKind::Seq);
+	assert_eq!(offsets.nodes().count(), 5);
+
+	let text = entries[3].value().unwrap();
+	assert_eq!(text.to_string(), "\"\"\"\n\tmulti \"line\"\n\t\"\"\"");
+
+	let bind = entries[5].entries().collect::<Vec<_>>();
+	assert_eq!(bind[0].key().unwrap().to_string(), "$mod+q");
+	let spawn = bind[1].value().unwrap();
+	assert_eq!(spawn.kind(), NodeKind::Tagged);
+	assert_eq!(spawn.nodes().next().unwrap().kind(), NodeKind::Seq);
+
+	let class = entries[6].entries().next().unwrap();
+	assert_eq!(class.kind(), NodeKind::Category);
+	let key = class.key().unwrap();
+	assert_eq!(key.to_string(), "class [ \"kitty\" ]");
+	assert_eq!(key.nodes().next().unwrap().kind(), NodeKind::Seq);
+}
+
+const E1: &str = r#"
+a = 1 2
+b =
+c "value"
+@d = 3
+e = [ 1 = ]
+"f = 4
+g {
+	h = 5
+"#;
+
+const E2: &str = r#"
+a {
+	b = 1 }
+}
+}
+"#;
+
+#[test]
+fn tolerant() {
+	let p1 = syntax::parse(E1);
+	assert_eq!(p1.root().to_string(), E1);
+
+	let codes = p1.errors().iter().map(|err| err.code()).collect::<Vec<_>>();
+	assert!(matches!(
+		codes[..],
+		[
+			ErrorCode::ExpectedNewline('2'),
+			ErrorCode::UnexpectedNewline,
+			ErrorCode::ExpectedValue('"'),
+			ErrorCode::ExpectedAsciiAlphabetic('@'),
+			ErrorCode::ExpectedSeqEnd('='),
+			ErrorCode::UnexpectedNewline,
+			ErrorCode::Eof,
+		]
+	));
+	assert_eq!(p1.errors()[0].span().unwrap().range(), 7..8);
+
+	// the valid entries are still parsed
+	let entries = p1.root().entries().collect::<Vec<_>>();
+	let keys = entries
+		.iter()
+		.filter_map(|entry| entry.key())
+		.map(|key| key.to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(keys, ["a", "b", "e", "g"]);
+	assert_eq!(entries[3].entries().count(), 1);
+
+	let p2 = syntax::parse(E2);
+	assert_eq!(p2.root().to_string(), E2);
+	assert!(matches!(
+		p2.errors().iter().map(|err| err.code()).collect::<Vec<_>>()[..],
+		[
+			ErrorCode::ExpectedAsciiAlphabetic('}'),
+			ErrorCode::ExpectedAsciiAlphabetic('}'),
+		]
+	));
+	assert_eq!(p2.errors()[0].span().unwrap().range(), 14..15);
+}
+
+#[test]
+fn empty() {
+	let p1 = syntax::parse("");
+	assert!(p1.errors().is_empty());
+	assert_eq!(p1.root().to_string(), "");
+	assert!(p1.root().children().is_empty());
+	assert_eq!(p1.root().span().range(), 0..0);
+}
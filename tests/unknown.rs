@@ -0,0 +1,115 @@
+use mayfig::error::{ErrorCode, Segment};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[expect(dead_code)]
+struct Keyboard {
+	repeat_rate: u32,
+	repeat_delay: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+	Spawn(String),
+	Close,
+	Workspace(u32),
+}
+
+#[derive(Debug, Deserialize)]
+#[expect(dead_code)]
+struct Config {
+	keyboard: Option<Keyboard>,
+	#[serde(default)]
+	bind: HashMap<String, Action>,
+	#[serde(default)]
+	rev: HashMap<Action, String>,
+}
+
+const U1: &str = r#"
+keyboard {
+	repeat-rte = 25
+}
+"#;
+
+const U2: &str = r#"
+bind {
+	mod+t = "spwan" [ "kitty" ]
+}
+"#;
+
+const U3: &str = r#"
+bind {
+	mod+q = "quit"
+}
+"#;
+
+const U4: &str = r#"
+rev {
+	clsoe = "mod+q"
+}
+"#;
+
+#[test]
+fn unknown_field() {
+	let e1 = mayfig::from_str::<Config>(U1).unwrap_err();
+	let ErrorCode::UnknownField(unknown) = e1.code() else {
+		panic!("expected unknown field, got {e1:?}");
+	};
+	assert_eq!(unknown.name, "repeat-rte");
+	assert_eq!(unknown.expected, &["repeat-rate", "repeat-delay"]);
+	assert_eq!(unknown.suggestion, Some("repeat-rate"));
+	assert_eq!(
+		e1.to_string(),
+		"unknown field `repeat-rte`, expected `repeat-rate` or `repeat-delay`; did you mean `repeat-rate`? at keyboard.repeat-rte, line 3, columns 2 to 12"
+	);
+	assert_eq!(
+		e1.path().unwrap().segments(),
+		&[
+			Segment::Key("keyboard".to_owned()),
+			Segment::Key("repeat-rte".to_owned())
+		]
+	);
+}
+
+#[test]
+fn unknown_variant() {
+	let e2 = mayfig::from_str::<Config>(U2).unwrap_err();
+	assert!(matches!(
+		e2.code(),
+		ErrorCode::UnknownVariant(unknown) if unknown.name == "spwan" && unknown.suggestion == Some("spawn")
+	));
+	assert_eq!(e2.span().unwrap().range(), 17..24);
+	assert_eq!(
+		e2.code().to_string(),
+		"unknown variant `spwan`, expected one of `spawn`, `close`, `workspace`; did you mean `spawn`?"
+	);
+
+	let e3 = mayfig::from_str::<Config>(U3).unwrap_err();
+	assert!(matches!(
+		e3.code(),
+		ErrorCode::UnknownVariant(unknown) if unknown.suggestion.is_none()
+	));
+	assert_eq!(
+		e3.code().to_string(),
+		"unknown variant `quit`, expected one of `spawn`, `close`, `workspace`"
+	);
+
+	let e4 = mayfig::from_str::<Config>(U4).unwrap_err();
+	assert!(matches!(
+		e4.code(),
+		ErrorCode::UnknownVariant(unknown) if unknown.suggestion == Some("close")
+	));
+	assert_eq!(e4.span().unwrap().range(), 8..13);
+
+	// the variants are the ones the enum was deserialized with
+	let ErrorCode::UnknownVariant(unknown) = e4.code() else {
+		panic!("expected unknown variant, got {e4:?}");
+	};
+	assert_eq!(unknown.expected, &["spawn", "close", "workspace"]);
+
+	let close = mayfig::from_str::<HashMap<String, Action>>("a = \"close\"").unwrap();
+	assert_eq!(close["a"], Action::Close);
+}
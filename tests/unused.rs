@@ -0,0 +1,86 @@
+use mayfig::{error::Segment, Deserializer};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+struct Keyboard {
+	repeat_rate: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Input {
+	keyboard: Keyboard,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+	Spawn(String),
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+	input: Input,
+	bind: HashMap<String, Action>,
+}
+
+const U1: &str = r#"
+input {
+	keyboard {
+		repeat-rate = 25
+		repeat-rte = 30
+		layout {
+			name = "us"
+			variant = [ "intl" ]
+		}
+	}
+	mouse = 1
+}
+
+bind {
+	mod+t = "spawn" [ "kitty" ]
+}
+
+output = [ "DP-1" ]
+"#;
+
+#[test]
+fn unused() {
+	let (u1, unused) = mayfig::from_str_with_unused::<Config>(U1).unwrap();
+	assert_eq!(u1, mayfig::from_str::<Config>(U1).unwrap());
+
+	let paths = unused
+		.iter()
+		.map(|key| key.path().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		paths,
+		[
+			"input.keyboard.repeat-rte",
+			"input.keyboard.layout",
+			"input.mouse",
+			"output",
+		]
+	);
+
+	assert_eq!(unused[0].span().range(), 42..52);
+	assert_eq!(unused[1].span().range(), 60..66);
+	assert_eq!(
+		unused[3].path().segments(),
+		&[Segment::Key("output".to_owned())]
+	);
+}
+
+#[test]
+fn no_unused() {
+	const U2: &str = "input { keyboard { repeat-rate = 1 } }\nbind {}\n";
+
+	let mut de = Deserializer::from_str(U2);
+	Config::deserialize(&mut de).unwrap();
+	assert!(de.unused_keys().is_empty());
+
+	let value =
+		mayfig::from_str_with_unused::<HashMap<String, HashMap<String, u32>>>("a { b = 1 }");
+	assert!(value.unwrap().1.is_empty());
+}
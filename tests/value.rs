@@ -1,4 +1,4 @@
-use mayfig::{value::Map, Value};
+use mayfig::{de::DuplicateKeys, error::ErrorCode, value::Map, Deserializer, Value};
 
 const V1: &str = r#"
 map {
@@ -58,6 +58,18 @@ fn value() {
 	]));
 	let t2 = mayfig::from_str::<Value>(TAG).unwrap();
 	assert_eq!(t1, t2);
+
+	let n1 = Value::Map(Map::from([(
+		Value::from("n"),
+		Value::Seq(vec![
+			Value::from(0xe),
+			Value::from(-0o17),
+			Value::from(1_000),
+			Value::try_from(1_000.5).unwrap(),
+		]),
+	)]));
+	let n2 = mayfig::from_str::<Value>("n = [ 0xe -0o17 1_000 1_000.5 ]").unwrap();
+	assert_eq!(n1, n2);
 }
 
 const IGNORED: &str = r#"
@@ -78,3 +90,100 @@ binds {
 fn ignored() {
 	let _ = mayfig::from_str::<serde::de::IgnoredAny>(IGNORED).unwrap();
 }
+
+const DUPLICATE: &str = r#"
+keyboard {
+	repeat-rate = 25
+	repeat-rate = 50
+}
+"#;
+
+#[test]
+fn duplicate() {
+	let d0 = mayfig::from_str::<Value>(DUPLICATE).unwrap();
+	let mut de = Deserializer::from_str(DUPLICATE);
+	de.set_duplicate_keys(DuplicateKeys::Error);
+	let e1 = <Value as serde::Deserialize>::deserialize(&mut de).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::DuplicateKey(_)));
+
+	let mut de = Deserializer::from_str(DUPLICATE);
+	de.set_duplicate_keys(DuplicateKeys::LastWins);
+	let d1 = <Value as serde::Deserialize>::deserialize(&mut de).unwrap();
+	assert_eq!(
+		d1,
+		mayfig::from_str::<Value>("keyboard { repeat-rate = 50 }").unwrap()
+	);
+	assert_eq!(d0, d1);
+}
+
+const MERGE: &str = r#"
+bind {
+	mod+q = "quit"
+	mod+t { spawn = "kitty" }
+}
+bind {
+	mod+n = "firefox"
+	mod+t { cwd = "~" }
+}
+"#;
+
+#[test]
+fn merge() {
+	let mut de = Deserializer::from_str(MERGE);
+	de.set_merge_categories(true);
+	let m1 = <Value as serde::Deserialize>::deserialize(&mut de).unwrap();
+
+	let merged = r#"
+bind {
+	mod+q = "quit"
+	mod+t {
+		spawn = "kitty"
+		cwd = "~"
+	}
+	mod+n = "firefox"
+}
+"#;
+	assert_eq!(m1, mayfig::from_str::<Value>(merged).unwrap());
+}
+
+const VARIABLE: &str = r#"
+$term = "kitty"
+$gaps = 10
+bind {
+	$mod = "super"
+	$mod+t = "spawn" [ $term ]
+}
+gaps = $gaps
+"#;
+
+#[test]
+fn variable() {
+	let v1 = mayfig::from_str::<Value>(VARIABLE).unwrap();
+	let substituted = r#"
+bind {
+	super+t = "spawn" [ "kitty" ]
+}
+gaps = 10
+"#;
+	assert_eq!(v1, mayfig::from_str::<Value>(substituted).unwrap());
+}
+
+#[test]
+fn format() {
+	let texts = [
+		V1,
+		VARIABLE,
+		"b = [ \"s\", [ ] ]\n",
+		"b = [ 's', [ 1 ], \"t\" [ 2 ], 3 ]\n",
+		"b = [\n\t\"s\"\n\t[ ]\n]\n",
+	];
+
+	for text in texts {
+		let formatted = mayfig::fmt::format(text).unwrap();
+		assert_eq!(
+			mayfig::from_str::<Value>(text).unwrap(),
+			mayfig::from_str::<Value>(&formatted).unwrap(),
+			"{formatted}"
+		);
+	}
+}
@@ -0,0 +1,97 @@
+use mayfig::error::ErrorCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+	Spawn(String),
+	Close,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct General {
+	gaps: u32,
+	border: f64,
+	animations: bool,
+	terminal: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+	general: General,
+	bind: HashMap<String, Action>,
+}
+
+const V1: &str = r#"
+$mod = "super"
+$term = "kitty"
+$gaps = 10
+
+general {
+	$border = 2.5
+	gaps = $gaps
+	border = $border
+	animations = $on
+	terminal = $term
+	$on = true
+}
+
+bind {
+	$mod = "alt"
+	$mod+q = "close"
+	$mod+$key = "spawn" [ $term ]
+	$key = "t"
+	ctrl+$key = "spawn" [ "firefox" ]
+}
+"#;
+
+#[test]
+fn variable() {
+	let e1 = mayfig::from_str::<Config>(V1).unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::UndefinedVariable(name) if name == "on"));
+	assert_eq!(e1.span().unwrap().range(), 115..118);
+	assert_eq!(e1.path().unwrap().to_string(), "general.animations");
+	assert_eq!(
+		e1.to_string(),
+		"undefined variable `$on` at general.animations, line 10, columns 15 to 18"
+	);
+
+	let v1 = V1.replace("\t$on = true\n", "").replace("= $on", "= true");
+	let e2 = mayfig::from_str::<Config>(&v1).unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::UndefinedVariable(name) if name == "key"));
+
+	let v1 = v1.replace("$mod+$key", "$mod+t");
+	let config = mayfig::from_str::<Config>(&v1).unwrap();
+	assert_eq!(
+		config.general,
+		General {
+			gaps: 10,
+			border: 2.5,
+			animations: true,
+			terminal: "kitty".to_owned(),
+		}
+	);
+
+	assert_eq!(config.bind.len(), 3);
+	assert_eq!(config.bind["alt+q"], Action::Close);
+	assert_eq!(config.bind["alt+t"], Action::Spawn("kitty".to_owned()));
+	assert_eq!(config.bind["ctrl+t"], Action::Spawn("firefox".to_owned()));
+
+	let r1 = mayfig::from_reader::<_, Config>(v1.as_bytes()).unwrap();
+	assert_eq!(r1, config);
+}
+
+#[test]
+fn variable_kind() {
+	let e1 = mayfig::from_str::<HashMap<String, u32>>("$n = \"10\"\nn = $n").unwrap_err();
+	assert!(matches!(e1.code(), ErrorCode::ExpectedNumeric('$')));
+	assert_eq!(e1.span().unwrap().range(), 14..16);
+
+	let e2 = mayfig::from_str::<HashMap<String, String>>("$s = 10\ns = $s").unwrap_err();
+	assert!(matches!(e2.code(), ErrorCode::ExpectedQuote('$')));
+
+	let m1 = mayfig::from_str::<HashMap<String, String>>("$a = \"x\"\n$b = $a\nb = $b").unwrap();
+	assert_eq!(m1["b"], "x");
+	assert_eq!(m1.len(), 1);
+}